@@ -0,0 +1,306 @@
+//! Benchmarks for the cache module, against the previous VecDeque-based LRU
+
+use coral_turbo_core::cache::LruCache;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rayon::prelude::*;
+
+/// The LRU cache as it was before sharding (src/cache.rs at 49c6e05, copied
+/// verbatim): one map plus a `VecDeque` of keys, with an O(n) scan to promote
+/// on every hit and a stats lock taken on every access
+#[allow(dead_code)]
+mod baseline {
+    use ahash::AHashMap;
+    use std::sync::RwLock;
+    use std::collections::VecDeque;
+
+    /// LRU cache entry with access tracking
+    #[derive(Debug, Clone)]
+    struct CacheEntry<V> {
+        value: V,
+        /// Hit count for analytics (reserved for future cache statistics)
+        #[allow(dead_code)]
+        hits: u64,
+    }
+
+    /// Thread-safe LRU cache with configurable capacity
+    pub struct LruCache<K, V> {
+        /// Storage for cached values
+        storage: RwLock<AHashMap<K, CacheEntry<V>>>,
+
+        /// Order of keys for LRU eviction
+        order: RwLock<VecDeque<K>>,
+
+        /// Maximum number of entries
+        capacity: usize,
+
+        /// Cache statistics
+        stats: RwLock<CacheStats>,
+    }
+
+    /// Cache statistics
+    #[derive(Debug, Default, Clone)]
+    pub struct CacheStats {
+        pub hits: u64,
+        pub misses: u64,
+        pub evictions: u64,
+        pub insertions: u64,
+    }
+
+    impl CacheStats {
+        /// Calculate hit rate as percentage
+        pub fn hit_rate(&self) -> f64 {
+            let total = self.hits + self.misses;
+            if total == 0 {
+                0.0
+            } else {
+                (self.hits as f64 / total as f64) * 100.0
+            }
+        }
+    }
+
+    impl<K, V> LruCache<K, V>
+    where
+        K: std::hash::Hash + Eq + Clone,
+        V: Clone,
+    {
+        /// Create a new LRU cache with the given capacity
+        pub fn new(capacity: usize) -> Self {
+            Self {
+                storage: RwLock::new(AHashMap::with_capacity(capacity)),
+                order: RwLock::new(VecDeque::with_capacity(capacity)),
+                capacity,
+                stats: RwLock::new(CacheStats::default()),
+            }
+        }
+
+        /// Get a value from the cache
+        pub fn get(&self, key: &K) -> Option<V> {
+            let storage = self.storage.read().unwrap();
+
+            if let Some(entry) = storage.get(key) {
+                // Update stats
+                if let Ok(mut stats) = self.stats.write() {
+                    stats.hits += 1;
+                }
+
+                // Update LRU order (move to back)
+                if let Ok(mut order) = self.order.write() {
+                    if let Some(pos) = order.iter().position(|k| k == key) {
+                        order.remove(pos);
+                        order.push_back(key.clone());
+                    }
+                }
+
+                Some(entry.value.clone())
+            } else {
+                if let Ok(mut stats) = self.stats.write() {
+                    stats.misses += 1;
+                }
+                None
+            }
+        }
+
+        /// Insert a value into the cache
+        pub fn insert(&self, key: K, value: V) {
+            let mut storage = self.storage.write().unwrap();
+            let mut order = self.order.write().unwrap();
+
+            // Check if key already exists
+            if storage.contains_key(&key) {
+                // Update existing entry
+                storage.insert(
+                    key.clone(),
+                    CacheEntry { value, hits: 1 },
+                );
+
+                // Move to back of LRU queue
+                if let Some(pos) = order.iter().position(|k| k == &key) {
+                    order.remove(pos);
+                    order.push_back(key);
+                }
+            } else {
+                // Evict if at capacity
+                while storage.len() >= self.capacity {
+                    if let Some(evict_key) = order.pop_front() {
+                        storage.remove(&evict_key);
+                        if let Ok(mut stats) = self.stats.write() {
+                            stats.evictions += 1;
+                        }
+                    } else {
+                        break;
+                    }
+                }
+
+                // Insert new entry
+                storage.insert(key.clone(), CacheEntry { value, hits: 1 });
+                order.push_back(key);
+
+                if let Ok(mut stats) = self.stats.write() {
+                    stats.insertions += 1;
+                }
+            }
+        }
+
+        /// Remove a value from the cache
+        pub fn remove(&self, key: &K) -> Option<V> {
+            let mut storage = self.storage.write().unwrap();
+            let mut order = self.order.write().unwrap();
+
+            if let Some(entry) = storage.remove(key) {
+                if let Some(pos) = order.iter().position(|k| k == key) {
+                    order.remove(pos);
+                }
+                Some(entry.value)
+            } else {
+                None
+            }
+        }
+
+        /// Clear all entries from the cache
+        pub fn clear(&self) {
+            let mut storage = self.storage.write().unwrap();
+            let mut order = self.order.write().unwrap();
+
+            storage.clear();
+            order.clear();
+        }
+
+        /// Get the number of entries in the cache
+        pub fn len(&self) -> usize {
+            self.storage.read().unwrap().len()
+        }
+
+        /// Check if the cache is empty
+        pub fn is_empty(&self) -> bool {
+            self.storage.read().unwrap().is_empty()
+        }
+
+        /// Get cache statistics
+        pub fn stats(&self) -> CacheStats {
+            self.stats.read().unwrap().clone()
+        }
+
+        /// Get the cache capacity
+        pub fn capacity(&self) -> usize {
+            self.capacity
+        }
+
+        /// Check if the cache contains a key
+        pub fn contains(&self, key: &K) -> bool {
+            self.storage.read().unwrap().contains_key(key)
+        }
+    }
+
+    impl<K, V> std::fmt::Debug for LruCache<K, V>
+    where
+        K: std::fmt::Debug + std::hash::Hash + Eq + Clone,
+        V: std::fmt::Debug + Clone,
+    {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("LruCache")
+                .field("capacity", &self.capacity)
+                .field("len", &self.len())
+                .field("stats", &self.stats())
+                .finish()
+        }
+    }
+}
+
+const CAPACITY: usize = 10_000;
+
+fn keys(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("hover:bg-red-{}", i)).collect()
+}
+
+fn cache_get_hit(c: &mut Criterion) {
+    let keys = keys(CAPACITY);
+
+    let sharded: LruCache<String, usize> = LruCache::new(CAPACITY);
+    let previous = baseline::LruCache::new(CAPACITY);
+    for (i, key) in keys.iter().enumerate() {
+        sharded.insert(key.clone(), i);
+        previous.insert(key.clone(), i);
+    }
+
+    // Every 10th key, so hits land all over the recency list
+    let lookups: Vec<&String> = keys.iter().step_by(10).collect();
+
+    let mut group = c.benchmark_group("cache_get_hit");
+    group.throughput(Throughput::Elements(lookups.len() as u64));
+
+    group.bench_function(BenchmarkId::new("sharded", CAPACITY), |b| {
+        b.iter(|| {
+            for key in &lookups {
+                black_box(sharded.get(key));
+            }
+        })
+    });
+
+    group.bench_function(BenchmarkId::new("baseline", CAPACITY), |b| {
+        b.iter(|| {
+            for key in &lookups {
+                black_box(previous.get(key));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+fn cache_insert_evict(c: &mut Criterion) {
+    let keys = keys(CAPACITY * 2);
+
+    let mut group = c.benchmark_group("cache_insert_evict");
+    group.throughput(Throughput::Elements(keys.len() as u64));
+
+    group.bench_function(BenchmarkId::new("sharded", CAPACITY), |b| {
+        b.iter(|| {
+            let cache: LruCache<String, usize> = LruCache::new(CAPACITY);
+            for (i, key) in keys.iter().enumerate() {
+                cache.insert(key.clone(), i);
+            }
+            black_box(cache.len())
+        })
+    });
+
+    group.bench_function(BenchmarkId::new("baseline", CAPACITY), |b| {
+        b.iter(|| {
+            let cache = baseline::LruCache::new(CAPACITY);
+            for (i, key) in keys.iter().enumerate() {
+                cache.insert(key.clone(), i);
+            }
+        })
+    });
+
+    group.finish();
+}
+
+fn cache_parallel_get(c: &mut Criterion) {
+    let keys = keys(CAPACITY);
+
+    let sharded: LruCache<String, usize> = LruCache::new(CAPACITY);
+    let previous = baseline::LruCache::new(CAPACITY);
+    for (i, key) in keys.iter().enumerate() {
+        sharded.insert(key.clone(), i);
+        previous.insert(key.clone(), i);
+    }
+
+    let lookups: Vec<&String> = keys.iter().step_by(10).collect();
+
+    let mut group = c.benchmark_group("cache_parallel_get");
+    group.throughput(Throughput::Elements(lookups.len() as u64));
+
+    group.bench_function(BenchmarkId::new("sharded", CAPACITY), |b| {
+        b.iter(|| lookups.par_iter().filter(|key| sharded.get(key).is_some()).count())
+    });
+
+    group.bench_function(BenchmarkId::new("baseline", CAPACITY), |b| {
+        b.iter(|| lookups.par_iter().filter(|key| previous.get(key).is_some()).count())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, cache_get_hit, cache_insert_evict, cache_parallel_get);
+
+criterion_main!(benches);
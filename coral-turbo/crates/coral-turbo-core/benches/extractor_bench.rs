@@ -1,7 +1,9 @@
 //! Benchmarks for the extractor module
 
+use coral_turbo_core::scanner::Scanner;
 use coral_turbo_core::Extractor;
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use regex::Regex;
 
 fn extract_html(c: &mut Criterion) {
     let extractor = Extractor::new();
@@ -69,6 +71,61 @@ fn extract_large_file(c: &mut Criterion) {
     group.finish();
 }
 
+fn scan_vs_regex(c: &mut Criterion) {
+    // The context patterns the extractor matched before the scanner
+    let patterns: Vec<Regex> = [
+        r#"class\s*=\s*["']([^"']+)["']"#,
+        r#"className\s*=\s*["']([^"']+)["']"#,
+        r#"className\s*=\s*\{["'`]([^"'`]+)["'`]\}"#,
+        r#":class\s*=\s*["']([^"']+)["']"#,
+        r#"class:([a-zA-Z0-9_\-:]+)"#,
+        r#"`([^`]*(?:hover:|dark:|md:|lg:|xl:|sm:|focus:|active:|group-|peer-)[^`]*)`"#,
+        r#"(?:clsx|classnames|cn|twMerge|cva)\s*\(\s*["'`]([^"'`]+)["'`]"#,
+        r#"['"]([a-zA-Z0-9_\-:\/\[\]]+)['"]:\s*(?:true|false|\w+)"#,
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).unwrap())
+    .collect();
+
+    let mut content = String::new();
+    for i in 0..100 {
+        content.push_str(&format!(
+            r#"<div className="flex items-center p-{} hover:bg-gray-{} md:(px-4 py-2)">
+                <span className={{cn("text-sm", active && "font-bold")}}>Item {}</span>
+            </div>
+            "#,
+            i % 12,
+            (i % 9 + 1) * 100,
+            i
+        ));
+    }
+
+    let mut group = c.benchmark_group("scan");
+    group.throughput(Throughput::Bytes(content.len() as u64));
+
+    group.bench_function("scanner", |b| {
+        b.iter(|| Scanner::new(black_box(&content)).count())
+    });
+
+    group.bench_function("regex_patterns", |b| {
+        b.iter(|| {
+            patterns
+                .iter()
+                .map(|pattern| pattern.captures_iter(black_box(&content)).count())
+                .sum::<usize>()
+        })
+    });
+
+    // Unclosed brackets and groups must not rescan the rest of the input
+    let unbalanced = format!("{} p-4", "[".repeat(50_000));
+    group.throughput(Throughput::Bytes(unbalanced.len() as u64));
+    group.bench_function("scanner_unbalanced", |b| {
+        b.iter(|| Scanner::new(black_box(&unbalanced)).count())
+    });
+
+    group.finish();
+}
+
 fn extract_parallel(c: &mut Criterion) {
     let extractor = Extractor::new();
 
@@ -116,6 +173,7 @@ criterion_group!(
     extract_jsx,
     extract_clsx,
     extract_large_file,
+    scan_vs_regex,
     extract_parallel,
     extract_arbitrary_values,
 );
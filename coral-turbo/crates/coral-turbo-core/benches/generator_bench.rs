@@ -1,6 +1,6 @@
 //! Benchmarks for the generator module
 
-use coral_turbo_core::{Generator, GenerateOptions, Layer, MatchResult, ParsedClass, CSSProperty};
+use coral_turbo_core::{ClassKind, Generator, GenerateOptions, Layer, MatchResult, ParsedClass, CSSProperty};
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 
 fn create_match_result(raw: &str, utility: &str, value: Option<&str>, properties: Vec<(&str, &str)>) -> MatchResult {
@@ -14,6 +14,9 @@ fn create_match_result(raw: &str, utility: &str, value: Option<&str>, properties
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         },
         properties: properties
             .into_iter()
@@ -25,6 +28,8 @@ fn create_match_result(raw: &str, utility: &str, value: Option<&str>, properties
         pattern_name: format!("{}-{}", utility, value.unwrap_or("default")),
         layer: Layer::Utilities,
         sort_order: 0,
+        selector_suffix: None,
+        extra_rules: Vec::new(),
     }
 }
 
@@ -87,6 +92,7 @@ fn generate_minified(c: &mut Criterion) {
         source_comments: false,
         sort_by_property: false,
         use_layers: true,
+        ..Default::default()
     });
 
     let results: Vec<MatchResult> = (0..50)
@@ -133,6 +139,14 @@ fn generate_large(c: &mut Criterion) {
         b.iter(|| generator.generate(black_box(&results)))
     });
 
+    group.bench_function("write_1000_rules_to_buffer", |b| {
+        let mut buffer = Vec::with_capacity(64 * 1024);
+        b.iter(|| {
+            buffer.clear();
+            generator.write_to(black_box(&results), &mut buffer).unwrap();
+        })
+    });
+
     group.finish();
 }
 
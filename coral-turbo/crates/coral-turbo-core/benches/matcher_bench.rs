@@ -1,6 +1,6 @@
 //! Benchmarks for the matcher module
 
-use coral_turbo_core::{Matcher, ParsedClass};
+use coral_turbo_core::{ClassKind, Matcher, ParsedClass};
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 
 fn create_parsed_class(raw: &str, utility: &str, value: Option<&str>) -> ParsedClass {
@@ -13,6 +13,9 @@ fn create_parsed_class(raw: &str, utility: &str, value: Option<&str>) -> ParsedC
         arbitrary: None,
         important: false,
         negative: false,
+        source: None,
+        diagnostics: Vec::new(),
+        kind: ClassKind::Utility,
     }
 }
 
@@ -32,6 +32,7 @@ fn main() {
         source_comments: false,
         sort_by_property: true,
         use_layers: true,
+        ..Default::default()
     });
 
     let total_start = Instant::now();
@@ -112,6 +113,7 @@ fn main() {
         source_comments: false,
         sort_by_property: false,
         use_layers: true,
+        ..Default::default()
     });
     let minified = minified_generator.generate(&match_results);
     println!("Minified size: {} bytes ({}% reduction)",
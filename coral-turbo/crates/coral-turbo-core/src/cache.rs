@@ -1,37 +1,30 @@
 //! High-performance caching for parsed classes and match results
 //!
-//! Uses a concurrent LRU cache with configurable size limits.
+//! A sharded LRU cache: keys hash to one of several independently locked
+//! shards, and each shard keeps its entries in an intrusive doubly-linked list
+//! over a slab, so lookups, promotions and evictions are all O(1). Capacity can
+//! be bounded by entry count and, optionally, by estimated memory.
 
-use ahash::AHashMap;
-use std::sync::RwLock;
-use std::collections::VecDeque;
+use ahash::{AHashMap, RandomState};
+use serde::{Deserialize, Serialize};
+use std::hash::Hash;
+use std::sync::{Mutex, MutexGuard};
 
-/// LRU cache entry with access tracking
-#[derive(Debug, Clone)]
-struct CacheEntry<V> {
-    value: V,
-    /// Hit count for analytics (reserved for future cache statistics)
-    #[allow(dead_code)]
-    hits: u64,
-}
+/// Sentinel index for "no node" in a shard's linked list
+const NIL: usize = usize::MAX;
 
-/// Thread-safe LRU cache with configurable capacity
-pub struct LruCache<K, V> {
-    /// Storage for cached values
-    storage: RwLock<AHashMap<K, CacheEntry<V>>>,
+/// Shards are only added once each can hold at least this many entries, so
+/// small caches keep exact LRU order
+const MIN_SHARD_CAPACITY: usize = 256;
 
-    /// Order of keys for LRU eviction
-    order: RwLock<VecDeque<K>>,
+/// Upper bound on the number of shards
+const MAX_SHARDS: usize = 16;
 
-    /// Maximum number of entries
-    capacity: usize,
-
-    /// Cache statistics
-    stats: RwLock<CacheStats>,
-}
+/// Estimates the memory held by one entry, in bytes
+pub type Weigher<K, V> = Box<dyn Fn(&K, &V) -> usize + Send + Sync>;
 
 /// Cache statistics
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CacheStats {
     pub hits: u64,
     pub misses: u64,
@@ -49,6 +42,174 @@ impl CacheStats {
             (self.hits as f64 / total as f64) * 100.0
         }
     }
+
+    fn merge(&mut self, other: &CacheStats) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.evictions += other.evictions;
+        self.insertions += other.insertions;
+    }
+}
+
+/// Linked-list node holding one entry
+struct Node<K, V> {
+    key: K,
+    value: V,
+    /// Number of `get` hits since insertion
+    hits: u64,
+    /// Estimated size in bytes (0 without a memory limit)
+    weight: usize,
+    prev: usize,
+    next: usize,
+}
+
+/// One independently locked part of the cache
+struct Shard<K, V> {
+    /// Key to slab index
+    map: AHashMap<K, usize>,
+
+    /// Slab of nodes; removal swaps the last node into the hole
+    nodes: Vec<Node<K, V>>,
+
+    /// Most recently used node
+    head: usize,
+
+    /// Least recently used node
+    tail: usize,
+
+    /// Maximum number of entries
+    capacity: usize,
+
+    /// Maximum total weight
+    max_bytes: usize,
+
+    /// Current total weight
+    bytes: usize,
+
+    stats: CacheStats,
+}
+
+impl<K, V> Shard<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    fn new(capacity: usize, max_bytes: usize) -> Self {
+        Self {
+            map: AHashMap::with_capacity(capacity.min(1024)),
+            nodes: Vec::with_capacity(capacity.min(1024)),
+            head: NIL,
+            tail: NIL,
+            capacity,
+            max_bytes,
+            bytes: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Detach a node from the list, leaving it in the slab
+    fn unlink(&mut self, idx: usize) {
+        let (prev, next) = (self.nodes[idx].prev, self.nodes[idx].next);
+
+        if prev == NIL {
+            self.head = next;
+        } else {
+            self.nodes[prev].next = next;
+        }
+
+        if next == NIL {
+            self.tail = prev;
+        } else {
+            self.nodes[next].prev = prev;
+        }
+    }
+
+    /// Attach a detached node as the most recently used
+    fn push_front(&mut self, idx: usize) {
+        self.nodes[idx].prev = NIL;
+        self.nodes[idx].next = self.head;
+
+        if self.head == NIL {
+            self.tail = idx;
+        } else {
+            self.nodes[self.head].prev = idx;
+        }
+        self.head = idx;
+    }
+
+    /// Mark a node as the most recently used
+    fn touch(&mut self, idx: usize) {
+        if self.head != idx {
+            self.unlink(idx);
+            self.push_front(idx);
+        }
+    }
+
+    /// Remove the node at `idx` from the list, slab and map
+    fn remove_at(&mut self, idx: usize) -> Node<K, V> {
+        self.unlink(idx);
+        let node = self.nodes.swap_remove(idx);
+        self.map.remove(&node.key);
+        self.bytes -= node.weight;
+
+        // The former last node now lives at `idx`; repoint its neighbors
+        if idx < self.nodes.len() {
+            let (prev, next) = (self.nodes[idx].prev, self.nodes[idx].next);
+
+            if prev == NIL {
+                self.head = idx;
+            } else {
+                self.nodes[prev].next = idx;
+            }
+
+            if next == NIL {
+                self.tail = idx;
+            } else {
+                self.nodes[next].prev = idx;
+            }
+
+            if let Some(slot) = self.map.get_mut(&self.nodes[idx].key) {
+                *slot = idx;
+            }
+        }
+
+        node
+    }
+
+    /// Evict least recently used entries until one of `weight` fits
+    fn make_room(&mut self, weight: usize) {
+        while self.tail != NIL
+            && (self.nodes.len() >= self.capacity || self.bytes + weight > self.max_bytes)
+        {
+            self.remove_at(self.tail);
+            self.stats.evictions += 1;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.nodes.clear();
+        self.head = NIL;
+        self.tail = NIL;
+        self.bytes = 0;
+    }
+}
+
+/// Thread-safe sharded LRU cache with configurable capacity
+pub struct LruCache<K, V> {
+    /// Independently locked shards; the count is a power of two
+    shards: Box<[Mutex<Shard<K, V>>]>,
+
+    /// Hasher used to pick a shard
+    hasher: RandomState,
+
+    /// Maximum number of entries
+    capacity: usize,
+
+    /// Maximum estimated memory, if bounded
+    max_bytes: Option<usize>,
+
+    /// Entry size estimate, used only with a memory limit
+    weigher: Option<Weigher<K, V>>,
 }
 
 impl<K, V> LruCache<K, V>
@@ -58,119 +219,141 @@ where
 {
     /// Create a new LRU cache with the given capacity
     pub fn new(capacity: usize) -> Self {
+        Self::build(capacity, None, None)
+    }
+
+    /// Create a cache bounded by both entry count and estimated memory
+    ///
+    /// `weigher` estimates the bytes held by an entry (e.g., string lengths
+    /// plus `size_of` the value). Entries heavier than a shard's share of
+    /// `max_bytes` are not cached.
+    pub fn with_memory_limit(
+        capacity: usize,
+        max_bytes: usize,
+        weigher: impl Fn(&K, &V) -> usize + Send + Sync + 'static,
+    ) -> Self {
+        Self::build(capacity, Some(max_bytes), Some(Box::new(weigher)))
+    }
+
+    fn build(capacity: usize, max_bytes: Option<usize>, weigher: Option<Weigher<K, V>>) -> Self {
+        let capacity = capacity.max(1);
+        let shard_count = prev_power_of_two((capacity / MIN_SHARD_CAPACITY).clamp(1, MAX_SHARDS));
+
+        // Split both limits evenly, rounding up so the totals are reachable.
+        // A skewed key distribution can therefore evict before `capacity`.
+        let shard_capacity = capacity.div_ceil(shard_count);
+        let shard_bytes = max_bytes.map_or(usize::MAX, |bytes| bytes.div_ceil(shard_count));
+
+        let shards = (0..shard_count)
+            .map(|_| Mutex::new(Shard::new(shard_capacity, shard_bytes)))
+            .collect();
+
         Self {
-            storage: RwLock::new(AHashMap::with_capacity(capacity)),
-            order: RwLock::new(VecDeque::with_capacity(capacity)),
+            shards,
+            hasher: RandomState::new(),
             capacity,
-            stats: RwLock::new(CacheStats::default()),
+            max_bytes,
+            weigher,
         }
     }
 
+    /// Lock the shard owning `key`
+    fn shard(&self, key: &K) -> MutexGuard<'_, Shard<K, V>> {
+        let idx = self.hasher.hash_one(key) as usize & (self.shards.len() - 1);
+        self.shards[idx].lock().unwrap()
+    }
+
     /// Get a value from the cache
     pub fn get(&self, key: &K) -> Option<V> {
-        let storage = self.storage.read().unwrap();
-
-        if let Some(entry) = storage.get(key) {
-            // Update stats
-            if let Ok(mut stats) = self.stats.write() {
-                stats.hits += 1;
+        let mut shard = self.shard(key);
+
+        match shard.map.get(key).copied() {
+            Some(idx) => {
+                shard.stats.hits += 1;
+                shard.nodes[idx].hits += 1;
+                shard.touch(idx);
+                Some(shard.nodes[idx].value.clone())
             }
-
-            // Update LRU order (move to back)
-            if let Ok(mut order) = self.order.write() {
-                if let Some(pos) = order.iter().position(|k| k == key) {
-                    order.remove(pos);
-                    order.push_back(key.clone());
-                }
+            None => {
+                shard.stats.misses += 1;
+                None
             }
-
-            Some(entry.value.clone())
-        } else {
-            if let Ok(mut stats) = self.stats.write() {
-                stats.misses += 1;
-            }
-            None
         }
     }
 
     /// Insert a value into the cache
     pub fn insert(&self, key: K, value: V) {
-        let mut storage = self.storage.write().unwrap();
-        let mut order = self.order.write().unwrap();
+        let weight = self.weigher.as_ref().map_or(0, |weigh| weigh(&key, &value));
+        let mut shard = self.shard(&key);
 
         // Check if key already exists
-        if storage.contains_key(&key) {
-            // Update existing entry
-            storage.insert(
-                key.clone(),
-                CacheEntry { value, hits: 1 },
-            );
-
-            // Move to back of LRU queue
-            if let Some(pos) = order.iter().position(|k| k == &key) {
-                order.remove(pos);
-                order.push_back(key);
-            }
-        } else {
-            // Evict if at capacity
-            while storage.len() >= self.capacity {
-                if let Some(evict_key) = order.pop_front() {
-                    storage.remove(&evict_key);
-                    if let Ok(mut stats) = self.stats.write() {
-                        stats.evictions += 1;
-                    }
-                } else {
-                    break;
-                }
+        if let Some(idx) = shard.map.get(&key).copied() {
+            // Replace the value in place, then trim in case it grew
+            let old_weight = std::mem::replace(&mut shard.nodes[idx].weight, weight);
+            shard.nodes[idx].value = value;
+            shard.bytes = shard.bytes - old_weight + weight;
+            shard.touch(idx);
+
+            while shard.bytes > shard.max_bytes && shard.tail != idx {
+                let tail = shard.tail;
+                shard.remove_at(tail);
+                shard.stats.evictions += 1;
             }
+            return;
+        }
 
-            // Insert new entry
-            storage.insert(key.clone(), CacheEntry { value, hits: 1 });
-            order.push_back(key);
-
-            if let Ok(mut stats) = self.stats.write() {
-                stats.insertions += 1;
-            }
+        if weight > shard.max_bytes {
+            return;
         }
+
+        shard.make_room(weight);
+
+        let idx = shard.nodes.len();
+        shard.nodes.push(Node {
+            key: key.clone(),
+            value,
+            hits: 0,
+            weight,
+            prev: NIL,
+            next: NIL,
+        });
+        shard.map.insert(key, idx);
+        shard.push_front(idx);
+        shard.bytes += weight;
+        shard.stats.insertions += 1;
     }
 
     /// Remove a value from the cache
     pub fn remove(&self, key: &K) -> Option<V> {
-        let mut storage = self.storage.write().unwrap();
-        let mut order = self.order.write().unwrap();
-
-        if let Some(entry) = storage.remove(key) {
-            if let Some(pos) = order.iter().position(|k| k == key) {
-                order.remove(pos);
-            }
-            Some(entry.value)
-        } else {
-            None
-        }
+        let mut shard = self.shard(key);
+        let idx = shard.map.get(key).copied()?;
+        Some(shard.remove_at(idx).value)
     }
 
     /// Clear all entries from the cache
     pub fn clear(&self) {
-        let mut storage = self.storage.write().unwrap();
-        let mut order = self.order.write().unwrap();
-
-        storage.clear();
-        order.clear();
+        for shard in self.shards.iter() {
+            shard.lock().unwrap().clear();
+        }
     }
 
     /// Get the number of entries in the cache
     pub fn len(&self) -> usize {
-        self.storage.read().unwrap().len()
+        self.shards.iter().map(|s| s.lock().unwrap().nodes.len()).sum()
     }
 
     /// Check if the cache is empty
     pub fn is_empty(&self) -> bool {
-        self.storage.read().unwrap().is_empty()
+        self.shards.iter().all(|s| s.lock().unwrap().nodes.is_empty())
     }
 
     /// Get cache statistics
     pub fn stats(&self) -> CacheStats {
-        self.stats.read().unwrap().clone()
+        let mut stats = CacheStats::default();
+        for shard in self.shards.iter() {
+            stats.merge(&shard.lock().unwrap().stats);
+        }
+        stats
     }
 
     /// Get the cache capacity
@@ -178,12 +361,34 @@ where
         self.capacity
     }
 
+    /// Memory limit in bytes, if the cache is memory-bounded
+    pub fn max_bytes(&self) -> Option<usize> {
+        self.max_bytes
+    }
+
+    /// Estimated memory held by all entries (0 without a memory limit)
+    pub fn memory_usage(&self) -> usize {
+        self.shards.iter().map(|s| s.lock().unwrap().bytes).sum()
+    }
+
+    /// Number of hits recorded for an entry, without counting as an access
+    pub fn entry_hits(&self, key: &K) -> Option<u64> {
+        let shard = self.shard(key);
+        let idx = *shard.map.get(key)?;
+        Some(shard.nodes[idx].hits)
+    }
+
     /// Check if the cache contains a key
     pub fn contains(&self, key: &K) -> bool {
-        self.storage.read().unwrap().contains_key(key)
+        self.shard(key).map.contains_key(key)
     }
 }
 
+/// Largest power of two not above `n` (`n` must be non-zero)
+fn prev_power_of_two(n: usize) -> usize {
+    1 << (usize::BITS - 1 - n.leading_zeros())
+}
+
 impl<K, V> std::fmt::Debug for LruCache<K, V>
 where
     K: std::fmt::Debug + std::hash::Hash + Eq + Clone,
@@ -192,19 +397,22 @@ where
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("LruCache")
             .field("capacity", &self.capacity)
+            .field("shards", &self.shards.len())
             .field("len", &self.len())
             .field("stats", &self.stats())
             .finish()
     }
 }
 
-/// Global cache for parsed classes
-pub static PARSE_CACHE: once_cell::sync::Lazy<LruCache<String, crate::types::ParsedClass>> =
-    once_cell::sync::Lazy::new(|| LruCache::new(10000));
+/// Statistics for the parse and match caches of one engine
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EngineCacheStats {
+    /// Parsed classes cache
+    pub parse: CacheStats,
 
-/// Global cache for match results
-pub static MATCH_CACHE: once_cell::sync::Lazy<LruCache<String, crate::types::MatchResult>> =
-    once_cell::sync::Lazy::new(|| LruCache::new(10000));
+    /// Match results cache
+    pub matcher: CacheStats,
+}
 
 #[cfg(test)]
 mod tests {
@@ -288,4 +496,90 @@ mod tests {
 
         assert!(cache.is_empty());
     }
+
+    #[test]
+    fn test_cache_entry_hits() {
+        let cache: LruCache<i32, i32> = LruCache::new(10);
+
+        cache.insert(1, 100);
+        cache.get(&1);
+        cache.get(&1);
+
+        assert_eq!(cache.entry_hits(&1), Some(2));
+        assert_eq!(cache.entry_hits(&2), None);
+
+        // Reading hits is not an access
+        assert_eq!(cache.stats().hits, 2);
+    }
+
+    #[test]
+    fn test_cache_remove_keeps_order() {
+        let cache: LruCache<i32, i32> = LruCache::new(3);
+
+        cache.insert(1, 100);
+        cache.insert(2, 200);
+        cache.insert(3, 300);
+        cache.remove(&1);
+
+        // Slots were compacted; the list must still evict 2 before 3
+        cache.insert(4, 400);
+        cache.insert(5, 500);
+
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&3), Some(300));
+        assert_eq!(cache.get(&5), Some(500));
+    }
+
+    #[test]
+    fn test_cache_update_existing() {
+        let cache: LruCache<i32, i32> = LruCache::new(2);
+
+        cache.insert(1, 100);
+        cache.insert(2, 200);
+        cache.insert(1, 111); // Refreshes 1
+        cache.insert(3, 300); // Evicts 2
+
+        assert_eq!(cache.get(&1), Some(111));
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.stats().insertions, 3);
+    }
+
+    #[test]
+    fn test_cache_memory_limit() {
+        let cache: LruCache<String, String> =
+            LruCache::with_memory_limit(100, 20, |k: &String, v: &String| k.len() + v.len());
+
+        cache.insert("a".to_string(), "123456789".to_string()); // 10 bytes
+        cache.insert("b".to_string(), "123456789".to_string()); // 20 bytes
+        cache.insert("c".to_string(), "123456789".to_string()); // Evicts "a"
+
+        assert_eq!(cache.get(&"a".to_string()), None);
+        assert!(cache.contains(&"c".to_string()));
+        assert_eq!(cache.memory_usage(), 20);
+        assert_eq!(cache.stats().evictions, 1);
+
+        // Too large to ever fit
+        cache.insert("d".to_string(), "x".repeat(50));
+        assert!(!cache.contains(&"d".to_string()));
+    }
+
+    #[test]
+    fn test_cache_sharded_concurrent() {
+        use rayon::prelude::*;
+
+        // Room to spare: each shard holds an even share of the capacity
+        let cache: LruCache<u32, u32> = LruCache::new(8192);
+        assert!(cache.shards.len() > 1);
+
+        (0..4096u32).into_par_iter().for_each(|i| cache.insert(i, i * 2));
+        assert_eq!(cache.len(), 4096);
+
+        let found = (0..4096u32)
+            .into_par_iter()
+            .filter(|i| cache.get(i) == Some(i * 2))
+            .count();
+        assert_eq!(found, 4096);
+        assert_eq!(cache.stats().hits, 4096);
+    }
 }
@@ -0,0 +1,370 @@
+//! Content scanning on disk
+//!
+//! Walks a root directory honoring `.gitignore`, selects files by include
+//! and exclude globs, skips binary and oversized files, and extracts class
+//! names from the rest in parallel.
+
+use crate::extractor::Extractor;
+use crate::types::{ContentConfig, ExtractionResult};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use rayon::prelude::*;
+use rustc_hash::FxHashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+#[cfg(feature = "watch")]
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+pub use globset::Error as GlobError;
+
+/// Leading bytes checked for NUL when deciding whether a file is binary
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+impl Extractor {
+    /// Extract class names from every file `config` selects
+    ///
+    /// Unreadable, binary and oversized files are skipped and not counted in
+    /// `file_count`.
+    pub fn scan(&self, config: &ContentConfig) -> Result<ExtractionResult, GlobError> {
+        let start = Instant::now();
+        let files = content_files(config)?;
+
+        let sets: Vec<FxHashSet<String>> = files
+            .par_iter()
+            .filter_map(|path| read_text(path, config.max_file_size))
+            .map(|content| self.extract_set(&content))
+            .collect();
+        let file_count = sets.len();
+
+        let mut merged = FxHashSet::default();
+        for set in sets {
+            merged.extend(set);
+        }
+        let mut classes: Vec<String> = merged.into_iter().collect();
+        classes.sort_unstable();
+
+        Ok(ExtractionResult {
+            classes,
+            file_count,
+            time_us: start.elapsed().as_micros() as u64,
+        })
+    }
+}
+
+/// Paths of the files `config` selects, in walk order
+pub fn content_files(config: &ContentConfig) -> Result<Vec<PathBuf>, GlobError> {
+    let include = glob_set(&config.include)?;
+    let exclude = glob_set(&config.exclude)?;
+
+    // Excluded directories are pruned rather than walked
+    let root = config.root.clone();
+    let walker = WalkBuilder::new(&config.root)
+        .git_ignore(config.gitignore)
+        .git_global(config.gitignore)
+        .git_exclude(config.gitignore)
+        .ignore(config.gitignore)
+        .parents(config.gitignore)
+        .require_git(false)
+        .filter_entry(move |entry| !exclude.is_match(relative(&root, entry.path())))
+        .build();
+
+    Ok(walker
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter(|entry| config.include.is_empty() || include.is_match(relative(&config.root, entry.path())))
+        .map(ignore::DirEntry::into_path)
+        .collect())
+}
+
+/// Decides whether single paths are content files, for paths reported one
+/// at a time (e.g., by a file watcher) rather than found by walking
+///
+/// Only the ignore files at the root are consulted; nested ones are honored
+/// by `content_files`.
+#[cfg(feature = "watch")]
+#[derive(Debug, Clone)]
+pub(crate) struct ContentFilter {
+    root: PathBuf,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    ignore: Option<Gitignore>,
+}
+
+#[cfg(feature = "watch")]
+impl ContentFilter {
+    pub(crate) fn new(config: &ContentConfig) -> Result<Self, GlobError> {
+        let include = if config.include.is_empty() {
+            None
+        } else {
+            Some(glob_set(&config.include)?)
+        };
+
+        // Unreadable ignore files are skipped, as the walker does
+        let ignore = config.gitignore.then(|| {
+            let mut builder = GitignoreBuilder::new(&config.root);
+            for name in [".gitignore", ".ignore"] {
+                builder.add(config.root.join(name));
+            }
+            builder.build().unwrap_or_else(|_| Gitignore::empty())
+        });
+
+        Ok(Self {
+            root: config.root.clone(),
+            include,
+            exclude: glob_set(&config.exclude)?,
+            ignore,
+        })
+    }
+
+    /// Files under `dir` that a walk of the root would select
+    pub(crate) fn files_under(&self, dir: &Path) -> Vec<PathBuf> {
+        WalkBuilder::new(dir)
+            .standard_filters(false)
+            .build()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+            .map(ignore::DirEntry::into_path)
+            .filter(|path| self.selects(path))
+            .collect()
+    }
+
+    /// Directories under `dir` (itself included) that a walk of the root
+    /// would enter
+    pub(crate) fn dirs_under(&self, dir: &Path) -> Vec<PathBuf> {
+        if !self.enters(dir) {
+            return Vec::new();
+        }
+
+        let filter = self.clone();
+        WalkBuilder::new(dir)
+            .standard_filters(false)
+            .filter_entry(move |entry| entry.file_type().is_some_and(|t| t.is_dir()) && filter.enters(entry.path()))
+            .build()
+            .filter_map(Result::ok)
+            .map(ignore::DirEntry::into_path)
+            .collect()
+    }
+
+    /// Whether a walk of the root would select the file at `path`
+    pub(crate) fn selects(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        if self.prunes(relative) {
+            return false;
+        }
+
+        if self.include.as_ref().is_some_and(|include| !include.is_match(relative)) {
+            return false;
+        }
+        !self
+            .ignore
+            .as_ref()
+            .is_some_and(|ignore| ignore.matched_path_or_any_parents(path, false).is_ignore())
+    }
+
+    /// Whether a walk of the root would enter the directory at `path`
+    pub(crate) fn enters(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        if relative.as_os_str().is_empty() {
+            return true;
+        }
+
+        !self.prunes(relative)
+            && !self
+                .ignore
+                .as_ref()
+                .is_some_and(|ignore| ignore.matched_path_or_any_parents(path, true).is_ignore())
+    }
+
+    /// Whether `relative` is or lies under a hidden or excluded directory,
+    /// which the walk prunes
+    fn prunes(&self, relative: &Path) -> bool {
+        let mut prefix = PathBuf::new();
+        relative.components().any(|component| {
+            prefix.push(component);
+            component.as_os_str().to_string_lossy().starts_with('.') || self.exclude.is_match(&prefix)
+        })
+    }
+}
+
+/// Contents of a text file, or `None` when it cannot be read, looks binary
+/// or is larger than `max_size` bytes
+///
+/// Invalid UTF-8 is replaced rather than rejected, so a stray byte in a
+/// template does not hide the classes around it.
+pub(crate) fn read_text(path: &Path, max_size: u64) -> Option<String> {
+    if fs::metadata(path).ok()?.len() > max_size {
+        return None;
+    }
+
+    let bytes = fs::read(path).ok()?;
+    if memchr::memchr(0, &bytes[..bytes.len().min(BINARY_SNIFF_LEN)]).is_some() {
+        return None;
+    }
+
+    Some(match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(err) => String::from_utf8_lossy(err.as_bytes()).into_owned(),
+    })
+}
+
+/// Compile globs, where `*` stops at `/` and `**` crosses directories
+fn glob_set(globs: &[String]) -> Result<GlobSet, GlobError> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(GlobBuilder::new(glob).literal_separator(true).build()?);
+    }
+    builder.build()
+}
+
+/// `path` relative to `root`, as globs are written
+fn relative<'a>(root: &Path, path: &'a Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &[u8]) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, ".gitignore", b"dist/\n");
+        write(root, "index.html", br#"<body class="bg-white">"#);
+        write(root, "src/App.tsx", br#"<div className="flex p-4" />"#);
+        write(root, "src/nested/Nav.vue", br#"<nav :class="'gap-2'" />"#);
+        write(root, "dist/bundle.js", br#""m-8""#);
+        write(root, "node_modules/pkg/index.js", br#""m-9""#);
+        write(root, "logo.png", b"\x89PNG\0\0 \"text-red-500\"");
+        dir
+    }
+
+    #[test]
+    fn test_scan_directory() {
+        let dir = project();
+        let result = Extractor::new()
+            .scan(&ContentConfig {
+                root: dir.path().to_path_buf(),
+                exclude: vec!["node_modules".to_string()],
+                ..Default::default()
+            })
+            .unwrap();
+
+        for class in ["bg-white", "flex", "p-4", "gap-2"] {
+            assert!(result.classes.contains(&class.to_string()), "missing {}", class);
+        }
+        // Ignored, excluded and binary files
+        for class in ["m-8", "m-9", "text-red-500"] {
+            assert!(!result.classes.contains(&class.to_string()), "unexpected {}", class);
+        }
+        // index.html, App.tsx and Nav.vue; hidden files are skipped
+        assert_eq!(result.file_count, 3);
+    }
+
+    #[test]
+    fn test_scan_include_globs() {
+        let dir = project();
+        let config = ContentConfig {
+            root: dir.path().to_path_buf(),
+            include: vec!["src/**/*.{tsx,vue}".to_string(), "dist/*.js".to_string()],
+            ..Default::default()
+        };
+
+        let files = content_files(&config).unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|f| f.starts_with(dir.path().join("src"))));
+
+        // Without gitignore support, dist/ is scanned too
+        let result = Extractor::new()
+            .scan(&ContentConfig {
+                gitignore: false,
+                ..config
+            })
+            .unwrap();
+        assert_eq!(result.file_count, 3);
+        assert!(result.classes.contains(&"m-8".to_string()));
+    }
+
+    #[test]
+    fn test_scan_skips_huge_files() {
+        let dir = project();
+        write(dir.path(), "src/huge.html", format!(r#"class="w-4 {}""#, "x".repeat(4096)).as_bytes());
+
+        let result = Extractor::new()
+            .scan(&ContentConfig {
+                root: dir.path().to_path_buf(),
+                include: vec!["src/**".to_string()],
+                max_file_size: 1024,
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(!result.classes.contains(&"w-4".to_string()));
+        assert_eq!(result.file_count, 2);
+    }
+
+    #[cfg(feature = "watch")]
+    #[test]
+    fn test_content_filter_agrees_with_walk() {
+        let dir = project();
+        let root = dir.path();
+        write(root, ".cache/tmp.html", b"");
+        let config = ContentConfig {
+            root: root.to_path_buf(),
+            exclude: vec!["node_modules".to_string()],
+            ..Default::default()
+        };
+        let filter = ContentFilter::new(&config).unwrap();
+
+        let walked = content_files(&config).unwrap();
+        for path in ["index.html", "src/App.tsx", "src/nested/Nav.vue", "logo.png"] {
+            assert!(walked.contains(&root.join(path)));
+            assert!(filter.selects(&root.join(path)), "{}", path);
+        }
+        for path in ["dist/bundle.js", "node_modules/pkg/index.js", ".gitignore", ".cache/tmp.html"] {
+            assert!(!filter.selects(&root.join(path)), "{}", path);
+        }
+        assert!(!filter.selects(Path::new("/elsewhere/index.html")));
+
+        let mut nested = filter.files_under(&root.join("src"));
+        nested.sort();
+        assert_eq!(nested, vec![root.join("src/App.tsx"), root.join("src/nested/Nav.vue")]);
+        assert!(filter.files_under(&root.join("dist")).is_empty());
+
+        let mut dirs = filter.dirs_under(root);
+        dirs.sort();
+        assert_eq!(dirs, vec![root.to_path_buf(), root.join("src"), root.join("src/nested")]);
+        assert!(filter.enters(&root.join("src/new")));
+        for dir in ["dist", "node_modules/pkg", ".cache"] {
+            assert!(!filter.enters(&root.join(dir)), "{}", dir);
+        }
+        assert!(filter.dirs_under(&root.join("node_modules")).is_empty());
+
+        let filter = ContentFilter::new(&ContentConfig {
+            include: vec!["src/**/*.tsx".to_string()],
+            ..config
+        })
+        .unwrap();
+        assert!(filter.selects(&root.join("src/App.tsx")));
+        assert!(!filter.selects(&root.join("index.html")));
+    }
+
+    #[test]
+    fn test_scan_invalid_glob() {
+        let config = ContentConfig {
+            include: vec!["src/[".to_string()],
+            ..Default::default()
+        };
+        assert!(Extractor::new().scan(&config).is_err());
+    }
+}
@@ -1,35 +1,17 @@
 //! Parallel file content extractor for CSS class names
 //!
-//! Scans file contents for CSS class names using optimized regex patterns
-//! and parallel processing with rayon.
+//! Scans file contents for CSS class names with a single-pass candidate
+//! scanner (see `scanner`) and parallel processing with rayon.
 
+use crate::parser::{expand_variant_group, find_variant_group, split_classes};
+use crate::scanner::Scanner;
+use crate::types::{ClassOccurrence, SourceLocation};
 use once_cell::sync::Lazy;
 use rayon::prelude::*;
 use regex::Regex;
 use rustc_hash::FxHashSet;
 
-/// Regex patterns for extracting class names from various contexts
-static CLASS_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
-    vec![
-        // HTML class attribute: class="..."
-        Regex::new(r#"class\s*=\s*["']([^"']+)["']"#).unwrap(),
-        // JSX className: className="..." or className={'...'}
-        Regex::new(r#"className\s*=\s*["']([^"']+)["']"#).unwrap(),
-        Regex::new(r#"className\s*=\s*\{["'`]([^"'`]+)["'`]\}"#).unwrap(),
-        // Vue :class binding
-        Regex::new(r#":class\s*=\s*["']([^"']+)["']"#).unwrap(),
-        // Svelte class directive: class:name={condition}
-        Regex::new(r#"class:([a-zA-Z0-9_\-:]+)"#).unwrap(),
-        // Template literals in JS/TS: `${...}` containing class names
-        Regex::new(r#"`([^`]*(?:hover:|dark:|md:|lg:|xl:|sm:|focus:|active:|group-|peer-)[^`]*)`"#).unwrap(),
-        // clsx/classnames/cn function calls
-        Regex::new(r#"(?:clsx|classnames|cn|twMerge|cva)\s*\(\s*["'`]([^"'`]+)["'`]"#).unwrap(),
-        // Object keys in clsx: { 'class-name': condition }
-        Regex::new(r#"['"]([a-zA-Z0-9_\-:\/\[\]]+)['"]:\s*(?:true|false|\w+)"#).unwrap(),
-    ]
-});
-
-/// Pattern for splitting class strings into individual classes
+/// Pattern for splitting raw class strings into individual classes
 static CLASS_SPLITTER: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"[\s,]+").unwrap()
 });
@@ -38,7 +20,7 @@ static CLASS_SPLITTER: Lazy<Regex> = Lazy::new(|| {
 static CLASS_VALIDATOR: Lazy<Regex> = Lazy::new(|| {
     // Valid class: starts with letter, !, or - and contains valid chars
     // Allow # for colors, % for percentages, and other chars common in arbitrary values
-    Regex::new(r"^[!-]?[a-zA-Z][a-zA-Z0-9_\-:/\[\]\.#,()%+*&']+$").unwrap()
+    Regex::new(r"^[!-]?(?:[a-zA-Z\[@]|\*:)[a-zA-Z0-9_\-:/\[\]\.#,()%+*&'>~=@]+$").unwrap()
 });
 
 /// High-performance class name extractor
@@ -75,20 +57,13 @@ impl Extractor {
     }
 
     /// Extract class names from a single content string
+    ///
+    /// Variant groups are returned as the whole group token (e.g.,
+    /// `hover:(p-4 m-2)`), the class tokens written on the element, which
+    /// `Parser::parse_all` expands into its members.
     pub fn extract(&self, content: &str) -> Vec<String> {
-        let mut classes = FxHashSet::default();
-
-        // Try each pattern
-        for pattern in CLASS_PATTERNS.iter() {
-            for captures in pattern.captures_iter(content) {
-                if let Some(matched) = captures.get(1) {
-                    self.extract_from_match(matched.as_str(), &mut classes);
-                }
-            }
-        }
-
         // Convert to sorted vector
-        let mut result: Vec<String> = classes.into_iter().collect();
+        let mut result: Vec<String> = self.extract_set(content).into_iter().collect();
         result.sort_unstable();
         result
     }
@@ -98,17 +73,7 @@ impl Extractor {
         // Process all contents in parallel
         let all_classes: Vec<FxHashSet<String>> = contents
             .par_iter()
-            .map(|content| {
-                let mut classes = FxHashSet::default();
-                for pattern in CLASS_PATTERNS.iter() {
-                    for captures in pattern.captures_iter(content) {
-                        if let Some(matched) = captures.get(1) {
-                            self.extract_from_match(matched.as_str(), &mut classes);
-                        }
-                    }
-                }
-                classes
-            })
+            .map(|content| self.extract_set(content))
             .collect();
 
         // Merge all results
@@ -123,29 +88,118 @@ impl Extractor {
         result
     }
 
+    /// Extract every class occurrence in `content` with its location
+    ///
+    /// Occurrences are in source order, and a class used twice is reported
+    /// twice. A variant group is one occurrence of its whole token.
+    pub fn extract_locations(&self, file: &str, content: &str) -> Vec<ClassOccurrence> {
+        let mut found: Vec<(usize, String)> = Vec::new();
+        self.for_each_class(content, |class, offset| found.push((offset, class.to_string())));
+
+        let lines = LineIndex::new(content);
+        found
+            .into_iter()
+            .map(|(offset, class)| {
+                let (line, column) = lines.position(content, offset);
+                ClassOccurrence {
+                    class,
+                    location: SourceLocation {
+                        file: file.to_string(),
+                        line,
+                        column,
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Extract class occurrences from multiple `(file, content)` pairs in
+    /// parallel, in file order
+    pub fn extract_locations_parallel(&self, files: &[(&str, &str)]) -> Vec<ClassOccurrence> {
+        files
+            .par_iter()
+            .map(|(file, content)| self.extract_locations(file, content))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
     /// Extract class names directly from raw strings (for performance)
     /// Assumes the string contains only class names separated by whitespace
     pub fn extract_raw(&self, raw: &str) -> Vec<String> {
         let mut classes = FxHashSet::default();
-        self.extract_from_match(raw, &mut classes);
+        self.visit_classes(raw, |class, _| {
+            classes.insert(class.to_string());
+        });
         classes.into_iter().collect()
     }
 
-    /// Extract individual classes from a matched string
-    fn extract_from_match(&self, matched: &str, classes: &mut FxHashSet<String>) {
+    /// Unique class names in `content`
+    pub(crate) fn extract_set(&self, content: &str) -> FxHashSet<String> {
+        let mut classes = FxHashSet::default();
+        self.for_each_class(content, |class, _| {
+            classes.insert(class.to_string());
+        });
+        classes
+    }
+
+    /// Call `visit` with each valid class in `content` and its byte offset
+    fn for_each_class(&self, content: &str, mut visit: impl FnMut(&str, usize)) {
+        for (offset, candidate) in Scanner::new(content) {
+            self.visit_token(candidate, offset, &mut visit);
+        }
+    }
+
+    /// Call `visit` with each valid class in a whitespace-separated class
+    /// string and its byte offset within it
+    fn visit_classes(&self, matched: &str, mut visit: impl FnMut(&str, usize)) {
+        // Variant groups contain whitespace, so they need group-aware splitting
+        if matched.contains('(') {
+            for token in split_classes(matched) {
+                self.visit_token(token, offset_in(matched, token), &mut visit);
+            }
+            return;
+        }
+
         // Split by whitespace and commas
         for class in CLASS_SPLITTER.split(matched) {
-            let class = class.trim();
+            self.visit_class(class, offset_in(matched, class), &mut visit);
+        }
+    }
 
-            // Length validation
-            if class.len() < self.min_length || class.len() > self.max_length {
-                continue;
-            }
+    /// Pass on a class, or a variant group token as a whole when any of the
+    /// classes it expands to is valid
+    fn visit_token(&self, token: &str, offset: usize, visit: &mut impl FnMut(&str, usize)) {
+        if !token.contains('(') || find_variant_group(token).is_none() {
+            self.visit_class(token, offset, visit);
+            return;
+        }
 
-            // Validate class name format
-            if self.is_valid_class(class) {
-                classes.insert(class.to_string());
-            }
+        let mut valid = false;
+        for class in expand_variant_group(token) {
+            self.visit_class(&class, offset, &mut |_, _| valid = true);
+        }
+        if valid {
+            visit(token, offset);
+        }
+    }
+
+    /// Validate a single candidate and pass it on
+    #[inline]
+    fn visit_class(&self, class: &str, offset: usize, visit: &mut impl FnMut(&str, usize)) {
+        let trimmed = class.trim_start();
+        let offset = offset + (class.len() - trimmed.len());
+        let class = trimmed.trim_end();
+
+        // Length validation
+        if class.len() < self.min_length || class.len() > self.max_length {
+            return;
+        }
+
+        // Validate class name format
+        if self.is_valid_class(class) {
+            visit(class, offset);
         }
     }
 
@@ -158,9 +212,13 @@ impl Extractor {
         }
 
         // Check first character
-        let first = class.as_bytes()[0];
-        if !matches!(first, b'a'..=b'z' | b'A'..=b'Z' | b'!' | b'-' | b'@') {
-            return false;
+        let bytes = class.as_bytes();
+        let first = bytes[0];
+        if !matches!(first, b'a'..=b'z' | b'A'..=b'Z' | b'!' | b'-' | b'@' | b'[') {
+            // `*:` (direct children) is the only variant starting with a symbol
+            if !(first == b'*' && bytes.get(1) == Some(&b':')) {
+                return false;
+            }
         }
 
         // Use regex for complete validation
@@ -168,6 +226,32 @@ impl Extractor {
     }
 }
 
+/// Byte offset of `part`, a subslice of `whole`
+fn offset_in(whole: &str, part: &str) -> usize {
+    part.as_ptr() as usize - whole.as_ptr() as usize
+}
+
+/// Start offsets of the lines of a file, for turning byte offsets into
+/// line and column numbers
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(content: &str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(memchr::memchr_iter(b'\n', content.as_bytes()).map(|i| i + 1));
+        Self { starts }
+    }
+
+    /// 1-based line and column (in UTF-16 units) of a byte offset
+    fn position(&self, content: &str, offset: usize) -> (u32, u32) {
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        let column = content[self.starts[line]..offset].encode_utf16().count() + 1;
+        (line as u32 + 1, column as u32)
+    }
+}
+
 /// Extract classes from HTML-like content quickly
 /// Optimized for build tools that process many files
 pub fn extract_fast(content: &str) -> Vec<String> {
@@ -263,4 +347,71 @@ mod tests {
         // Should only have unique classes
         assert_eq!(classes.len(), 2);
     }
+
+    #[test]
+    fn test_extract_locations() {
+        let extractor = Extractor::new();
+        let content = "<main>\n  <div class=\"p-4 m-2\">\n    <p class='p-4 md:(flex block)'>é</p>\n";
+        let found = extractor.extract_locations("src/App.html", content);
+
+        let at = |class: &str| -> Vec<(u32, u32)> {
+            found
+                .iter()
+                .filter(|o| o.class == class)
+                .map(|o| (o.location.line, o.location.column))
+                .collect()
+        };
+        assert_eq!(at("p-4"), vec![(2, 15), (3, 15)]);
+        assert_eq!(at("m-2"), vec![(2, 19)]);
+        assert_eq!(at("md:(flex block)"), vec![(3, 19)]);
+        assert!(at("md:flex").is_empty());
+        assert!(found.iter().all(|o| o.location.file == "src/App.html"));
+
+        let files = [("a.html", r#"class="flex""#), ("b.html", r#"class="flex""#)];
+        let files: Vec<String> = extractor
+            .extract_locations_parallel(&files)
+            .into_iter()
+            .map(|o| o.location.to_string())
+            .collect();
+        assert_eq!(files, vec!["a.html:1", "b.html:1"]);
+
+        // Columns count UTF-16 units, so an emoji takes two
+        let found = extractor.extract_locations("a.html", "<b>🎨</b><i class=\"flex\">");
+        assert_eq!(found[0].location.column, 20);
+    }
+
+    #[test]
+    fn test_extract_any_file_type() {
+        let extractor = Extractor::new();
+        let content = r#"
+            const styles = ["flex", "items-center"];
+            export const Nav = () => <Card cls="rounded-lg shadow-md" />;
+            view! { <p class="text-sm">{"md:grid-cols-2"}</p> }
+            <p class={["px-3", @active && "font-bold"]}>
+        "#;
+        let classes = extractor.extract(content);
+
+        for class in ["flex", "items-center", "rounded-lg", "shadow-md", "text-sm", "md:grid-cols-2", "px-3", "font-bold"] {
+            assert!(classes.contains(&class.to_string()), "missing {}", class);
+        }
+        // Attribute names and called functions are not candidates
+        assert!(!classes.contains(&"cls".to_string()));
+        assert!(!classes.contains(&"class".to_string()));
+    }
+
+    #[test]
+    fn test_extract_variant_group() {
+        let extractor = Extractor::new();
+        let content = r#"<a class="flex md:(hover:(p-4 m-2) underline)">"#;
+        let classes = extractor.extract(content);
+
+        // Groups stay whole, as written on the element
+        assert_eq!(classes, vec!["flex", "md:(hover:(p-4 m-2) underline)"]);
+        assert_eq!(extractor.extract_raw("!(mt-1) hover:(p-4 m-2)").len(), 2);
+        assert!(extractor.extract(r#"class="md:(#  $)""#).is_empty());
+
+        let arbitrary = extractor.extract(r#"class="bg-[rgb(0,0,0)] p-4""#);
+        assert!(arbitrary.contains(&"bg-[rgb(0,0,0)]".to_string()));
+        assert!(arbitrary.contains(&"p-4".to_string()));
+    }
 }
@@ -3,8 +3,47 @@
 //! Generates CSS output from matched utility classes.
 //! Handles variant wrapping, layer organization, and output formatting.
 
-use crate::types::{GenerateOptions, Layer, MatchResult, Variant, VariantSelector};
-use std::collections::BTreeMap;
+use crate::parser::{parse_arbitrary_variant, ArbitraryVariant};
+use crate::source_map::{SourceIndex, SourceMap, SourceMapBuilder};
+use crate::theme::{length_px, Theme};
+use crate::types::{
+    CSSProperty, DarkMode, GenerateOptions, Important, Layer, MatchResult, ParsedClass, SourceLocation, SourceMapMode,
+    Variant, VariantSelector,
+};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{self, Write};
+use std::io;
+use std::sync::Arc;
+
+/// Order of arbitrary at-rule variants, after every named variant
+const ARBITRARY_VARIANT_ORDER: u32 = 300;
+
+/// Order of responsive width queries, which are further ranked by width
+const BREAKPOINT_ORDER: u32 = 50;
+
+/// Order of container queries, inside breakpoints and also ranked by width
+const CONTAINER_ORDER: u32 = 80;
+
+/// Order of `group-*` and `peer-*` variants, alongside pseudo-classes
+const RELATIONAL_VARIANT_ORDER: u32 = 100;
+
+/// Order of `aria-*`, `data-*`, `has-*` and similar variants
+const STRUCTURAL_VARIANT_ORDER: u32 = 110;
+
+/// Prefixes of the structural variant families, longest first so that
+/// "nth-last-of-type-2" is not read as "nth-" + "last-of-type-2"
+const STRUCTURAL_VARIANTS: &[&str] = &[
+    "nth-last-of-type",
+    "nth-of-type",
+    "nth-last",
+    "nth",
+    "aria",
+    "data",
+    "has",
+    "not",
+    "in",
+];
 
 /// High-performance CSS generator
 #[derive(Debug, Clone)]
@@ -14,6 +53,9 @@ pub struct Generator {
 
     /// Registered variants
     variants: Vec<Variant>,
+
+    /// Theme providing breakpoints
+    theme: Arc<Theme>,
 }
 
 impl Default for Generator {
@@ -25,30 +67,68 @@ impl Default for Generator {
 impl Generator {
     /// Create a new Generator with default options
     pub fn new() -> Self {
-        let mut generator = Self {
-            options: GenerateOptions::default(),
-            variants: Vec::new(),
-        };
-
-        generator.register_default_variants();
-        generator
+        Self::with_options(GenerateOptions::default())
     }
 
     /// Create a Generator with custom options
     pub fn with_options(options: GenerateOptions) -> Self {
+        Self::with_theme(options, Arc::new(Theme::default()))
+    }
+
+    /// Create a Generator whose responsive variants come from `theme`
+    pub fn with_theme(options: GenerateOptions, theme: Arc<Theme>) -> Self {
         let mut generator = Self {
             options,
             variants: Vec::new(),
+            theme,
         };
 
         generator.register_default_variants();
         generator
     }
 
+    /// Switch to another theme, re-registering the variants derived from it
+    pub fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+        self.variants.clear();
+        self.register_default_variants();
+    }
+
     /// Generate CSS from match results
     pub fn generate(&self, results: &[MatchResult]) -> String {
+        let mut output = String::with_capacity(results.len() * 100);
+        // Writing to a String cannot fail
+        let _ = self.generate_into(results, &mut output);
+        output
+    }
+
+    /// Stream CSS from match results into a `fmt::Write` sink
+    ///
+    /// Produces the same output as `generate`, writing each rule directly
+    /// instead of building the stylesheet in memory first.
+    pub fn generate_into<W: Write>(&self, results: &[MatchResult], out: &mut W) -> fmt::Result {
+        self.render(results, &mut Output::new(out, None, false))
+    }
+
+    /// Generate CSS from match results, with source comments and a source
+    /// map pointing each rule back to where its class was used
+    ///
+    /// The map is produced when `source_map` is not `Off`, and comments when
+    /// `source_comments` is set; classes missing from `sources` get neither.
+    pub fn generate_with_sources(&self, results: &[MatchResult], sources: &SourceIndex) -> GeneratedCss {
+        let mut css = String::with_capacity(results.len() * 100);
+        let mut output = Output::new(&mut css, Some(sources), self.options.source_map != SourceMapMode::Off);
+        // Writing to a String cannot fail
+        let _ = self.render(results, &mut output);
+
+        let map = output.map.take().map(SourceMapBuilder::build);
+        GeneratedCss { css, map }
+    }
+
+    /// Write the stylesheet, layer by layer
+    fn render<W: Write>(&self, results: &[MatchResult], out: &mut Output<'_, W>) -> fmt::Result {
         if results.is_empty() {
-            return String::new();
+            return Ok(());
         }
 
         // Group results by layer
@@ -57,8 +137,6 @@ impl Generator {
             by_layer.entry(result.layer).or_default().push(result);
         }
 
-        let mut output = String::with_capacity(results.len() * 100);
-
         // Generate CSS for each layer
         if self.options.use_layers {
             for (layer, results) in by_layer {
@@ -68,59 +146,299 @@ impl Generator {
                     Layer::Utilities => "utilities",
                 };
 
-                output.push_str(&format!("@layer {} {{\n", layer_name));
-
-                for result in results {
-                    let rule = self.generate_rule(result);
-                    output.push_str(&rule);
+                if self.options.minify {
+                    write!(out, "@layer {}{{", layer_name)?;
+                    self.write_rules(&results, out)?;
+                    out.write_char('}')?;
+                } else {
+                    writeln!(out, "@layer {} {{", layer_name)?;
+                    self.write_rules(&results, out)?;
+                    out.write_str("}\n\n")?;
                 }
-
-                output.push_str("}\n\n");
             }
         } else {
             // No layers, just output rules
-            for result in results {
-                let rule = self.generate_rule(result);
-                output.push_str(&rule);
+            let results: Vec<&MatchResult> = results.iter().collect();
+            self.write_rules(&results, out)?;
+        }
+
+        Ok(())
+    }
+
+    /// Stream CSS from match results into an `io::Write` sink (e.g., a file)
+    ///
+    /// Output is written in many small pieces, so unbuffered sinks should be
+    /// wrapped in a `BufWriter`.
+    pub fn write_to<W: io::Write>(&self, results: &[MatchResult], out: W) -> io::Result<()> {
+        let mut adapter = IoAdapter { inner: out, error: None };
+        self.generate_into(results, &mut adapter).map_err(|_| {
+            adapter
+                .error
+                .take()
+                .unwrap_or_else(|| io::Error::other("failed to format CSS"))
+        })
+    }
+
+    /// Write rules in cascade order, grouping those that share an at-rule
+    /// context into one block
+    ///
+    /// Rules are sorted by at-rule variants (unconditional rules first, then
+    /// breakpoints from narrowest to widest and so on), selector variants,
+    /// utility sort order, property count and names, and finally class name,
+    /// so the output does not depend on input order.
+    fn write_rules<W: Write>(&self, results: &[&MatchResult], out: &mut Output<'_, W>) -> fmt::Result {
+        let mut rules: Vec<(RuleScope, &MatchResult)> = results
+            .iter()
+            .map(|result| (self.generate_scope(result), *result))
+            .collect();
+        rules.sort_by(|a, b| self.compare_rules(a, b));
+
+        // Sorting keeps every at-rule chain contiguous
+        for block in rules.chunk_by(|(a, _), (b, _)| a.at_rules == b.at_rules) {
+            self.write_block(block, out)?;
+        }
+
+        // Supporting rules (e.g., @keyframes) once each, after the utilities
+        let mut emitted: Vec<&str> = Vec::new();
+        for rule in rules.iter().flat_map(|(_, r)| r.extra_rules.iter()) {
+            if emitted.contains(&rule.as_str()) {
+                continue;
+            }
+            emitted.push(rule);
+
+            if self.options.minify {
+                write_minified(rule, out)?;
+            } else {
+                writeln!(out, "{}", rule)?;
             }
         }
 
-        if self.options.minify {
-            self.minify(&output)
-        } else {
-            output
+        Ok(())
+    }
+
+    /// Write rules sharing one at-rule chain
+    ///
+    /// Rules repeating an earlier selector and declarations are dropped. With
+    /// `merge_rules`, a rule whose declarations match an earlier one joins its
+    /// selector list, unless a rule in between touches the same properties.
+    fn write_block<W: Write>(&self, rules: &[(RuleScope, &MatchResult)], out: &mut Output<'_, W>) -> fmt::Result {
+        let at_rules = &rules[0].0.at_rules;
+        let depth = at_rules.len();
+
+        let mut written: Vec<MergedRule> = Vec::new();
+        let mut seen: HashSet<(&str, &[CSSProperty], bool)> = HashSet::new();
+        for (scope, result) in rules {
+            let important = self.is_important(result);
+            if !seen.insert((scope.selector.as_str(), &result.properties, important)) {
+                continue;
+            }
+
+            let rule = MergedRule {
+                selectors: vec![(&scope.selector, source_class(&result.parsed))],
+                properties: &result.properties,
+                important,
+                families: result.properties.iter().map(|p| property_family(&p.property)).collect(),
+                isolated: scope.selector_order.contains(&ARBITRARY_VARIANT_ORDER)
+                    || has_isolated_pseudo(&scope.selector),
+            };
+            let target = if self.options.merge_rules {
+                merge_target(&written, &rule)
+            } else {
+                None
+            };
+
+            match target {
+                Some(index) => written[index].selectors.push((&scope.selector, source_class(&result.parsed))),
+                None => written.push(rule),
+            }
+        }
+
+        for (level, at_rule) in at_rules.iter().enumerate() {
+            if self.options.minify {
+                write!(out, "{}{{", at_rule)?;
+            } else {
+                writeln!(out, "{}{} {{", indent(level), at_rule)?;
+            }
+        }
+        for rule in &written {
+            self.write_rule(rule, depth, out)?;
+        }
+        self.close_at_rules(depth, out)
+    }
+
+    /// Close `depth` nested at-rule blocks
+    fn close_at_rules<W: Write>(&self, depth: usize, out: &mut Output<'_, W>) -> fmt::Result {
+        for depth in (0..depth).rev() {
+            if self.options.minify {
+                out.write_char('}')?;
+            } else {
+                writeln!(out, "{}}}", indent(depth))?;
+            }
         }
+        Ok(())
     }
 
-    /// Generate a single CSS rule from a match result
-    fn generate_rule(&self, result: &MatchResult) -> String {
-        let selector = self.generate_selector(&result.parsed.raw, &result.parsed.variants);
-        let properties = self.generate_properties(result);
+    /// Position of two results relative to each other in generated output:
+    /// by layer when `use_layers` is set, then in cascade order
+    pub(crate) fn cascade_order(&self, a: &MatchResult, b: &MatchResult) -> Ordering {
+        let by_layer = if self.options.use_layers {
+            a.layer.cmp(&b.layer)
+        } else {
+            Ordering::Equal
+        };
+        by_layer.then_with(|| self.compare_rules(&(self.generate_scope(a), a), &(self.generate_scope(b), b)))
+    }
+
+    /// Cascade order of two rules (see `write_rules`)
+    fn compare_rules(
+        &self,
+        (a, a_result): &(RuleScope, &MatchResult),
+        (b, b_result): &(RuleScope, &MatchResult),
+    ) -> Ordering {
+        a.order
+            .cmp(&b.order)
+            .then_with(|| a.at_rules.cmp(&b.at_rules))
+            .then_with(|| a.selector_order.cmp(&b.selector_order))
+            .then_with(|| a_result.sort_order.cmp(&b_result.sort_order))
+            .then_with(|| {
+                if !self.options.sort_by_property {
+                    return Ordering::Equal;
+                }
+                let a_names = a_result.properties.iter().map(|p| &p.property);
+                let b_names = b_result.properties.iter().map(|p| &p.property);
+                a_result
+                    .properties
+                    .len()
+                    .cmp(&b_result.properties.len())
+                    .then_with(|| a_names.cmp(b_names))
+            })
+            .then_with(|| a_result.parsed.raw.cmp(&b_result.parsed.raw))
+            .then_with(|| a.selector.cmp(&b.selector))
+    }
+
+    /// Write a single CSS rule with its selector list and declarations
+    fn write_rule<W: Write>(&self, rule: &MergedRule<'_>, depth: usize, out: &mut Output<'_, W>) -> fmt::Result {
+        let minify = self.options.minify;
+
+        if self.options.source_comments {
+            self.write_source_comment(rule, depth, out)?;
+        }
+
+        for (i, (selector, class)) in rule.selectors.iter().enumerate() {
+            if i > 0 {
+                out.write_str(if minify { "," } else { ",\n" })?;
+            }
+            if !minify {
+                out.write_str(indent(depth))?;
+            }
+            out.map_class(class, self.options.source_map == SourceMapMode::All);
+            out.write_str(selector)?;
+        }
+        out.write_str(if minify { "{" } else { " {\n" })?;
+
+        let important = if rule.important { " !important" } else { "" };
+        for prop in rule.properties {
+            if minify {
+                write!(out, "{}:{}{};", prop.property, prop.value, important)?;
+            } else {
+                writeln!(out, "{}{}: {}{};", indent(depth + 1), prop.property, prop.value, important)?;
+            }
+        }
 
-        if self.options.minify {
-            format!("{}{{{}}}", selector, properties)
+        if minify {
+            out.write_char('}')
         } else {
-            format!("{} {{\n{}}}\n", selector, properties)
+            writeln!(out, "{}}}", indent(depth))
+        }
+    }
+
+    /// Write `/* file:line */` with the first usage of each class in a rule
+    fn write_source_comment<W: Write>(&self, rule: &MergedRule<'_>, depth: usize, out: &mut Output<'_, W>) -> fmt::Result {
+        let mut usages: Vec<&SourceLocation> = Vec::new();
+        for (_, class) in &rule.selectors {
+            if let Some(location) = out.locations(class).first() {
+                if !usages.contains(&location) {
+                    usages.push(location);
+                }
+            }
+        }
+        if usages.is_empty() {
+            return Ok(());
+        }
+
+        if !self.options.minify {
+            out.write_str(indent(depth))?;
+        }
+        out.write_str("/* ")?;
+        for (i, location) in usages.iter().enumerate() {
+            if i > 0 {
+                out.write_str(", ")?;
+            }
+            write!(out, "{}", location)?;
         }
+        out.write_str(if self.options.minify { " */" } else { " */\n" })
     }
 
-    /// Generate CSS selector with variant wrappers
-    fn generate_selector(&self, class_name: &str, variants: &[String]) -> String {
-        // Escape special characters in class name for CSS selector
-        let escaped = escape_css_selector(class_name);
-        let base_selector = format!(".{}", escaped);
+    /// Resolve the selector and at-rule wrappers for a class and its variants
+    fn generate_scope(&self, result: &MatchResult) -> RuleScope {
+        let variants = &result.parsed.variants;
+        let suffix = result.selector_suffix.as_deref().unwrap_or("");
+
+        let base_selector = class_selector(&result.parsed);
 
         if variants.is_empty() {
-            return base_selector;
+            return RuleScope {
+                selector: self.scope_selector(base_selector + suffix),
+                at_rules: Vec::new(),
+                order: Vec::new(),
+                selector_order: Vec::new(),
+            };
         }
 
         // Build selector with variants (apply in reverse order)
         let mut selector = base_selector;
-        let mut media_queries = Vec::new();
-        let mut container_queries = Vec::new();
+        let mut at_rules = Vec::new();
+        let mut order = Vec::new();
+        let mut selector_order = Vec::new();
+
+        // Responsive variants are merged into one query, placed where the
+        // leftmost of them appears
+        let mut width = WidthRange::default();
+        let mut width_at = None;
 
         for variant_name in variants.iter().rev() {
-            if let Some(variant) = self.find_variant(variant_name) {
+            if let Some(bound) = self.width_bound(variant_name) {
+                match bound {
+                    WidthBound::Min(value) => width.min.push(value),
+                    WidthBound::Max(value) => width.max.push(value),
+                }
+                width_at = Some(at_rules.len());
+            } else if let Some((query, rank)) = self.container_query(variant_name) {
+                at_rules.push(query);
+                order.push((CONTAINER_ORDER, rank));
+            } else if variant_name.starts_with('[') {
+                // Invalid arbitrary variants were reported by the parser
+                match parse_arbitrary_variant(variant_name) {
+                    Ok(ArbitraryVariant::Selector(template)) => {
+                        selector = template.replace('&', &selector);
+                        selector_order.push(ARBITRARY_VARIANT_ORDER);
+                    }
+                    Ok(ArbitraryVariant::AtRule(rule)) => {
+                        at_rules.push(rule);
+                        order.push((ARBITRARY_VARIANT_ORDER, 0));
+                    }
+                    Err(_) => {}
+                }
+            } else if let Some((parent, combinator)) = self.relational_selector(variant_name) {
+                selector = format!("{}{}{}", parent, combinator, selector);
+                selector_order.push(RELATIONAL_VARIANT_ORDER);
+            } else if let Some(template) = self.structural_template(variant_name) {
+                selector = template.replace('&', &selector);
+                selector_order.push(STRUCTURAL_VARIANT_ORDER);
+            } else if let Some(variant) = self.find_variant(variant_name) {
+                if !matches!(variant.selector, VariantSelector::Media(_) | VariantSelector::Container(_)) {
+                    selector_order.push(variant.order);
+                }
                 match &variant.selector {
                     VariantSelector::Pseudo(pseudo) => {
                         selector = format!("{}{}", selector, pseudo);
@@ -128,14 +446,12 @@ impl Generator {
                     VariantSelector::PseudoElement(pseudo) => {
                         selector = format!("{}{}", selector, pseudo);
                     }
-                    VariantSelector::Media(query) => {
-                        media_queries.push(query.clone());
-                    }
-                    VariantSelector::Container(query) => {
-                        container_queries.push(query.clone());
+                    VariantSelector::Media(query) | VariantSelector::Container(query) => {
+                        at_rules.push(query.clone());
+                        order.push((variant.order, 0));
                     }
                     VariantSelector::Attribute(attr) => {
-                        selector = format!("{}{}", attr, selector.replace('.', " ."));
+                        selector = format!("{} {}", attr, selector);
                     }
                     VariantSelector::Parent(parent) => {
                         selector = format!("{} {}", parent, selector);
@@ -144,28 +460,41 @@ impl Generator {
             } else {
                 // Unknown variant - treat as custom pseudo-class
                 selector = format!("{}:{}", selector, variant_name);
+                selector_order.push(ARBITRARY_VARIANT_ORDER);
             }
         }
 
-        // Wrap in media queries if needed (these go around the whole rule later)
-        // For now, just return the selector
-        selector
-    }
+        if let Some(at) = width_at {
+            width.min.reverse();
+            width.max.reverse();
+            at_rules.insert(at, width.media_query(self.options.media_range_syntax));
+            order.insert(at, (BREAKPOINT_ORDER, width.rank()));
+        }
 
-    /// Generate CSS property declarations
-    fn generate_properties(&self, result: &MatchResult) -> String {
-        let mut output = String::new();
-        let important = if result.parsed.important { " !important" } else { "" };
+        // Variants were walked right-to-left; the leftmost at-rule is outermost
+        at_rules.reverse();
+        order.reverse();
+        selector_order.sort_unstable();
 
-        for prop in &result.properties {
-            if self.options.minify {
-                output.push_str(&format!("{}:{}{};", prop.property, prop.value, important));
-            } else {
-                output.push_str(&format!("  {}: {}{};\n", prop.property, prop.value, important));
-            }
+        RuleScope {
+            selector: self.scope_selector(selector + suffix),
+            at_rules,
+            order,
+            selector_order,
         }
+    }
 
-        output
+    /// Nest a selector under the configured important scope, if any
+    fn scope_selector(&self, selector: String) -> String {
+        match self.options.important {
+            Important::Selector(ref scope) => format!("{} {}", scope, selector),
+            Important::Flag(_) => selector,
+        }
+    }
+
+    /// Whether the declarations of a result get `!important`
+    fn is_important(&self, result: &MatchResult) -> bool {
+        result.parsed.important || self.options.important == Important::Flag(true)
     }
 
     /// Find a variant by name
@@ -173,13 +502,176 @@ impl Generator {
         self.variants.iter().find(|v| v.name == name)
     }
 
-    /// Minify CSS output
-    fn minify(&self, css: &str) -> String {
-        css.lines()
-            .map(|l| l.trim())
-            .filter(|l| !l.is_empty())
-            .collect::<Vec<_>>()
-            .join("")
+    /// Resolve `group-*` and `peer-*` variants against the pseudo-class and
+    /// structural variants, returning the marker selector and the combinator
+    /// to join it with (e.g., "group-hover/card" gives `(".group\/card:hover", " ")`)
+    fn relational_selector(&self, name: &str) -> Option<(String, &'static str)> {
+        let (marker, combinator, rest) = if let Some(rest) = name.strip_prefix("group-") {
+            ("group", " ", rest)
+        } else if let Some(rest) = name.strip_prefix("peer-") {
+            ("peer", " ~ ", rest)
+        } else {
+            return None;
+        };
+
+        let (state, group_name) = match rest.rsplit_once('/') {
+            Some((state, group_name)) if !group_name.contains(']') => (state, Some(group_name)),
+            _ => (rest, None),
+        };
+        let template = match self.pseudo_class(state) {
+            Some(pseudo) => format!("&{}", pseudo),
+            None => self.structural_template(state)?,
+        };
+
+        let marker = match group_name {
+            Some(group_name) => escape_css_selector(&format!("{}/{}", marker, group_name)),
+            None => marker.to_string(),
+        };
+        Some((template.replace('&', &format!(".{}", marker)), combinator))
+    }
+
+    /// Selector template for the `aria-*`, `data-*`, `has-*`, `not-*`,
+    /// `in-*`, `nth-*` and `*` variant families, with `&` standing for the
+    /// class selector
+    ///
+    /// Arbitrary values are used as written, with underscores as spaces:
+    /// `data-[state=open]` gives `&[data-state=open]` and `has-[:checked]`
+    /// gives `&:has(:checked)`.
+    fn structural_template(&self, name: &str) -> Option<String> {
+        if name == "*" {
+            return Some(":is(& > *)".to_string());
+        }
+
+        let (family, value) = STRUCTURAL_VARIANTS
+            .iter()
+            .find_map(|family| Some((*family, name.strip_prefix(family)?.strip_prefix('-')?)))?;
+        let arbitrary = value
+            .strip_prefix('[')
+            .and_then(|v| v.strip_suffix(']'))
+            .map(|v| v.replace('_', " "));
+
+        match family {
+            "aria" | "data" => {
+                let condition = match arbitrary {
+                    Some(condition) => condition,
+                    None if family == "aria" => self.theme.aria.get(value)?.clone(),
+                    None => match self.theme.data.get(value) {
+                        Some(condition) => condition.clone(),
+                        None if is_identifier(value) => value.to_string(),
+                        None => return None,
+                    },
+                };
+                Some(format!("&[{}-{}]", family, condition))
+            }
+            "has" | "not" | "in" => {
+                let inner = match arbitrary {
+                    Some(selector) => selector,
+                    // `not-first` negates the pseudo-class itself; `has-checked`
+                    // and `in-focus` look for any element in that state
+                    None if family == "not" => self.pseudo_class(value)?.to_string(),
+                    None => format!("*{}", self.pseudo_class(value)?),
+                };
+                Some(match family {
+                    "has" => format!("&:has({})", inner),
+                    "not" => format!("&:not({})", inner),
+                    _ => format!(":where({}) &", inner),
+                })
+            }
+            _ => {
+                let index = match arbitrary {
+                    Some(index) => index,
+                    None if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                        value.to_string()
+                    }
+                    None => return None,
+                };
+                let pseudo = match family {
+                    "nth-last" => "nth-last-child",
+                    "nth-of-type" => "nth-of-type",
+                    "nth-last-of-type" => "nth-last-of-type",
+                    _ => "nth-child",
+                };
+                Some(format!("&:{}({})", pseudo, index))
+            }
+        }
+    }
+
+    /// Resolve a responsive variant into a width bound
+    ///
+    /// Screens (`md`, or `min-md`) give a lower bound and `max-md` an
+    /// exclusive upper one; `min-[900px]` and `max-[600px]` take the width
+    /// as written.
+    fn width_bound(&self, name: &str) -> Option<WidthBound> {
+        let (is_max, screen) = match name.strip_prefix("max-") {
+            Some(rest) => (true, rest),
+            None => (false, name.strip_prefix("min-").unwrap_or(name)),
+        };
+
+        let value = match screen.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            Some(value) if screen != name => value.replace('_', " "),
+            Some(_) => return None,
+            None => self.theme.screens.get(screen)?.clone(),
+        };
+
+        Some(if is_max {
+            WidthBound::Max(value)
+        } else {
+            WidthBound::Min(value)
+        })
+    }
+
+    /// Resolve a container query variant into its at-rule and width rank
+    ///
+    /// `@md` and `@max-md` use the theme's container sizes, `@[500px]` the
+    /// width as written, and a `/name` suffix targets a named container
+    /// (`@md/sidebar` gives `@container sidebar (min-width: 28rem)`).
+    fn container_query(&self, name: &str) -> Option<(String, u64)> {
+        let rest = name.strip_prefix('@')?;
+        let (size, container) = match rest.rsplit_once('/') {
+            Some((size, container)) if !container.contains(']') => (size, Some(container)),
+            _ => (rest, None),
+        };
+        let (is_max, size) = match size.strip_prefix("max-") {
+            Some(size) => (true, size),
+            None => (false, size.strip_prefix("min-").unwrap_or(size)),
+        };
+
+        let value = match size.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            Some(value) => value.replace('_', " "),
+            None => self.theme.containers.get(size)?.clone(),
+        };
+        let range = if is_max {
+            WidthRange { min: Vec::new(), max: vec![value] }
+        } else {
+            WidthRange { min: vec![value], max: Vec::new() }
+        };
+
+        let conditions = range.conditions(self.options.media_range_syntax);
+        let query = match container {
+            Some(container) => format!("@container {} {}", container, conditions),
+            None => format!("@container {}", conditions),
+        };
+        Some((query, range.rank()))
+    }
+
+    /// The `dark` variant for the configured strategy
+    fn dark_selector(&self) -> VariantSelector {
+        match &self.options.dark_mode {
+            DarkMode::Class(class) => VariantSelector::Parent(format!(".{}", escape_css_selector(class))),
+            DarkMode::Attribute(attr) => VariantSelector::Attribute(attr.clone()),
+            DarkMode::Media => VariantSelector::Media("@media (prefers-color-scheme: dark)".to_string()),
+            DarkMode::Where(selector) => {
+                VariantSelector::Pseudo(format!(":where({}, {} *)", selector, selector))
+            }
+        }
+    }
+
+    /// Look up a registered pseudo-class variant (e.g., "hover" gives ":hover")
+    fn pseudo_class(&self, name: &str) -> Option<&str> {
+        match &self.find_variant(name)?.selector {
+            VariantSelector::Pseudo(pseudo) => Some(pseudo),
+            _ => None,
+        }
     }
 
     /// Register default variants
@@ -246,27 +738,10 @@ impl Generator {
             });
         }
 
-        // Responsive variants
-        let breakpoints = [
-            ("sm", "@media (min-width: 640px)"),
-            ("md", "@media (min-width: 768px)"),
-            ("lg", "@media (min-width: 1024px)"),
-            ("xl", "@media (min-width: 1280px)"),
-            ("2xl", "@media (min-width: 1536px)"),
-        ];
-
-        for (name, query) in breakpoints {
-            self.variants.push(Variant {
-                name: name.to_string(),
-                selector: VariantSelector::Media(query.to_string()),
-                order: 50,
-            });
-        }
-
         // Dark mode
         self.variants.push(Variant {
             name: "dark".to_string(),
-            selector: VariantSelector::Parent(".dark".to_string()),
+            selector: self.dark_selector(),
             order: 10,
         });
 
@@ -288,100 +763,490 @@ impl Generator {
             selector: VariantSelector::Media("@media (prefers-reduced-motion: reduce)".to_string()),
             order: 70,
         });
+    }
+}
 
-        // Container queries
-        self.variants.push(Variant {
-            name: "@container".to_string(),
-            selector: VariantSelector::Container("@container".to_string()),
-            order: 80,
-        });
+/// Selector and at-rule context resolved for a single rule
+#[derive(Debug)]
+struct RuleScope {
+    /// Final selector with pseudo/parent variants applied
+    selector: String,
+
+    /// At-rules wrapping the rule, outermost first
+    at_rules: Vec<String>,
+
+    /// Variant order of each at-rule, and its rank within that order, used
+    /// to order at-rule blocks
+    order: Vec<(u32, u64)>,
+
+    /// Sorted orders of the variants that changed the selector, so state
+    /// variants (e.g., `hover`) come after the plain utility
+    selector_order: Vec<u32>,
+}
+
+/// A rule being written, possibly shared by several selectors
+struct MergedRule<'a> {
+    /// Selectors with the class token each was generated from (see
+    /// `source_class`)
+    selectors: Vec<(&'a str, &'a str)>,
+    properties: &'a [CSSProperty],
+    important: bool,
+
+    /// Property families the declarations touch (see `property_family`)
+    families: Vec<&'a str>,
+
+    /// Whether the rule must keep a selector list of its own, because a
+    /// browser that rejects one selector drops the whole list
+    isolated: bool,
+}
+
+/// Find an earlier rule with the same declarations that a new rule can join
+///
+/// Joining moves the new rule up to that rule's position, so every rule in
+/// between must leave the new rule's property families alone.
+fn merge_target(written: &[MergedRule<'_>], new: &MergedRule<'_>) -> Option<usize> {
+    if new.isolated {
+        return None;
+    }
+    for (index, rule) in written.iter().enumerate().rev() {
+        if !rule.isolated && rule.properties == new.properties && rule.important == new.important {
+            return Some(index);
+        }
+        if rule.families.iter().any(|family| new.families.contains(family)) {
+            return None;
+        }
     }
+    None
 }
 
-/// Escape special characters in CSS selector
-fn escape_css_selector(s: &str) -> String {
-    let mut result = String::with_capacity(s.len() * 2);
+/// Write a pre-formatted CSS rule without the whitespace it does not need
+///
+/// The rule is split at `{`, `}` and `;` outside of strings; preludes keep
+/// single spaces between their parts (`a b` differs from `ab`), while
+/// declarations also lose the spaces around their colon.
+fn write_minified<W: Write>(css: &str, out: &mut Output<'_, W>) -> fmt::Result {
+    let mut segment = String::new();
+    let mut quote = None;
+    let mut escaped = false;
+    for ch in css.chars() {
+        if let Some(open) = quote {
+            segment.push(ch);
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == open {
+                quote = None;
+            }
+            continue;
+        }
 
-    for ch in s.chars() {
         match ch {
-            ':' | '[' | ']' | '(' | ')' | '/' | '!' | '@' | '#' | '$' | '%' | '^' | '&' | '*'
-            | '+' | '=' | '~' | '`' | '{' | '}' | '|' | '\\' | ';' | ',' | '.' | '?' | '<' | '>' => {
-                result.push('\\');
-                result.push(ch);
+            '"' | '\'' => {
+                quote = Some(ch);
+                segment.push(ch);
             }
-            _ => result.push(ch),
+            '{' | '}' | ';' => {
+                out.write_str(&minify_segment(&segment, ch != '{'))?;
+                out.write_char(ch)?;
+                segment.clear();
+            }
+            _ => segment.push(ch),
         }
     }
-
-    result
+    out.write_str(&minify_segment(&segment, false))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::types::{CSSProperty, ParsedClass};
+/// Collapse the whitespace of a selector, at-rule prelude or declaration
+fn minify_segment(segment: &str, declaration: bool) -> String {
+    let mut minified = String::with_capacity(segment.len());
+    let mut quote = None;
+    let mut space = false;
+    for ch in segment.chars() {
+        if quote.is_none() && ch.is_whitespace() {
+            space = true;
+            continue;
+        }
+        if space && !minified.is_empty() && !minified.ends_with(',') && (ch != ',' || quote.is_some()) {
+            minified.push(' ');
+        }
+        space = false;
 
-    fn make_result(class: &str, property: &str, value: &str) -> MatchResult {
-        MatchResult {
-            parsed: ParsedClass {
-                raw: class.to_string(),
-                utility: class.to_string(),
-                value: None,
-                variants: vec![],
-                opacity: None,
-                arbitrary: None,
-                important: false,
-                negative: false,
-            },
-            properties: vec![CSSProperty::new(property, value)],
-            pattern_name: "test".to_string(),
-            layer: Layer::Utilities,
-            sort_order: 0,
+        minified.push(ch);
+        match quote {
+            Some(open) if ch == open && !minified[..minified.len() - 1].ends_with('\\') => quote = None,
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            _ => {}
         }
     }
 
-    #[test]
-    fn test_generate_simple() {
-        let generator = Generator::new();
-        let results = vec![make_result("p-4", "padding", "1rem")];
+    if declaration {
+        if let Some((name, value)) = minified.split_once(':') {
+            return format!("{}:{}", name.trim_end(), value.trim_start());
+        }
+    }
+    minified
+}
 
-        let css = generator.generate(&results);
-        assert!(css.contains(".p-4"));
-        assert!(css.contains("padding: 1rem"));
+/// Whether a selector uses a pseudo that some browsers reject: a
+/// vendor-prefixed one (e.g., `::-webkit-scrollbar`) or `:has()`
+///
+/// Escaped characters, such as the `\:` of a class name, are skipped.
+fn has_isolated_pseudo(selector: &str) -> bool {
+    let mut chars = selector.char_indices().peekable();
+    while let Some((_, ch)) = chars.next() {
+        match ch {
+            '\\' => {
+                chars.next();
+            }
+            ':' => {
+                if let Some(&(_, ':')) = chars.peek() {
+                    chars.next();
+                }
+                match chars.peek() {
+                    Some(&(_, '-')) => return true,
+                    Some(&(i, _)) if selector[i..].starts_with("has(") => return true,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
     }
+    false
+}
 
-    #[test]
-    fn test_escape_selector() {
-        let escaped = escape_css_selector("hover:bg-blue-500");
-        assert_eq!(escaped, "hover\\:bg-blue-500");
+/// Shorthand family of a CSS property, used to detect declarations that can
+/// override each other (e.g., "padding-left" and "padding", "top" and "inset")
+///
+/// Custom properties only conflict with themselves.
+fn property_family(property: &str) -> &str {
+    if property.starts_with("--") {
+        return property;
     }
 
-    #[test]
-    fn test_generate_with_variants() {
-        let generator = Generator::new();
-        let mut result = make_result("hover:bg-blue-500", "background-color", "#3b82f6");
-        result.parsed.variants = vec!["hover".to_string()];
+    let root = property.trim_start_matches('-').split('-').next().unwrap_or(property);
+    match root {
+        "top" | "right" | "bottom" | "left" => "inset",
+        "column" | "row" if property.ends_with("-gap") => "gap",
+        "align" | "justify" => "place",
+        "line" if property == "line-height" => "font",
+        _ => root,
+    }
+}
 
-        let results = vec![result];
-        let css = generator.generate(&results);
+/// Stylesheet with the source map of its rules
+#[derive(Debug, Clone, Default)]
+pub struct GeneratedCss {
+    pub css: String,
 
-        assert!(css.contains("hover\\:bg-blue-500:hover"));
-    }
+    /// Present when `source_map` is enabled
+    pub map: Option<SourceMap>,
+}
 
-    #[test]
-    fn test_generate_important() {
-        let generator = Generator::new();
-        let mut result = make_result("!p-4", "padding", "1rem");
-        result.parsed.important = true;
+/// Output sink that knows where class usages are and, when building a
+/// source map, tracks the generated position
+struct Output<'s, W> {
+    inner: W,
+    sources: Option<&'s SourceIndex>,
+    map: Option<SourceMapBuilder>,
 
-        let results = vec![result];
-        let css = generator.generate(&results);
+    /// Current generated line and column (UTF-16 units), 0-based
+    line: u32,
+    column: u32,
+}
 
-        assert!(css.contains("!important"));
+impl<'s, W: Write> Output<'s, W> {
+    fn new(inner: W, sources: Option<&'s SourceIndex>, source_map: bool) -> Self {
+        Self {
+            inner,
+            sources,
+            map: source_map.then(SourceMapBuilder::default),
+            line: 0,
+            column: 0,
+        }
     }
 
-    #[test]
-    fn test_minify() {
+    /// Recorded usages of `class`
+    fn locations(&self, class: &str) -> &'s [SourceLocation] {
+        self.sources.map(|sources| sources.locations(class)).unwrap_or(&[])
+    }
+
+    /// Map the current position to the first (or every) usage of `class`
+    fn map_class(&mut self, class: &str, all: bool) {
+        let locations = self.locations(class);
+        let Some(ref mut map) = self.map else {
+            return;
+        };
+
+        let count = if all { locations.len() } else { locations.len().min(1) };
+        for location in &locations[..count] {
+            map.add(self.line, self.column, location);
+        }
+    }
+}
+
+impl<W: Write> Write for Output<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.map.is_some() {
+            match s.rfind('\n') {
+                Some(last) => {
+                    self.line += s.matches('\n').count() as u32;
+                    self.column = s[last + 1..].encode_utf16().count() as u32;
+                }
+                None => self.column += s.encode_utf16().count() as u32,
+            }
+        }
+        self.inner.write_str(s)
+    }
+}
+
+/// `fmt::Write` view of an `io::Write` sink that keeps the underlying error
+struct IoAdapter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> Write for IoAdapter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+/// A bound contributed by one responsive variant
+enum WidthBound {
+    /// Inclusive minimum width (e.g., "768px" from `md`)
+    Min(String),
+
+    /// Exclusive maximum width (e.g., "1280px" from `max-xl`)
+    Max(String),
+}
+
+/// Width bounds collected from all responsive variants of a class
+#[derive(Debug, Default)]
+struct WidthRange {
+    min: Vec<String>,
+    max: Vec<String>,
+}
+
+impl WidthRange {
+    /// Render as a single media query
+    fn media_query(&self, range_syntax: bool) -> String {
+        format!("@media {}", self.conditions(range_syntax))
+    }
+
+    /// Render the bounds as query conditions joined with `and`
+    ///
+    /// Classic syntax subtracts 0.02px from upper bounds to keep them
+    /// exclusive; range syntax writes `width < X` directly.
+    fn conditions(&self, range_syntax: bool) -> String {
+        let conditions: Vec<String> = if range_syntax {
+            match (self.min.as_slice(), self.max.as_slice()) {
+                ([min], [max]) => vec![format!("({} <= width < {})", min, max)],
+                (min, max) => min
+                    .iter()
+                    .map(|v| format!("(width >= {})", v))
+                    .chain(max.iter().map(|v| format!("(width < {})", v)))
+                    .collect(),
+            }
+        } else {
+            self.min
+                .iter()
+                .map(|v| format!("(min-width: {})", v))
+                .chain(self.max.iter().map(|v| format!("(max-width: {})", below(v))))
+                .collect()
+        };
+
+        conditions.join(" and ")
+    }
+
+    /// Rank among width queries: upper-bound-only queries first, widest
+    /// first, then by lower bound from narrowest to widest, with narrower
+    /// ranges after wider ones sharing the same lower bound
+    fn rank(&self) -> u64 {
+        let centi_px = |px: f64| (px * 100.0).round().min(u32::MAX as f64 - 1.0) as u64;
+
+        let min = self.min.iter().map(|v| length_px(v)).reduce(f64::max);
+        let max = self.max.iter().map(|v| length_px(v)).reduce(f64::min);
+
+        let low = min.map_or(0, |px| centi_px(px) + 1);
+        let high = max.map_or(0, |px| u32::MAX as u64 - centi_px(px));
+        (low << 32) | high
+    }
+}
+
+/// Largest width below `value`, for exclusive `max-width` bounds
+fn below(value: &str) -> String {
+    match length_px(value) {
+        px if px < f64::MAX => format!("{}px", px - 0.02),
+        _ => format!("calc({} - 0.02px)", value),
+    }
+}
+
+/// Indentation for the given nesting depth
+#[inline]
+fn indent(depth: usize) -> &'static str {
+    const SPACES: &str = "                ";
+    &SPACES[..(depth * 2).min(SPACES.len())]
+}
+
+/// Check for a plain attribute-name suffix (e.g., "active" in `data-active`)
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+/// Selector matching the element a class is written on
+///
+/// Classes expanded from a variant group are written as part of the group,
+/// whose whitespace-separated pieces are the class tokens the browser sees
+/// (`hover:(bg-red-500` and `text-white)`), so the selector requires all of
+/// them. Other classes select their own escaped name.
+fn class_selector(parsed: &ParsedClass) -> String {
+    match parsed.source {
+        Some(ref source) => source
+            .split_whitespace()
+            .map(|token| format!(".{}", escape_css_selector(token)))
+            .collect(),
+        None => format!(".{}", escape_css_selector(&parsed.raw)),
+    }
+}
+
+/// Class token whose usages a rule maps back to: the group token for
+/// classes expanded from a variant group, the class itself otherwise
+fn source_class(parsed: &ParsedClass) -> &str {
+    parsed.source.as_deref().unwrap_or(&parsed.raw)
+}
+
+/// Escape special characters in CSS selector
+fn escape_css_selector(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() * 2);
+
+    for ch in s.chars() {
+        match ch {
+            ':' | '[' | ']' | '(' | ')' | '/' | '!' | '@' | '#' | '$' | '%' | '^' | '&' | '*'
+            | '+' | '=' | '~' | '`' | '{' | '}' | '|' | '\\' | ';' | ',' | '.' | '?' | '<' | '>'
+            | '"' | '\'' => {
+                result.push('\\');
+                result.push(ch);
+            }
+            _ => result.push(ch),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CSSProperty, ClassKind, ParsedClass};
+
+    fn make_result(class: &str, property: &str, value: &str) -> MatchResult {
+        MatchResult {
+            parsed: ParsedClass {
+                raw: class.to_string(),
+                utility: class.to_string(),
+                value: None,
+                variants: vec![],
+                opacity: None,
+                arbitrary: None,
+                important: false,
+                negative: false,
+                source: None,
+                diagnostics: Vec::new(),
+                kind: ClassKind::Utility,
+            },
+            properties: vec![CSSProperty::new(property, value)],
+            pattern_name: "test".to_string(),
+            layer: Layer::Utilities,
+            sort_order: 0,
+            selector_suffix: None,
+            extra_rules: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_generate_simple() {
+        let generator = Generator::new();
+        let results = vec![make_result("p-4", "padding", "1rem")];
+
+        let css = generator.generate(&results);
+        assert!(css.contains(".p-4"));
+        assert!(css.contains("padding: 1rem"));
+    }
+
+    #[test]
+    fn test_escape_selector() {
+        let escaped = escape_css_selector("hover:bg-blue-500");
+        assert_eq!(escaped, "hover\\:bg-blue-500");
+    }
+
+    #[test]
+    fn test_generate_variant_group_selector() {
+        let generator = Generator::new();
+        let mut result = make_result("hover:bg-red-500", "background-color", "#ef4444");
+        result.parsed.variants = vec!["hover".to_string()];
+        result.parsed.source = Some("hover:(bg-red-500 text-white)".to_string());
+
+        // Both class tokens of the group as it appears in markup
+        let css = generator.generate(&[result]);
+        assert!(css.contains(".hover\\:\\(bg-red-500.text-white\\):hover {"), "{}", css);
+    }
+
+    #[test]
+    fn test_generate_with_variants() {
+        let generator = Generator::new();
+        let mut result = make_result("hover:bg-blue-500", "background-color", "#3b82f6");
+        result.parsed.variants = vec!["hover".to_string()];
+
+        let results = vec![result];
+        let css = generator.generate(&results);
+
+        assert!(css.contains("hover\\:bg-blue-500:hover"));
+    }
+
+    #[test]
+    fn test_generate_important() {
+        let generator = Generator::new();
+        let mut result = make_result("!p-4", "padding", "1rem");
+        result.parsed.important = true;
+
+        let results = vec![result];
+        let css = generator.generate(&results);
+
+        assert!(css.contains("!important"));
+    }
+
+    #[test]
+    fn test_generate_global_important() {
+        let generator = Generator::with_options(GenerateOptions {
+            important: Important::Flag(true),
+            ..Default::default()
+        });
+        let css = generator.generate(&[make_result("p-4", "padding", "1rem")]);
+        assert!(css.contains("padding: 1rem !important;"));
+    }
+
+    #[test]
+    fn test_generate_important_selector_scope() {
+        let generator = Generator::with_options(GenerateOptions {
+            important: Important::from("#app"),
+            ..Default::default()
+        });
+
+        let mut hover = make_result("hover:p-4", "padding", "1rem");
+        hover.parsed.variants = vec!["hover".to_string()];
+
+        let css = generator.generate(&[make_result("p-4", "padding", "1rem"), hover]);
+        assert!(css.contains("#app .p-4 {"));
+        assert!(css.contains("#app .hover\\:p-4:hover {"));
+        assert!(!css.contains("!important"));
+    }
+
+    #[test]
+    fn test_minify() {
         let generator = Generator::with_options(GenerateOptions {
             minify: true,
             ..Default::default()
@@ -393,4 +1258,643 @@ mod tests {
         // Minified output should not have newlines
         assert!(!css.contains("\n  "));
     }
+
+    #[test]
+    fn test_generate_media_query() {
+        let generator = Generator::new();
+        let mut result = make_result("md:flex", "display", "flex");
+        result.parsed.variants = vec!["md".to_string()];
+
+        let css = generator.generate(&[make_result("block", "display", "block"), result]);
+
+        assert!(css.contains("@media (min-width: 768px) {\n  .md\\:flex {\n    display: flex;\n  }\n}"));
+        // Unconditional rules stay outside the media block
+        assert!(css.find(".block").unwrap() < css.find("@media").unwrap());
+    }
+
+    #[test]
+    fn test_generate_nested_at_rules() {
+        let generator = Generator::new();
+        let mut result = make_result("md:print:hidden", "display", "none");
+        result.parsed.variants = vec!["md".to_string(), "print".to_string()];
+
+        let css = generator.generate(&[result]);
+
+        let outer = css.find("@media (min-width: 768px) {").unwrap();
+        let inner = css.find("  @media print {").unwrap();
+        let rule = css.find("    .md\\:print\\:hidden {").unwrap();
+        assert!(outer < inner && inner < rule);
+    }
+
+    #[test]
+    fn test_generate_groups_shared_queries_in_breakpoint_order() {
+        let generator = Generator::new();
+        let mut lg = make_result("lg:p-8", "padding", "2rem");
+        lg.parsed.variants = vec!["lg".to_string()];
+        let mut md_p = make_result("md:p-4", "padding", "1rem");
+        md_p.parsed.variants = vec!["md".to_string()];
+        let mut md_m = make_result("md:m-4", "margin", "1rem");
+        md_m.parsed.variants = vec!["md".to_string()];
+
+        let css = generator.generate(&[lg, md_p, md_m]);
+
+        assert_eq!(css.matches("@media (min-width: 768px)").count(), 1);
+        assert!(css.find("min-width: 768px").unwrap() < css.find("min-width: 1024px").unwrap());
+    }
+
+    #[test]
+    fn test_generate_media_query_minified() {
+        let generator = Generator::with_options(GenerateOptions {
+            minify: true,
+            ..Default::default()
+        });
+        let mut result = make_result("sm:p-4", "padding", "1rem");
+        result.parsed.variants = vec!["sm".to_string()];
+
+        let css = generator.generate(&[result]);
+
+        assert!(css.contains("@media (min-width: 640px){.sm\\:p-4{padding:1rem;}}"));
+    }
+
+    #[test]
+    fn test_generate_group_and_peer_variants() {
+        let generator = Generator::new();
+
+        let mut group = make_result("group-hover:p-4", "padding", "1rem");
+        group.parsed.variants = vec!["group-hover".to_string()];
+        let mut peer = make_result("peer-checked:focus:p-4", "padding", "1rem");
+        peer.parsed.variants = vec!["peer-checked".to_string(), "focus".to_string()];
+        let mut named = make_result("group-focus-within/card:p-4", "padding", "1rem");
+        named.parsed.variants = vec!["group-focus-within/card".to_string()];
+
+        let css = generator.generate(&[group, peer, named]);
+        assert!(css.contains(".group:hover .group-hover\\:p-4 {"));
+        assert!(css.contains(".peer:checked ~ .peer-checked\\:focus\\:p-4:focus {"));
+        assert!(css.contains(".group\\/card:focus-within .group-focus-within\\/card\\:p-4 {"));
+    }
+
+    fn selector_for(generator: &Generator, variants: &[&str]) -> String {
+        let mut result = make_result("x", "padding", "1rem");
+        result.parsed.variants = variants.iter().map(|v| v.to_string()).collect();
+        generator.generate_scope(&result).selector
+    }
+
+    #[test]
+    fn test_generate_structural_variants() {
+        let generator = Generator::new();
+
+        assert_eq!(selector_for(&generator, &["aria-checked"]), r#".x[aria-checked="true"]"#);
+        assert_eq!(selector_for(&generator, &["aria-[sort=ascending]"]), ".x[aria-sort=ascending]");
+        assert_eq!(selector_for(&generator, &["data-[state=open]"]), ".x[data-state=open]");
+        assert_eq!(selector_for(&generator, &["data-active"]), ".x[data-active]");
+        assert_eq!(selector_for(&generator, &["has-[:checked]"]), ".x:has(:checked)");
+        assert_eq!(selector_for(&generator, &["has-checked"]), ".x:has(*:checked)");
+        assert_eq!(selector_for(&generator, &["not-first"]), ".x:not(:first-child)");
+        assert_eq!(selector_for(&generator, &["not-[.active]"]), ".x:not(.active)");
+        assert_eq!(selector_for(&generator, &["in-focus"]), ":where(*:focus) .x");
+        assert_eq!(selector_for(&generator, &["nth-3"]), ".x:nth-child(3)");
+        assert_eq!(selector_for(&generator, &["nth-[2n+1]"]), ".x:nth-child(2n+1)");
+        assert_eq!(selector_for(&generator, &["nth-last-of-type-2"]), ".x:nth-last-of-type(2)");
+        assert_eq!(selector_for(&generator, &["*"]), ":is(.x > *)");
+        assert_eq!(selector_for(&generator, &["*", "hover"]), ":is(.x:hover > *)");
+        assert_eq!(
+            selector_for(&generator, &["group-aria-expanded/nav"]),
+            r#".group\/nav[aria-expanded="true"] .x"#
+        );
+        assert_eq!(selector_for(&generator, &["peer-data-[state=on]"]), ".peer[data-state=on] ~ .x");
+
+        // Pseudo-classes that share a family prefix still resolve normally
+        assert_eq!(selector_for(&generator, &["in-range"]), ".x:in-range");
+    }
+
+    #[test]
+    fn test_generate_theme_aria_and_data() {
+        let theme = Theme::default()
+            .with_aria("current", r#"current="page""#)
+            .with_data("open", r#"state="open""#);
+        let generator = Generator::with_theme(GenerateOptions::default(), Arc::new(theme));
+
+        assert_eq!(selector_for(&generator, &["aria-current"]), r#".x[aria-current="page"]"#);
+        assert_eq!(selector_for(&generator, &["data-open"]), r#".x[data-state="open"]"#);
+
+        let mut result = make_result(r#"data-[state="open"]:p-4"#, "padding", "1rem");
+        result.parsed.variants = vec![r#"data-[state="open"]"#.to_string()];
+        let css = generator.generate(&[result]);
+        assert!(css.contains(r#".data-\[state\=\"open\"\]\:p-4[data-state="open"] {"#));
+    }
+
+    #[test]
+    fn test_generate_dark_mode_strategies() {
+        let css_for = |dark_mode: DarkMode| {
+            let generator = Generator::with_options(GenerateOptions {
+                dark_mode,
+                ..Default::default()
+            });
+            let mut result = make_result("dark:hover:bg-x", "background-color", "red");
+            result.parsed.variants = vec!["dark".to_string(), "hover".to_string()];
+            generator.generate(&[result])
+        };
+
+        let css = css_for(DarkMode::default());
+        assert!(css.contains(".dark .dark\\:hover\\:bg-x:hover {"));
+
+        let css = css_for(DarkMode::Class("theme-dark".to_string()));
+        assert!(css.contains(".theme-dark .dark\\:hover\\:bg-x:hover {"));
+
+        let css = css_for(DarkMode::Attribute(r#"[data-theme="dark"]"#.to_string()));
+        assert!(css.contains(r#"[data-theme="dark"] .dark\:hover\:bg-x:hover {"#));
+
+        let css = css_for(DarkMode::Media);
+        assert!(css.contains("@media (prefers-color-scheme: dark) {\n  .dark\\:hover\\:bg-x:hover {"));
+
+        let css = css_for(DarkMode::Where(".dark".to_string()));
+        assert!(css.contains(".dark\\:hover\\:bg-x:hover:where(.dark, .dark *) {"));
+    }
+
+    #[test]
+    fn test_generate_arbitrary_selector_variant() {
+        let generator = Generator::new();
+
+        let mut children = make_result("[&>*]:p-4", "padding", "1rem");
+        children.parsed.variants = vec!["[&>*]".to_string()];
+        let mut dark = make_result("[.dark_&]:hover:p-4", "padding", "1rem");
+        dark.parsed.variants = vec!["[.dark_&]".to_string(), "hover".to_string()];
+
+        let css = generator.generate(&[children, dark]);
+        assert!(css.contains(".\\[\\&\\>\\*\\]\\:p-4>* {"));
+        assert!(css.contains(".dark .\\[\\.dark_\\&\\]\\:hover\\:p-4:hover {"));
+    }
+
+    #[test]
+    fn test_generate_arbitrary_at_rule_variant() {
+        let generator = Generator::new();
+
+        let mut media = make_result("[@media(min-width:900px)]:p-4", "padding", "1rem");
+        media.parsed.variants = vec!["[@media(min-width:900px)]".to_string()];
+        let mut supports = make_result("md:[@supports(display:grid)]:grid", "display", "grid");
+        supports.parsed.variants = vec!["md".to_string(), "[@supports(display:grid)]".to_string()];
+
+        let css = generator.generate(&[media, supports]);
+        assert!(css.contains("@media (min-width:900px) {"));
+        assert!(css.contains("@media (min-width: 768px) {\n  @supports (display:grid) {"));
+    }
+
+    #[test]
+    fn test_generate_theme_screens() {
+        let theme = Theme::default().with_screen("tablet", "900px").with_screen("xs", "400px");
+        let generator = Generator::with_theme(GenerateOptions::default(), Arc::new(theme));
+
+        let mut tablet = make_result("tablet:p-4", "padding", "1rem");
+        tablet.parsed.variants = vec!["tablet".to_string()];
+        let mut xs = make_result("xs:p-2", "padding", "0.5rem");
+        xs.parsed.variants = vec!["xs".to_string()];
+
+        let css = generator.generate(&[tablet, xs]);
+        let xs_at = css.find("@media (min-width: 400px)").unwrap();
+        let tablet_at = css.find("@media (min-width: 900px)").unwrap();
+        assert!(xs_at < tablet_at);
+    }
+
+    fn responsive(generator: &Generator, classes: &[&str]) -> String {
+        let results: Vec<MatchResult> = classes
+            .iter()
+            .map(|class| {
+                let mut result = make_result(class, "display", "flex");
+                let (variants, _) = class.rsplit_once(':').unwrap();
+                result.parsed.variants = variants.split(':').map(str::to_string).collect();
+                result
+            })
+            .collect();
+        generator.generate(&results)
+    }
+
+    #[test]
+    fn test_generate_max_and_arbitrary_breakpoints() {
+        let generator = Generator::new();
+        let css = responsive(&generator, &["max-md:flex", "min-[900px]:flex", "max-[600px]:flex", "max-[40rem]:flex"]);
+
+        assert!(css.contains("@media (max-width: 767.98px) {"));
+        assert!(css.contains("@media (min-width: 900px) {"));
+        assert!(css.contains("@media (max-width: 599.98px) {"));
+        assert!(css.contains("@media (max-width: 639.98px) {"));
+    }
+
+    #[test]
+    fn test_generate_stacked_breakpoints_merge() {
+        let generator = Generator::new();
+        let css = responsive(&generator, &["md:hover:max-xl:flex"]);
+
+        assert!(css.contains("@media (min-width: 768px) and (max-width: 1279.98px) {"));
+        assert_eq!(css.matches("@media").count(), 1);
+        assert!(css.contains(".md\\:hover\\:max-xl\\:flex:hover {"));
+    }
+
+    #[test]
+    fn test_generate_breakpoint_order() {
+        let generator = Generator::new();
+        let css = responsive(
+            &generator,
+            &["lg:flex", "max-md:flex", "md:max-lg:flex", "md:flex", "max-xl:flex", "md:max-xl:flex"],
+        );
+
+        let position = |query: &str| css.find(query).unwrap_or_else(|| panic!("missing {}", query));
+        let expected = [
+            "@media (max-width: 1279.98px)",
+            "@media (max-width: 767.98px)",
+            "@media (min-width: 768px) {",
+            "@media (min-width: 768px) and (max-width: 1279.98px)",
+            "@media (min-width: 768px) and (max-width: 1023.98px)",
+            "@media (min-width: 1024px)",
+        ];
+        for pair in expected.windows(2) {
+            assert!(position(pair[0]) < position(pair[1]), "{} before {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn test_generate_media_range_syntax() {
+        let generator = Generator::with_options(GenerateOptions {
+            media_range_syntax: true,
+            ..Default::default()
+        });
+        let css = responsive(&generator, &["md:flex", "max-md:flex", "sm:max-lg:flex"]);
+
+        assert!(css.contains("@media (width >= 768px) {"));
+        assert!(css.contains("@media (width < 768px) {"));
+        assert!(css.contains("@media (640px <= width < 1024px) {"));
+    }
+
+    #[test]
+    fn test_generate_container_queries() {
+        let generator = Generator::new();
+        let css = responsive(
+            &generator,
+            &["@lg:flex", "@md:flex", "@max-md:flex", "@[500px]:flex", "@md/sidebar:flex", "md:@sm:flex"],
+        );
+
+        assert!(css.contains("@container (min-width: 28rem) {\n  .\\@md\\:flex {"));
+        assert!(css.contains("@container (max-width: 447.98px) {"));
+        assert!(css.contains("@container (min-width: 500px) {"));
+        assert!(css.contains("@container sidebar (min-width: 28rem) {"));
+        assert!(css.contains("@media (min-width: 768px) {\n  @container (min-width: 20rem) {"));
+        assert!(css.find("@container (min-width: 28rem)").unwrap() < css.find("@container (min-width: 32rem)").unwrap());
+    }
+
+    #[test]
+    fn test_generate_container_sizes_from_theme() {
+        let theme = Theme::default().with_container("card", "300px");
+        let generator = Generator::with_theme(
+            GenerateOptions {
+                media_range_syntax: true,
+                ..Default::default()
+            },
+            Arc::new(theme),
+        );
+        let css = responsive(&generator, &["@card:flex", "@max-card/main:flex"]);
+
+        assert!(css.contains("@container (width >= 300px) {"));
+        assert!(css.contains("@container main (width < 300px) {"));
+    }
+
+    #[test]
+    fn test_generate_is_independent_of_input_order() {
+        let generator = Generator::new();
+        let mut results = vec![
+            make_result("p-4", "padding", "1rem"),
+            make_result("m-2", "margin", "0.5rem"),
+            make_result("flex", "display", "flex"),
+        ];
+        let mut hover = make_result("hover:p-2", "padding", "0.5rem");
+        hover.parsed.variants = vec!["hover".to_string()];
+        let mut md = make_result("md:p-8", "padding", "2rem");
+        md.parsed.variants = vec!["md".to_string()];
+        results.extend([hover, md]);
+
+        let css = generator.generate(&results);
+        results.reverse();
+        assert_eq!(generator.generate(&results), css);
+        results.rotate_left(2);
+        assert_eq!(generator.generate(&results), css);
+    }
+
+    #[test]
+    fn test_generate_variant_order() {
+        let generator = Generator::new();
+        let mut md = make_result("md:p-8", "padding", "2rem");
+        md.parsed.variants = vec!["md".to_string()];
+        let mut hover = make_result("hover:p-2", "padding", "0.5rem");
+        hover.parsed.variants = vec!["hover".to_string()];
+        let mut before = make_result("before:p-1", "padding", "0.25rem");
+        before.parsed.variants = vec!["before".to_string()];
+        let base = make_result("p-4", "padding", "1rem");
+
+        let css = generator.generate(&[md, before, hover, base]);
+        let base_at = css.find(".p-4 {").unwrap();
+        let hover_at = css.find(".hover\\:p-2:hover {").unwrap();
+        let before_at = css.find(".before\\:p-1::before {").unwrap();
+        let md_at = css.find("@media (min-width: 768px)").unwrap();
+        assert!(base_at < hover_at && hover_at < before_at && before_at < md_at);
+    }
+
+    #[test]
+    fn test_generate_sort_order_and_properties() {
+        let generator = Generator::new();
+
+        let mut pl = make_result("pl-1", "padding-left", "0.25rem");
+        pl.sort_order = 2;
+        let mut px = make_result("px-2", "padding-left", "0.5rem");
+        px.properties.push(CSSProperty::new("padding-right", "0.5rem"));
+        px.sort_order = 1;
+        let p = make_result("p-4", "padding", "1rem");
+        let color = make_result("bg-red-500", "background-color", "#ef4444");
+
+        let css = generator.generate(&[pl, px, p, color]);
+        let order: Vec<usize> = [".bg-red-500 {", ".p-4 {", ".px-2 {", ".pl-1 {"]
+            .iter()
+            .map(|selector| css.find(selector).unwrap())
+            .collect();
+        assert!(order.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_generate_extra_rules_once() {
+        let generator = Generator::new();
+        let keyframes = "@keyframes spin {\n  to {\n    transform: rotate(360deg);\n  }\n}";
+
+        let mut spin = make_result("animate-spin", "animation", "spin 1s linear infinite");
+        spin.extra_rules.push(keyframes.to_string());
+        let mut hover_spin = spin.clone();
+        hover_spin.parsed.variants = vec!["hover".to_string()];
+
+        let css = generator.generate(&[spin, hover_spin]);
+        assert_eq!(css.matches("@keyframes spin").count(), 1);
+        assert!(css.find("@keyframes").unwrap() > css.find(":hover").unwrap());
+    }
+
+    #[test]
+    fn test_generate_selector_suffix_after_variants() {
+        let generator = Generator::new();
+        let mut result = make_result("hover:placeholder-gray-400", "color", "#9ca3af");
+        result.parsed.variants = vec!["hover".to_string()];
+        result.selector_suffix = Some("::placeholder".to_string());
+
+        let css = generator.generate(&[result]);
+        assert!(css.contains(".hover\\:placeholder-gray-400:hover::placeholder {"));
+    }
+
+    #[test]
+    fn test_generate_deduplicates_rules() {
+        let generator = Generator::new();
+        let css = generator.generate(&[
+            make_result("p-4", "padding", "1rem"),
+            make_result("p-4", "padding", "1rem"),
+        ]);
+        assert_eq!(css.matches(".p-4 {").count(), 1);
+    }
+
+    #[test]
+    fn test_generate_merge_rules() {
+        let merging = Generator::with_options(GenerateOptions {
+            merge_rules: true,
+            ..Default::default()
+        });
+        let flex = make_result("flex", "display", "flex");
+        let mut md_flex = make_result("md:flex", "display", "flex");
+        md_flex.parsed.variants = vec!["md".to_string()];
+        let custom = make_result("d-flex", "display", "flex");
+
+        let css = merging.generate(&[flex.clone(), md_flex.clone(), custom.clone()]);
+        assert!(css.contains(".d-flex,\n.flex {"));
+        // Rules in another at-rule context stay separate
+        assert!(css.contains("  .md\\:flex {"));
+
+        let minified = Generator::with_options(GenerateOptions {
+            merge_rules: true,
+            minify: true,
+            ..Default::default()
+        });
+        assert!(minified.generate(&[flex.clone(), custom.clone()]).contains(".d-flex,.flex{display:flex;}"));
+
+        // Off by default
+        let css = Generator::new().generate(&[flex, custom]);
+        assert!(css.contains(".d-flex {") && css.contains(".flex {"));
+    }
+
+    #[test]
+    fn test_generate_merge_rules_keeps_vendor_pseudos_apart() {
+        let generator = Generator::with_options(GenerateOptions {
+            merge_rules: true,
+            ..Default::default()
+        });
+        let hidden = make_result("hidden", "display", "none");
+        let mut scrollbar = make_result("[&::-webkit-scrollbar]:hidden", "display", "none");
+        scrollbar.parsed.variants = vec!["[&::-webkit-scrollbar]".to_string()];
+        let mut placeholder = make_result("placeholder-hidden", "display", "none");
+        placeholder.selector_suffix = Some("::-moz-placeholder".to_string());
+        let mut negative = make_result("hover:-m-4", "display", "none");
+        negative.parsed.variants = vec!["hover".to_string()];
+        let mut child = make_result("[&>p]:hidden", "display", "none");
+        child.parsed.variants = vec!["[&>p]".to_string()];
+
+        // Escaped colons in class names are not pseudos
+        let css = generator.generate(&[hidden.clone(), negative, scrollbar, child]);
+        assert!(css.contains(".hidden,\n.hover\\:-m-4:hover {"));
+        assert!(css.contains("\n.\\[\\&\\:\\:-webkit-scrollbar\\]\\:hidden::-webkit-scrollbar {"));
+        assert!(css.contains("\n.\\[\\&\\>p\\]\\:hidden>p {"));
+
+        let css = generator.generate(&[hidden, placeholder]);
+        assert!(css.contains("\n.hidden {") && css.contains("\n.placeholder-hidden::-moz-placeholder {"));
+
+        assert!(has_isolated_pseudo("input:-moz-focusring"));
+        assert!(!has_isolated_pseudo(".hover\\:-m-4:hover"));
+    }
+
+    #[test]
+    fn test_generate_merge_rules_keeps_has_apart() {
+        let generator = Generator::with_options(GenerateOptions {
+            merge_rules: true,
+            ..Default::default()
+        });
+        let padded = make_result("p-4", "padding", "1rem");
+        let mut checked = make_result("has-[:checked]:p-4", "padding", "1rem");
+        checked.parsed.variants = vec!["has-[:checked]".to_string()];
+        let mut hovered = make_result("hover:p-4", "padding", "1rem");
+        hovered.parsed.variants = vec!["hover".to_string()];
+
+        let css = generator.generate(&[padded, checked, hovered]);
+        assert!(css.contains("\n.has-\\[\\:checked\\]\\:p-4:has(:checked) {"));
+        assert!(css.contains(".p-4,\n.hover\\:p-4:hover {"));
+
+        assert!(has_isolated_pseudo(".x:has(*:checked)"));
+        assert!(!has_isolated_pseudo(".has-checked\\:p-4"));
+    }
+
+    #[test]
+    fn test_generate_merge_rules_keeps_cascade() {
+        let generator = Generator::with_options(GenerateOptions {
+            merge_rules: true,
+            minify: true,
+            ..Default::default()
+        });
+
+        let mut a = make_result("a", "padding", "1rem");
+        a.sort_order = 0;
+        let mut b = make_result("b", "padding-left", "0");
+        b.sort_order = 1;
+        let mut c = make_result("c", "padding", "1rem");
+        c.sort_order = 2;
+        let mut d = make_result("d", "margin", "0");
+        d.sort_order = 3;
+        let mut e = make_result("e", "padding", "1rem");
+        e.sort_order = 4;
+
+        // `c` would move above `b` and lose to it, `e` only crosses `d`
+        let css = generator.generate(&[a, b, c, d, e]);
+        assert!(css.contains(".a{padding:1rem;}.b{padding-left:0;}.c,.e{padding:1rem;}.d{margin:0;}"));
+    }
+
+    #[test]
+    fn test_generate_into_and_write_to() {
+        let mut hover = make_result("hover:p-4", "padding", "1rem");
+        hover.parsed.variants = vec!["hover".to_string()];
+        let results = vec![make_result("flex", "display", "flex"), hover];
+
+        for minify in [false, true] {
+            let generator = Generator::with_options(GenerateOptions {
+                minify,
+                ..Default::default()
+            });
+            let css = generator.generate(&results);
+
+            let mut streamed = String::new();
+            generator.generate_into(&results, &mut streamed).unwrap();
+            assert_eq!(streamed, css);
+
+            let mut bytes = Vec::new();
+            generator.write_to(&results, &mut bytes).unwrap();
+            assert_eq!(String::from_utf8(bytes).unwrap(), css);
+        }
+    }
+
+    #[test]
+    fn test_write_to_reports_io_errors() {
+        struct Full;
+        impl io::Write for Full {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::StorageFull, "disk full"))
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let generator = Generator::new();
+        let err = generator
+            .write_to(&[make_result("flex", "display", "flex")], Full)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+    }
+
+    #[test]
+    fn test_minify_layers_and_extra_rules() {
+        let generator = Generator::with_options(GenerateOptions {
+            minify: true,
+            ..Default::default()
+        });
+        let mut spin = make_result("animate-spin", "animation", "spin 1s linear infinite");
+        spin.extra_rules
+            .push("@keyframes spin {\n  to {\n    transform: rotate(360deg);\n  }\n}".to_string());
+
+        let css = generator.generate(&[spin]);
+        assert_eq!(
+            css,
+            "@layer utilities{.animate-spin{animation:spin 1s linear infinite;}\
+             @keyframes spin{to{transform:rotate(360deg);}}}"
+        );
+
+        let mut ping = make_result("animate-ping", "animation", "ping 1s infinite");
+        ping.extra_rules.push(
+            "@keyframes ping {\n  75%, 100% {\n    transform: scale(2);\n    opacity: 0;\n  }\n}".to_string(),
+        );
+        let mut quoted = make_result("quoted", "content", "none");
+        quoted
+            .extra_rules
+            .push(".quoted::before {\n  content: \"a  {b};\";\n}\n@media (min-width: 40rem) {\n  a :hover {\n    color: red;\n  }\n}".to_string());
+
+        let css = generator.generate(&[ping, quoted]);
+        assert!(css.contains("@keyframes ping{75%,100%{transform:scale(2);opacity:0;}}"));
+        assert!(css.contains(".quoted::before{content:\"a  {b};\";}@media (min-width: 40rem){a :hover{color:red;}}"));
+    }
+
+    fn source_index(entries: &[(&str, &str, u32, u32)]) -> SourceIndex {
+        let mut index = SourceIndex::new();
+        for &(class, file, line, column) in entries {
+            index.insert(class, SourceLocation { file: file.to_string(), line, column });
+        }
+        index
+    }
+
+    #[test]
+    fn test_generate_source_map() {
+        let results = vec![make_result("flex", "display", "flex"), make_result("p-4", "padding", "1rem")];
+        let sources = source_index(&[("flex", "a.html", 1, 8), ("flex", "b.html", 3, 1), ("p-4", "a.html", 1, 13)]);
+
+        let first = Generator::with_options(GenerateOptions {
+            use_layers: false,
+            source_map: SourceMapMode::First,
+            ..Default::default()
+        });
+        let output = first.generate_with_sources(&results, &sources);
+        assert_eq!(output.css, first.generate(&results));
+        let map = output.map.unwrap();
+        assert_eq!(map.sources, vec!["a.html"]);
+        assert_eq!(map.mappings, "AAAO;;;AAAK");
+
+        let all = Generator::with_options(GenerateOptions {
+            use_layers: false,
+            source_map: SourceMapMode::All,
+            ..Default::default()
+        });
+        let map = all.generate_with_sources(&results, &sources).map.unwrap();
+        assert_eq!(map.sources, vec!["a.html", "b.html"]);
+        assert_eq!(map.mappings, "AAAO,ACEP;;;ADFY");
+
+        assert!(Generator::new().generate_with_sources(&results, &sources).map.is_none());
+    }
+
+    #[test]
+    fn test_generate_source_comments() {
+        let results = vec![make_result("flex", "display", "flex"), make_result("d-flex", "display", "flex")];
+        let sources = source_index(&[("flex", "src/App.tsx", 12, 5), ("d-flex", "src/Nav.tsx", 3, 9)]);
+
+        let generator = Generator::with_options(GenerateOptions {
+            source_comments: true,
+            merge_rules: true,
+            ..Default::default()
+        });
+        let css = generator.generate_with_sources(&results, &sources).css;
+        assert!(css.contains("/* src/Nav.tsx:3, src/App.tsx:12 */\n.d-flex,\n.flex {"));
+
+        let minified = Generator::with_options(GenerateOptions {
+            source_comments: true,
+            minify: true,
+            ..Default::default()
+        });
+        let css = minified.generate_with_sources(&results, &sources).css;
+        assert!(css.contains("/* src/App.tsx:12 */.flex{display:flex;}"));
+
+        // Without locations there is nothing to point to
+        assert!(!generator.generate(&results).contains("/*"));
+    }
+
+    #[test]
+    fn test_property_family() {
+        assert_eq!(property_family("padding-left"), "padding");
+        assert_eq!(property_family("top"), "inset");
+        assert_eq!(property_family("row-gap"), "gap");
+        assert_eq!(property_family("justify-content"), "place");
+        assert_eq!(property_family("line-height"), "font");
+        assert_eq!(property_family("-webkit-box-orient"), "webkit");
+        assert_eq!(property_family("--tw-ring-color"), "--tw-ring-color");
+    }
 }
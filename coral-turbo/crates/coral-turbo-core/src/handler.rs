@@ -0,0 +1,107 @@
+//! Pluggable utility handlers
+//!
+//! A handler turns a matched class into CSS declarations. Every `UtilityPattern`
+//! can carry its own handler, so custom utilities registered from Rust produce
+//! real CSS without touching the matcher.
+
+use crate::theme::Theme;
+use crate::types::{CSSProperty, ParsedClass, UtilityPattern};
+use std::sync::Arc;
+
+/// Key used in `UtilityPattern::values` for a class with nothing after its
+/// prefix (e.g., `rounded`, `border`, `flex`)
+pub const DEFAULT_VALUE_KEY: &str = "DEFAULT";
+
+/// Everything a handler needs to resolve a matched class
+#[derive(Debug, Clone, Copy)]
+pub struct HandlerContext<'a> {
+    /// The class being matched
+    pub parsed: &'a ParsedClass,
+
+    /// The pattern whose prefix and regex matched
+    pub pattern: &'a UtilityPattern,
+
+    /// Full utility string (e.g., "px-4", "bg-[#fff]")
+    pub full_class: &'a str,
+
+    /// Length of the matched prefix within `full_class`
+    pub prefix_len: usize,
+
+    /// Theme of the matching engine
+    pub theme: &'a Theme,
+}
+
+impl<'a> HandlerContext<'a> {
+    /// Class text after the matched prefix
+    ///
+    /// A leading `-` separator is dropped for prefixes without a trailing dash
+    /// (`rounded-lg` with prefix `rounded` yields `lg`), and an empty remainder
+    /// yields `DEFAULT_VALUE_KEY`. Returns `None` when the remainder does not
+    /// start at a separator (`roundedx`).
+    pub fn value_key(&self) -> Option<&'a str> {
+        let rest = self.full_class.get(self.prefix_len..)?;
+
+        if rest.is_empty() {
+            Some(DEFAULT_VALUE_KEY)
+        } else if self.pattern.prefix.ends_with('-') {
+            Some(rest)
+        } else {
+            rest.strip_prefix('-')
+        }
+    }
+}
+
+/// Declarations and extra top-level rules produced by a handler
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UtilityOutput {
+    /// Declarations for the class rule
+    pub properties: Vec<CSSProperty>,
+
+    /// Complete CSS rules the utility depends on (e.g., `@keyframes spin { ... }`),
+    /// emitted once per stylesheet
+    pub extra_rules: Vec<String>,
+}
+
+impl UtilityOutput {
+    /// Add a top-level rule the utility depends on
+    pub fn with_extra_rule(mut self, rule: impl Into<String>) -> Self {
+        self.extra_rules.push(rule.into());
+        self
+    }
+}
+
+impl From<Vec<CSSProperty>> for UtilityOutput {
+    fn from(properties: Vec<CSSProperty>) -> Self {
+        Self {
+            properties,
+            extra_rules: Vec::new(),
+        }
+    }
+}
+
+/// Resolves a matched class into CSS
+///
+/// Implemented for any `Fn(&HandlerContext) -> Option<UtilityOutput>`, so a
+/// closure can be attached with `UtilityPattern::with_handler`. Returning `None`
+/// lets the matcher try the next candidate pattern.
+pub trait UtilityHandler: Send + Sync {
+    fn handle(&self, ctx: &HandlerContext<'_>) -> Option<UtilityOutput>;
+}
+
+impl<F> UtilityHandler for F
+where
+    F: Fn(&HandlerContext<'_>) -> Option<UtilityOutput> + Send + Sync,
+{
+    fn handle(&self, ctx: &HandlerContext<'_>) -> Option<UtilityOutput> {
+        self(ctx)
+    }
+}
+
+impl std::fmt::Debug for dyn UtilityHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UtilityHandler")
+    }
+}
+
+/// Shared handler reference stored on patterns
+pub type HandlerRef = Arc<dyn UtilityHandler>;
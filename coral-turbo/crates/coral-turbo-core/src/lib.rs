@@ -7,8 +7,11 @@
 //!
 //! - **Parser**: Ultra-fast class name parsing with variant extraction
 //! - **Matcher**: Trie-based pattern matching for 700+ utility patterns
-//! - **Extractor**: Parallel file scanning with rayon
+//! - **Extractor**: Single-pass candidate scanning of any file type, in
+//!   parallel with rayon
 //! - **Generator**: Optimized CSS string generation
+//! - **Theme**: Per-engine design tokens, loadable from JSON or TOML
+//! - **Source maps**: Rules mapped back to the files that use each class
 //!
 //! ## Performance
 //!
@@ -19,18 +22,47 @@
 //! - Cache-friendly data structures
 
 pub mod types;
+pub mod theme;
+pub mod handler;
 pub mod parser;
 pub mod matcher;
 pub mod extractor;
 pub mod generator;
 pub mod trie;
 pub mod cache;
+pub mod source_map;
+pub mod scanner;
+#[cfg(feature = "fs")]
+pub mod content;
+pub mod session;
+pub mod stylesheet;
+#[cfg(feature = "watch")]
+pub mod watch;
 
 pub use types::*;
+pub use theme::{ColorValue, FontSize, Theme};
+pub use handler::{HandlerContext, UtilityHandler, UtilityOutput};
 pub use parser::Parser;
 pub use matcher::Matcher;
 pub use extractor::Extractor;
-pub use generator::Generator;
+pub use generator::{GeneratedCss, Generator};
+pub use source_map::{SourceIndex, SourceMap};
+pub use session::{ClassChanges, ExtractionSession};
+pub use stylesheet::{LiveStylesheet, PatchedRule, StylesheetPatch};
+#[cfg(feature = "watch")]
+pub use watch::{WatchError, WatchUpdate, Watcher};
+#[cfg(feature = "fs")]
+pub use content::GlobError;
+
+use cache::LruCache;
+use once_cell::sync::OnceCell;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+pub use cache::{CacheStats, EngineCacheStats};
+
+/// Cache key: the engine's cache epoch plus the class name
+type CacheKey = (u64, String);
 
 /// Engine version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -42,29 +74,163 @@ pub struct TurboEngine {
     matcher: Matcher,
     generator: Generator,
     extractor: Extractor,
+    theme: Arc<Theme>,
+    config: EngineConfig,
+
+    /// Dedicated worker pool for batch operations, built on first use
+    pool: OnceCell<Option<rayon::ThreadPool>>,
+
+    /// Parsed classes by class name
+    parse_cache: Option<LruCache<CacheKey, ParsedClass>>,
+
+    /// Match results by class name, including misses
+    match_cache: Option<LruCache<CacheKey, Option<MatchResult>>>,
+
+    /// Fingerprint of everything cached results depend on (theme, prefix,
+    /// registered patterns); entries from an older epoch are never hit
+    cache_epoch: u64,
 }
 
 impl TurboEngine {
     /// Create a new TurboEngine instance with default configuration
     pub fn new() -> Self {
-        Self {
-            parser: Parser::new(),
-            matcher: Matcher::new(),
-            generator: Generator::new(),
+        Self::with_config_and_theme(EngineConfig::default(), Theme::default())
+    }
+
+    /// Create a TurboEngine whose utilities and variants resolve against `theme`
+    pub fn with_theme(theme: Theme) -> Self {
+        Self::with_config_and_theme(EngineConfig::default(), theme)
+    }
+
+    /// Create a TurboEngine with custom configuration and the default theme
+    pub fn with_config(config: EngineConfig) -> Self {
+        Self::with_config_and_theme(config, Theme::default())
+    }
+
+    /// Create a TurboEngine with custom configuration and theme
+    pub fn with_config_and_theme(config: EngineConfig, theme: Theme) -> Self {
+        let theme = Arc::new(theme);
+
+        let mut engine = Self {
+            parser: build_parser(&config),
+            matcher: Matcher::with_theme(Arc::clone(&theme)),
+            generator: Generator::with_theme(generate_options(&config), Arc::clone(&theme)),
             extractor: Extractor::new(),
+            theme,
+            pool: OnceCell::new(),
+            parse_cache: build_cache(&config),
+            match_cache: build_cache(&config),
+            config,
+            cache_epoch: 0,
+        };
+
+        engine.refresh_cache_epoch();
+        engine
+    }
+
+    /// Switch to another theme, keeping registered utilities
+    ///
+    /// Cached parse and match results from the previous theme are not reused.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = Arc::new(theme);
+        self.matcher.set_theme(Arc::clone(&self.theme));
+        self.generator.set_theme(Arc::clone(&self.theme));
+        self.refresh_cache_epoch();
+    }
+
+    /// Apply a new configuration, keeping the theme and registered utilities
+    pub fn set_config(&mut self, config: EngineConfig) {
+        self.parser = build_parser(&config);
+        self.generator = Generator::with_theme(generate_options(&config), Arc::clone(&self.theme));
+
+        if config.thread_count != self.config.thread_count {
+            self.pool = OnceCell::new();
+        }
+
+        if config.cache_enabled != self.config.cache_enabled || config.cache_size != self.config.cache_size {
+            self.parse_cache = build_cache(&config);
+            self.match_cache = build_cache(&config);
+        }
+
+        self.config = config;
+        self.refresh_cache_epoch();
+    }
+
+    /// Hit/miss statistics of the parse and match caches (zero when disabled)
+    pub fn cache_stats(&self) -> EngineCacheStats {
+        EngineCacheStats {
+            parse: self.parse_cache.as_ref().map(LruCache::stats).unwrap_or_default(),
+            matcher: self.match_cache.as_ref().map(LruCache::stats).unwrap_or_default(),
         }
     }
 
+    /// Drop all cached parse and match results
+    pub fn clear_caches(&self) {
+        if let Some(ref cache) = self.parse_cache {
+            cache.clear();
+        }
+        if let Some(ref cache) = self.match_cache {
+            cache.clear();
+        }
+    }
+
+    /// Recompute the cache epoch after the theme, prefix or patterns change
+    fn refresh_cache_epoch(&mut self) {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.theme.hash(&mut hasher);
+        self.config.prefix.hash(&mut hasher);
+        self.matcher.pattern_count().hash(&mut hasher);
+        self.cache_epoch = hasher.finish();
+    }
+
+    /// Theme used by this engine
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Configuration this engine was built with
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+
     /// Parse a class string into individual ParsedClass structs
     #[inline]
     pub fn parse(&self, class_string: &str) -> Vec<ParsedClass> {
-        self.parser.parse_all(class_string)
+        let Some(ref cache) = self.parse_cache else {
+            return self.parser.parse_all(class_string);
+        };
+
+        self.parser.parse_all_with(class_string, |class| {
+            let key = (self.cache_epoch, class.to_string());
+            if let Some(parsed) = cache.get(&key) {
+                return parsed;
+            }
+
+            let parsed = self.parser.parse(class);
+            cache.insert(key, parsed.clone());
+            parsed
+        })
     }
 
     /// Match a parsed class against registered utility patterns
     #[inline]
     pub fn match_class(&self, parsed: &ParsedClass) -> Option<MatchResult> {
-        self.matcher.match_class(parsed)
+        let Some(ref cache) = self.match_cache else {
+            return self.matcher.match_class(parsed);
+        };
+
+        let key = (self.cache_epoch, parsed.raw.clone());
+        if let Some(cached) = cache.get(&key) {
+            // The cached result may come from another variant group member
+            return cached.map(|mut result| {
+                result.parsed = parsed.clone();
+                result
+            });
+        }
+
+        let result = self.matcher.match_class(parsed);
+        cache.insert(key, result.clone());
+        result
     }
 
     /// Generate CSS from match results
@@ -93,20 +259,105 @@ impl TurboEngine {
     pub fn process_batch(&self, class_strings: &[&str]) -> Vec<String> {
         use rayon::prelude::*;
 
-        class_strings
-            .par_iter()
-            .map(|s| self.process(s))
-            .collect()
+        self.install(|| {
+            class_strings
+                .par_iter()
+                .map(|s| self.process(s))
+                .collect()
+        })
     }
 
     /// Extract classes from multiple files in parallel
     pub fn extract_from_files(&self, contents: &[&str]) -> Vec<String> {
-        self.extractor.extract_parallel(contents)
+        self.install(|| self.extractor.extract_parallel(contents))
+    }
+
+    /// Extract class names from every file `config` selects, reading files
+    /// in parallel on the engine's worker pool
+    #[cfg(feature = "fs")]
+    pub fn scan(&self, config: &ContentConfig) -> Result<ExtractionResult, GlobError> {
+        self.install(|| self.extractor.scan(config))
+    }
+
+    /// Sync `session` with the files `config` selects, re-extracting changed
+    /// files in parallel on the engine's worker pool
+    #[cfg(feature = "fs")]
+    pub fn scan_session(
+        &self,
+        session: &mut ExtractionSession,
+        config: &ContentConfig,
+    ) -> Result<ClassChanges, GlobError> {
+        self.install(|| session.scan(config))
+    }
+
+    /// Start an incremental extraction session using this engine's extractor
+    pub fn extraction_session(&self) -> ExtractionSession {
+        ExtractionSession::with_extractor(self.extractor.clone())
+    }
+
+    /// Start an empty stylesheet that `LiveStylesheet::apply` keeps in sync
+    /// with an extraction session, generating with this engine's options
+    pub fn live_stylesheet(self: &Arc<Self>) -> LiveStylesheet {
+        LiveStylesheet::new(Arc::clone(self))
+    }
+
+    /// Extract class occurrences with their locations from `(file, content)`
+    /// pairs in parallel
+    pub fn extract_sources(&self, files: &[(&str, &str)]) -> SourceIndex {
+        self.install(|| self.extractor.extract_locations_parallel(files))
+            .into_iter()
+            .collect()
+    }
+
+    /// Generate CSS with source comments and a source map (see
+    /// `Generator::generate_with_sources`)
+    pub fn generate_with_sources(&self, results: &[MatchResult], sources: &SourceIndex) -> GeneratedCss {
+        self.generator.generate_with_sources(results, sources)
+    }
+
+    /// Full pipeline over source files: extract -> parse -> match -> generate,
+    /// mapping each rule back to the files that use its class
+    pub fn process_files(&self, files: &[(&str, &str)]) -> GeneratedCss {
+        let sources = self.extract_sources(files);
+
+        let mut classes: Vec<&str> = sources.classes().collect();
+        classes.sort_unstable();
+        let results: Vec<MatchResult> = classes
+            .iter()
+            .flat_map(|class| self.parse(class))
+            .filter_map(|parsed| self.match_class(&parsed))
+            .collect();
+
+        self.generate_with_sources(&results, &sources)
+    }
+
+    /// Run a parallel operation on the engine's worker pool
+    ///
+    /// `thread_count == 0`, or a pool that fails to build, falls back to the
+    /// global rayon pool.
+    fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        let pool = self.pool.get_or_init(|| {
+            if self.config.thread_count == 0 {
+                return None;
+            }
+
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(self.config.thread_count)
+                .thread_name(|i| format!("coral-turbo-{}", i))
+                .build()
+                .ok()
+        });
+
+        match pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
     }
 
     /// Register a custom utility pattern
     pub fn register_utility(&mut self, pattern: UtilityPattern) {
         self.matcher.register(pattern);
+        self.refresh_cache_epoch();
     }
 
     /// Register multiple utility patterns
@@ -114,9 +365,36 @@ impl TurboEngine {
         for pattern in patterns {
             self.matcher.register(pattern);
         }
+        self.refresh_cache_epoch();
     }
 }
 
+/// Parser recognizing the configured prefix
+fn build_parser(config: &EngineConfig) -> Parser {
+    match config.prefix {
+        Some(ref prefix) => Parser::with_prefix(prefix.as_str()),
+        None => Parser::new(),
+    }
+}
+
+/// Generator options derived from the engine configuration
+fn generate_options(config: &EngineConfig) -> GenerateOptions {
+    GenerateOptions {
+        important: config.important.clone(),
+        dark_mode: config.dark_mode.clone(),
+        media_range_syntax: config.media_range_syntax,
+        merge_rules: config.merge_rules,
+        source_comments: config.source_comments,
+        source_map: config.source_map,
+        ..Default::default()
+    }
+}
+
+/// A cache sized by the configuration, or `None` when caching is off
+fn build_cache<V: Clone>(config: &EngineConfig) -> Option<LruCache<CacheKey, V>> {
+    (config.cache_enabled && config.cache_size > 0).then(|| LruCache::new(config.cache_size))
+}
+
 impl Default for TurboEngine {
     fn default() -> Self {
         Self::new()
@@ -233,10 +511,257 @@ mod tests {
 
         let pattern = UtilityPattern::new("custom-spacing", "cs-")
             .with_pattern(r"^cs-\d+$")
-            .with_css_property("custom-spacing");
+            .with_css_property("custom-spacing")
+            .with_handler(|ctx: &HandlerContext<'_>| {
+                let value = format!("{}px", ctx.value_key()?);
+                Some(vec![CSSProperty::new(&ctx.pattern.css_property, value)].into())
+            });
 
         engine.register_utility(pattern);
-        // Registration should not panic
+
+        let css = engine.process("cs-3");
+        assert!(css.contains(".cs-3"));
+        assert!(css.contains("custom-spacing: 3px"));
+    }
+
+    #[test]
+    fn test_engines_with_different_themes() {
+        let default_engine = TurboEngine::new();
+        let themed = TurboEngine::with_theme(
+            Theme::default()
+                .with_spacing("4", "20px")
+                .with_color("brand", "#ff5500")
+                .with_screen("tablet", "900px"),
+        );
+
+        assert!(default_engine.process("p-4").contains("padding: 1rem"));
+        assert!(themed.process("p-4").contains("padding: 20px"));
+
+        assert!(default_engine.process("bg-brand").is_empty());
+        assert!(themed.process("bg-brand").contains("background-color: #ff5500"));
+
+        let css = themed.process("tablet:p-4");
+        assert!(css.contains("@media (min-width: 900px)"));
+        assert_eq!(themed.theme().spacing["4"], "20px");
+    }
+
+    #[test]
+    fn test_config_prefix() {
+        let engine = TurboEngine::with_config(EngineConfig {
+            prefix: Some("tw-".to_string()),
+            ..Default::default()
+        });
+
+        let css = engine.process("tw-p-4 hover:tw-bg-red-500 p-2");
+        assert!(css.contains(".tw-p-4 {"));
+        assert!(css.contains(".hover\\:tw-bg-red-500:hover {"));
+        assert!(!css.contains(".p-2"));
+    }
+
+    #[test]
+    fn test_config_important() {
+        let engine = TurboEngine::with_config(EngineConfig {
+            important: Important::Flag(true),
+            ..Default::default()
+        });
+        assert!(engine.process("p-4").contains("padding: 1rem !important;"));
+
+        let engine = TurboEngine::with_config(EngineConfig {
+            important: Important::from("#app"),
+            ..Default::default()
+        });
+        let css = engine.process("p-4");
+        assert!(css.contains("#app .p-4 {"));
+        assert!(!css.contains("!important"));
+    }
+
+    #[test]
+    fn test_config_dark_mode() {
+        let engine = TurboEngine::with_config(EngineConfig {
+            dark_mode: DarkMode::Media,
+            ..Default::default()
+        });
+        let css = engine.process("dark:hover:flex");
+
+        assert!(css.contains("@media (prefers-color-scheme: dark)"));
+        assert!(!css.contains(".dark .dark"));
+    }
+
+    #[test]
+    fn test_config_media_range_syntax() {
+        let engine = TurboEngine::with_config(EngineConfig {
+            media_range_syntax: true,
+            ..Default::default()
+        });
+        let css = engine.process("md:max-xl:flex");
+
+        assert!(css.contains("@media (768px <= width < 1280px)"));
+    }
+
+    #[test]
+    fn test_duplicate_and_merged_rules() {
+        let engine = TurboEngine::new();
+        let css = engine.process("p-4 p-4 m-2");
+        assert_eq!(css.matches(".p-4 {").count(), 1);
+
+        let engine = TurboEngine::with_config(EngineConfig {
+            merge_rules: true,
+            ..Default::default()
+        });
+        let css = engine.process("flex hover:flex md:block");
+        assert!(css.contains(".flex,\n.hover\\:flex:hover {"));
+    }
+
+    #[test]
+    fn test_process_files_with_sources() {
+        let engine = TurboEngine::with_config(EngineConfig {
+            source_comments: true,
+            source_map: SourceMapMode::First,
+            ..Default::default()
+        });
+        let files = [
+            ("src/App.tsx", "export const App = () => (\n  <div className=\"flex p-4\" />\n);\n"),
+            ("src/Nav.tsx", "<nav className=\"flex\" />"),
+        ];
+        let output = engine.process_files(&files);
+
+        assert!(output.css.contains("/* src/App.tsx:2 */\n.flex {"));
+        assert!(output.css.contains("/* src/App.tsx:2 */\n.p-4 {"));
+
+        let map = output.map.unwrap();
+        assert_eq!(map.version, 3);
+        assert_eq!(map.sources, vec!["src/App.tsx"]);
+        assert!(!map.mappings.is_empty());
+
+        // Without the options the output is plain CSS
+        let plain = TurboEngine::new().process_files(&files);
+        assert!(!plain.css.contains("/*"));
+        assert!(plain.map.is_none());
+    }
+
+    #[test]
+    fn test_config_thread_pool() {
+        let engine = TurboEngine::with_config(EngineConfig {
+            thread_count: 2,
+            ..Default::default()
+        });
+
+        assert_eq!(engine.install(rayon::current_num_threads), 2);
+        let results = engine.process_batch(&["p-4", "m-2"]);
+        assert!(results[1].contains(".m-2"));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_scan_session() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.html"), r#"class="p-4 flex""#).unwrap();
+        let config = ContentConfig {
+            root: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let engine = TurboEngine::with_config(EngineConfig {
+            thread_count: 2,
+            ..Default::default()
+        });
+
+        let mut session = engine.extraction_session();
+        let changes = engine.scan_session(&mut session, &config).unwrap();
+        assert_eq!(changes.added, vec!["flex".to_string(), "p-4".to_string()]);
+        assert_eq!(session.file_count(), 1);
+    }
+
+    #[test]
+    fn test_process_variant_group_matches_markup() {
+        let engine = TurboEngine::new();
+        let markup = "hover:(bg-red-500 text-white)";
+        let css = engine.process(markup);
+
+        // The class tokens a browser sees in class="hover:(bg-red-500 text-white)"
+        let selector: String = markup
+            .split_whitespace()
+            .map(|token| format!(".{}", token.replace(':', "\\:").replace('(', "\\(").replace(')', "\\)")))
+            .collect();
+        assert_eq!(selector, ".hover\\:\\(bg-red-500.text-white\\)");
+        assert_eq!(css.matches(&format!("{}:hover {{", selector)).count(), 2, "{}", css);
+        assert!(!css.contains(".hover\\:bg-red-500"));
+    }
+
+    #[test]
+    fn test_config_caches() {
+        let engine = TurboEngine::with_config(EngineConfig {
+            cache_size: 64,
+            ..Default::default()
+        });
+        assert_eq!(engine.parse_cache.as_ref().unwrap().capacity(), 64);
+        assert_eq!(engine.match_cache.as_ref().unwrap().capacity(), 64);
+
+        let first = engine.process("p-4 hover:(m-2 p-4)");
+        assert_eq!(engine.process("p-4 hover:(m-2 p-4)"), first);
+        let key = (engine.cache_epoch, "hover:p-4".to_string());
+        assert!(engine.match_cache.as_ref().unwrap().contains(&key));
+
+        let uncached = TurboEngine::with_config(EngineConfig {
+            cache_enabled: false,
+            ..Default::default()
+        });
+        assert!(uncached.parse_cache.is_none());
+        assert_eq!(uncached.process("p-4 hover:(m-2 p-4)"), first);
+    }
+
+    #[test]
+    fn test_cache_stats() {
+        let engine = TurboEngine::new();
+        engine.process("p-4 m-2");
+        engine.process("p-4 m-2");
+
+        let stats = engine.cache_stats();
+        assert_eq!(stats.parse.misses, 2);
+        assert_eq!(stats.parse.hits, 2);
+        assert_eq!(stats.matcher.misses, 2);
+        assert_eq!(stats.matcher.hits, 2);
+
+        engine.clear_caches();
+        engine.process("p-4");
+        assert_eq!(engine.cache_stats().parse.misses, 3);
+    }
+
+    #[test]
+    fn test_cache_invalidated_by_theme_and_patterns() {
+        let mut engine = TurboEngine::new();
+        assert!(engine.process("bg-brand cs-3").is_empty());
+
+        engine.set_theme(Theme::default().with_color("brand", "#ff5500"));
+        assert!(engine.process("bg-brand").contains("background-color: #ff5500"));
+
+        engine.register_utility(
+            UtilityPattern::new("custom-spacing", "cs-")
+                .with_pattern(r"^cs-\d+$")
+                .with_values([("3".to_string(), "3px".to_string())].into_iter().collect())
+                .with_css_property("custom-spacing"),
+        );
+        assert!(engine.process("cs-3").contains("custom-spacing: 3px"));
+    }
+
+    #[test]
+    fn test_cache_toggle_and_prefix_change() {
+        let mut engine = TurboEngine::with_config(EngineConfig {
+            cache_enabled: false,
+            ..Default::default()
+        });
+        engine.process("p-4");
+        assert_eq!(engine.cache_stats(), EngineCacheStats::default());
+
+        engine.set_config(EngineConfig::default());
+        engine.process("p-4");
+        assert_eq!(engine.cache_stats().parse.misses, 1);
+
+        engine.set_config(EngineConfig {
+            prefix: Some("tw-".to_string()),
+            ..Default::default()
+        });
+        assert!(engine.process("p-4").is_empty());
+        assert!(engine.process("tw-p-4").contains(".tw-p-4"));
     }
 
     #[test]
@@ -254,6 +779,87 @@ mod tests {
         // Registration should not panic
     }
 
+    #[test]
+    fn test_process_arbitrary_variants() {
+        let engine = TurboEngine::new();
+        let css = engine.process("[&>*]:p-4 [@media(min-width:900px)]:flex [p]:m-2");
+
+        assert!(css.contains(":p-4>* {"));
+        assert!(css.contains("@media (min-width:900px) {"));
+        assert!(!css.contains("margin"));
+
+        let html = r#"<ul class="[&>*]:p-4 [@supports(display:grid)]:grid">"#;
+        let classes = engine.extract(html);
+        assert!(classes.contains(&"[&>*]:p-4".to_string()));
+        assert!(classes.contains(&"[@supports(display:grid)]:grid".to_string()));
+    }
+
+    #[test]
+    fn test_process_arbitrary_properties() {
+        let engine = TurboEngine::new();
+        let css = engine.process("[mask-type:luminance] hover:![--my-var:10px] [Not:valid]");
+
+        assert!(css.contains(".\\[mask-type\\:luminance\\] {"));
+        assert!(css.contains("mask-type: luminance;"));
+        assert!(css.contains(":hover {"));
+        assert!(css.contains("--my-var: 10px !important;"));
+        assert!(!css.contains("Not"));
+    }
+
+    #[test]
+    fn test_process_group_and_peer_variants() {
+        let engine = TurboEngine::new();
+        let css = engine.process("group-hover:block peer-invalid:flex group-hover/card:p-4");
+
+        assert!(css.contains(".group:hover .group-hover\\:block"));
+        assert!(css.contains(".peer:invalid ~ .peer-invalid\\:flex"));
+        assert!(css.contains(".group\\/card:hover .group-hover\\/card\\:p-4"));
+    }
+
+    #[test]
+    fn test_process_structural_variants() {
+        let engine = TurboEngine::new();
+        let css = engine.process("aria-checked:block data-[state=open]:flex has-[:checked]:p-4 *:m-2 nth-3:hidden");
+
+        assert!(css.contains(r#".aria-checked\:block[aria-checked="true"] {"#));
+        assert!(css.contains(r".data-\[state\=open\]\:flex[data-state=open] {"));
+        assert!(css.contains(r".has-\[\:checked\]\:p-4:has(:checked) {"));
+        assert!(css.contains(r":is(.\*\:m-2 > *) {"));
+        assert!(css.contains(r".nth-3\:hidden:nth-child(3) {"));
+
+        let classes = engine.extract(r#"<ul class="*:m-2 has-[:checked]:p-4">"#);
+        assert!(classes.contains(&"*:m-2".to_string()));
+        assert!(classes.contains(&"has-[:checked]:p-4".to_string()));
+    }
+
+    #[test]
+    fn test_process_container_queries() {
+        let engine = TurboEngine::new();
+        let css = engine.process("@container/sidebar @md/sidebar:flex @max-lg:hidden");
+
+        assert!(css.contains("container-name: sidebar;"));
+        assert!(css.contains("@container sidebar (min-width: 28rem)"));
+        assert!(css.contains("@container (max-width: 511.98px)"));
+
+        let classes = engine.extract(r#"<aside class="@container/sidebar @md/sidebar:flex">"#);
+        assert!(classes.contains(&"@container/sidebar".to_string()));
+        assert!(classes.contains(&"@md/sidebar:flex".to_string()));
+    }
+
+    #[test]
+    fn test_process_cascade_order() {
+        let engine = TurboEngine::new();
+        let css = engine.process("pl-1 px-2 p-4 hover:p-6 md:p-8");
+
+        assert_eq!(engine.process("md:p-8 p-4 hover:p-6 px-2 pl-1"), css);
+        let p = css.find(".p-4 {").unwrap();
+        let px = css.find(".px-2 {").unwrap();
+        let pl = css.find(".pl-1 {").unwrap();
+        let hover = css.find(".hover\\:p-6:hover {").unwrap();
+        let md = css.find("@media (min-width: 768px)").unwrap();
+        assert!(p < px && px < pl && pl < hover && hover < md);
+    }
+
     #[test]
     fn test_process_empty_string() {
         let engine = TurboEngine::new();
@@ -3,113 +3,20 @@
 //! Uses a trie-based lookup for O(k) prefix matching where k is the prefix length.
 //! Falls back to regex matching for complex patterns.
 
+use crate::handler::{HandlerContext, HandlerRef, UtilityOutput, DEFAULT_VALUE_KEY};
+use crate::theme::Theme;
 use crate::trie::PrefixTrie;
-use crate::types::{CSSProperty, MatchResult, ParsedClass, UtilityPattern};
+use crate::types::{CSSProperty, Layer, MatchResult, ParsedClass, UtilityPattern};
 use ahash::AHashMap;
-use once_cell::sync::Lazy;
 use regex::Regex;
-use std::sync::RwLock;
-
-/// Spacing scale values (Tailwind default)
-static SPACING_SCALE: Lazy<AHashMap<&str, &str>> = Lazy::new(|| {
-    let mut m = AHashMap::new();
-    m.insert("0", "0px");
-    m.insert("px", "1px");
-    m.insert("0.5", "0.125rem");
-    m.insert("1", "0.25rem");
-    m.insert("1.5", "0.375rem");
-    m.insert("2", "0.5rem");
-    m.insert("2.5", "0.625rem");
-    m.insert("3", "0.75rem");
-    m.insert("3.5", "0.875rem");
-    m.insert("4", "1rem");
-    m.insert("5", "1.25rem");
-    m.insert("6", "1.5rem");
-    m.insert("7", "1.75rem");
-    m.insert("8", "2rem");
-    m.insert("9", "2.25rem");
-    m.insert("10", "2.5rem");
-    m.insert("11", "2.75rem");
-    m.insert("12", "3rem");
-    m.insert("14", "3.5rem");
-    m.insert("16", "4rem");
-    m.insert("20", "5rem");
-    m.insert("24", "6rem");
-    m.insert("28", "7rem");
-    m.insert("32", "8rem");
-    m.insert("36", "9rem");
-    m.insert("40", "10rem");
-    m.insert("44", "11rem");
-    m.insert("48", "12rem");
-    m.insert("52", "13rem");
-    m.insert("56", "14rem");
-    m.insert("60", "15rem");
-    m.insert("64", "16rem");
-    m.insert("72", "18rem");
-    m.insert("80", "20rem");
-    m.insert("96", "24rem");
-    m.insert("auto", "auto");
-    m.insert("full", "100%");
-    m.insert("screen", "100vw");
-    m.insert("svw", "100svw");
-    m.insert("lvw", "100lvw");
-    m.insert("dvw", "100dvw");
-    m.insert("min", "min-content");
-    m.insert("max", "max-content");
-    m.insert("fit", "fit-content");
-    m
-});
-
-/// Color palette (Tailwind default - subset)
-static COLORS: Lazy<AHashMap<&str, &str>> = Lazy::new(|| {
-    let mut m = AHashMap::new();
-    // Gray scale
-    m.insert("slate-50", "#f8fafc");
-    m.insert("slate-100", "#f1f5f9");
-    m.insert("slate-200", "#e2e8f0");
-    m.insert("slate-300", "#cbd5e1");
-    m.insert("slate-400", "#94a3b8");
-    m.insert("slate-500", "#64748b");
-    m.insert("slate-600", "#475569");
-    m.insert("slate-700", "#334155");
-    m.insert("slate-800", "#1e293b");
-    m.insert("slate-900", "#0f172a");
-    m.insert("slate-950", "#020617");
-    // Basic colors
-    m.insert("red-500", "#ef4444");
-    m.insert("orange-500", "#f97316");
-    m.insert("amber-500", "#f59e0b");
-    m.insert("yellow-500", "#eab308");
-    m.insert("lime-500", "#84cc16");
-    m.insert("green-500", "#22c55e");
-    m.insert("emerald-500", "#10b981");
-    m.insert("teal-500", "#14b8a6");
-    m.insert("cyan-500", "#06b6d4");
-    m.insert("sky-500", "#0ea5e9");
-    m.insert("blue-500", "#3b82f6");
-    m.insert("indigo-500", "#6366f1");
-    m.insert("violet-500", "#8b5cf6");
-    m.insert("purple-500", "#a855f7");
-    m.insert("fuchsia-500", "#d946ef");
-    m.insert("pink-500", "#ec4899");
-    m.insert("rose-500", "#f43f5e");
-    // Special values
-    m.insert("black", "#000000");
-    m.insert("white", "#ffffff");
-    m.insert("transparent", "transparent");
-    m.insert("current", "currentColor");
-    m.insert("inherit", "inherit");
-    m
-});
-
-/// Pattern handler function type
-type PatternHandler = fn(&ParsedClass, &UtilityPattern) -> Option<Vec<CSSProperty>>;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
 
 /// Internal pattern with compiled regex
 struct CompiledPattern {
     pattern: UtilityPattern,
     regex: Option<Regex>,
-    handler: PatternHandler,
+    handler: HandlerRef,
 }
 
 /// High-performance utility pattern matcher
@@ -120,6 +27,9 @@ pub struct Matcher {
     /// All registered patterns
     patterns: Vec<CompiledPattern>,
 
+    /// Design tokens handlers resolve against
+    theme: Arc<Theme>,
+
     /// Regex cache for compiled patterns (reserved for future dynamic patterns)
     #[allow(dead_code)]
     regex_cache: RwLock<AHashMap<String, Regex>>,
@@ -134,11 +44,17 @@ impl std::fmt::Debug for Matcher {
 }
 
 impl Matcher {
-    /// Create a new Matcher with default utility patterns
+    /// Create a new Matcher with default utility patterns and theme
     pub fn new() -> Self {
+        Self::with_theme(Arc::new(Theme::default()))
+    }
+
+    /// Create a Matcher with default utility patterns resolving against `theme`
+    pub fn with_theme(theme: Arc<Theme>) -> Self {
         let mut matcher = Self {
             prefix_trie: PrefixTrie::new(),
             patterns: Vec::new(),
+            theme,
             regex_cache: RwLock::new(AHashMap::new()),
         };
 
@@ -148,6 +64,21 @@ impl Matcher {
         matcher
     }
 
+    /// Theme used to resolve values
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Resolve values against another theme, keeping registered patterns
+    pub fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    /// Number of registered patterns
+    pub fn pattern_count(&self) -> usize {
+        self.patterns.len()
+    }
+
     /// Register a new utility pattern
     pub fn register(&mut self, pattern: UtilityPattern) {
         let idx = self.patterns.len();
@@ -159,8 +90,11 @@ impl Matcher {
             None
         };
 
-        // Determine handler based on pattern type
-        let handler = determine_handler(&pattern);
+        // Patterns without their own handler resolve through their value map
+        let handler = pattern
+            .handler
+            .clone()
+            .unwrap_or_else(|| Arc::new(handle_value) as HandlerRef);
 
         // Add to prefix trie
         if !pattern.prefix.is_empty() {
@@ -183,29 +117,22 @@ impl Matcher {
 
     /// Match a parsed class against registered patterns
     pub fn match_class(&self, parsed: &ParsedClass) -> Option<MatchResult> {
-        let _utility = &parsed.utility;
+        if !parsed.diagnostics.is_empty() {
+            return None;
+        }
+        if parsed.is_arbitrary_property() {
+            return match_arbitrary_property(parsed);
+        }
+
         let full_class = parsed.full_utility();
 
-        // Try trie-based prefix lookup first
-        if let Some((indices, _prefix_len)) = self.prefix_trie.longest_prefix_match(&full_class) {
+        // Try trie-based prefix lookup first, longest prefix first, so that
+        // "rounded-lg" falls back from "rounded-l" to "rounded"
+        for (indices, prefix_len) in self.prefix_trie.all_prefix_matches(&full_class).into_iter().rev() {
             for &idx in indices {
                 if let Some(compiled) = self.patterns.get(idx) {
-                    // Try regex match if pattern has one
-                    if let Some(ref regex) = compiled.regex {
-                        if !regex.is_match(&full_class) {
-                            continue;
-                        }
-                    }
-
-                    // Try handler
-                    if let Some(properties) = (compiled.handler)(parsed, &compiled.pattern) {
-                        return Some(MatchResult {
-                            parsed: parsed.clone(),
-                            properties,
-                            pattern_name: compiled.pattern.name.clone(),
-                            layer: compiled.pattern.layer,
-                            sort_order: compiled.pattern.sort_order,
-                        });
+                    if let Some(result) = self.try_pattern(compiled, parsed, &full_class, prefix_len) {
+                        return Some(result);
                     }
                 }
             }
@@ -213,17 +140,14 @@ impl Matcher {
 
         // Fallback: try all patterns with regex
         for compiled in &self.patterns {
-            if let Some(ref regex) = compiled.regex {
-                if regex.is_match(&full_class) {
-                    if let Some(properties) = (compiled.handler)(parsed, &compiled.pattern) {
-                        return Some(MatchResult {
-                            parsed: parsed.clone(),
-                            properties,
-                            pattern_name: compiled.pattern.name.clone(),
-                            layer: compiled.pattern.layer,
-                            sort_order: compiled.pattern.sort_order,
-                        });
-                    }
+            if compiled.regex.is_some() {
+                let prefix_len = if full_class.starts_with(compiled.pattern.prefix.as_str()) {
+                    compiled.pattern.prefix.len()
+                } else {
+                    0
+                };
+                if let Some(result) = self.try_pattern(compiled, parsed, &full_class, prefix_len) {
+                    return Some(result);
                 }
             }
         }
@@ -231,6 +155,51 @@ impl Matcher {
         None
     }
 
+    /// Check a single pattern's regex and run its handler
+    fn try_pattern(
+        &self,
+        compiled: &CompiledPattern,
+        parsed: &ParsedClass,
+        full_class: &str,
+        prefix_len: usize,
+    ) -> Option<MatchResult> {
+        // Try regex match if pattern has one
+        if let Some(ref regex) = compiled.regex {
+            if !regex.is_match(full_class) {
+                return None;
+            }
+        }
+
+        let ctx = HandlerContext {
+            parsed,
+            pattern: &compiled.pattern,
+            full_class,
+            prefix_len,
+            theme: &self.theme,
+        };
+        let output = compiled.handler.handle(&ctx)?;
+
+        Some(MatchResult {
+            parsed: parsed.clone(),
+            properties: output.properties,
+            pattern_name: compiled.pattern.name.clone(),
+            layer: compiled.pattern.layer,
+            sort_order: compiled.pattern.sort_order,
+            selector_suffix: compiled.pattern.selector_suffix.clone(),
+            extra_rules: output.extra_rules,
+        })
+    }
+
+    /// Register a value-less utility that always emits `prop: value`
+    fn register_static(&mut self, name: &str, prop: &str, value: &str) {
+        self.register(
+            UtilityPattern::new(name, name)
+                .with_css_property(prop)
+                .with_pattern(format!(r"^{}$", regex::escape(name)))
+                .with_values(default_value(value)),
+        );
+    }
+
     /// Register default utility patterns
     fn register_default_patterns(&mut self) {
         // Spacing utilities
@@ -256,6 +225,9 @@ impl Matcher {
     }
 
     fn register_spacing_utilities(&mut self) {
+        // Pattern chars: word chars, dash, dot, brackets, hash, percent (for arbitrary values)
+        const ARB_CHARS: &str = r"[\w\-\.\[\]#%]+";
+
         // Padding
         let padding_props = [
             ("p-", "padding", vec!["padding"]),
@@ -265,16 +237,17 @@ impl Matcher {
             ("pl-", "padding-left", vec!["padding-left"]),
             ("px-", "padding-x", vec!["padding-left", "padding-right"]),
             ("py-", "padding-y", vec!["padding-top", "padding-bottom"]),
+            ("ps-", "padding-start", vec!["padding-inline-start"]),
+            ("pe-", "padding-end", vec!["padding-inline-end"]),
         ];
 
-        // Pattern chars: word chars, dash, dot, brackets, hash, percent (for arbitrary values)
-        const ARB_CHARS: &str = r"[\w\-\.\[\]#%]+";
-
-        for (prefix, name, _props) in padding_props {
+        for (prefix, name, props) in padding_props {
             self.register(
                 UtilityPattern::new(name, prefix)
-                    .with_css_property(name)
-                    .with_pattern(&format!(r"^{}{}$", regex::escape(prefix), ARB_CHARS)),
+                    .with_css_properties(props)
+                    .with_sort_order(side_order(prefix, "p"))
+                    .with_pattern(format!(r"^{}{}$", regex::escape(prefix), ARB_CHARS))
+                    .with_handler(handle_spacing),
             );
         }
 
@@ -287,14 +260,18 @@ impl Matcher {
             ("ml-", "margin-left", vec!["margin-left"]),
             ("mx-", "margin-x", vec!["margin-left", "margin-right"]),
             ("my-", "margin-y", vec!["margin-top", "margin-bottom"]),
+            ("ms-", "margin-start", vec!["margin-inline-start"]),
+            ("me-", "margin-end", vec!["margin-inline-end"]),
         ];
 
-        for (prefix, name, _props) in margin_props {
+        for (prefix, name, props) in margin_props {
             self.register(
                 UtilityPattern::new(name, prefix)
-                    .with_css_property(name)
-                    .with_pattern(&format!(r"^-?{}{}$", regex::escape(prefix), ARB_CHARS))
-                    .with_negative(),
+                    .with_css_properties(props)
+                    .with_sort_order(side_order(prefix, "m"))
+                    .with_pattern(format!(r"^-?{}{}$", regex::escape(prefix), ARB_CHARS))
+                    .with_negative()
+                    .with_handler(handle_spacing),
             );
         }
 
@@ -302,18 +279,99 @@ impl Matcher {
         self.register(
             UtilityPattern::new("gap", "gap-")
                 .with_css_property("gap")
-                .with_pattern(r"^gap-[\w\-\.]+$"),
+                .with_pattern(r"^gap-[\w\-\.]+$")
+                .with_handler(handle_spacing),
         );
         self.register(
             UtilityPattern::new("gap-x", "gap-x-")
                 .with_css_property("column-gap")
-                .with_pattern(r"^gap-x-[\w\-\.]+$"),
+                .with_sort_order(side_order("gap-x-", "gap"))
+                .with_pattern(r"^gap-x-[\w\-\.]+$")
+                .with_handler(handle_spacing),
         );
         self.register(
             UtilityPattern::new("gap-y", "gap-y-")
                 .with_css_property("row-gap")
-                .with_pattern(r"^gap-y-[\w\-\.]+$"),
+                .with_sort_order(side_order("gap-y-", "gap"))
+                .with_pattern(r"^gap-y-[\w\-\.]+$")
+                .with_handler(handle_spacing),
         );
+
+        // Inset (positioning)
+        let inset_props = [
+            ("inset-", "inset", vec!["inset"]),
+            ("inset-x-", "inset-x", vec!["left", "right"]),
+            ("inset-y-", "inset-y", vec!["top", "bottom"]),
+            ("top-", "inset-top", vec!["top"]),
+            ("right-", "inset-right", vec!["right"]),
+            ("bottom-", "inset-bottom", vec!["bottom"]),
+            ("left-", "inset-left", vec!["left"]),
+            ("start-", "inset-start", vec!["inset-inline-start"]),
+            ("end-", "inset-end", vec!["inset-inline-end"]),
+        ];
+
+        for (prefix, name, props) in inset_props {
+            self.register(
+                UtilityPattern::new(name, prefix)
+                    .with_css_properties(props)
+                    .with_sort_order(side_order(prefix, "inset"))
+                    .with_pattern(format!(r"^{}{}$", regex::escape(prefix), ARB_CHARS))
+                    .with_values(fraction_values())
+                    .with_negative()
+                    .with_handler(handle_spacing),
+            );
+        }
+
+        // Scroll margin and padding
+        for (kind, negative) in [("m", true), ("p", false)] {
+            let base = if kind == "m" { "scroll-margin" } else { "scroll-padding" };
+            let sides: [(&str, &str, Vec<String>); 9] = [
+                ("", "", vec![base.to_string()]),
+                ("t", "-top", vec![format!("{}-top", base)]),
+                ("r", "-right", vec![format!("{}-right", base)]),
+                ("b", "-bottom", vec![format!("{}-bottom", base)]),
+                ("l", "-left", vec![format!("{}-left", base)]),
+                ("x", "-x", vec![format!("{}-left", base), format!("{}-right", base)]),
+                ("y", "-y", vec![format!("{}-top", base), format!("{}-bottom", base)]),
+                ("s", "-start", vec![format!("{}-inline-start", base)]),
+                ("e", "-end", vec![format!("{}-inline-end", base)]),
+            ];
+
+            for (side, suffix, props) in sides {
+                let prefix = format!("scroll-{}{}-", kind, side);
+                let mut pattern = UtilityPattern::new(format!("{}{}", base, suffix), prefix.as_str())
+                    .with_css_properties(props)
+                    .with_sort_order(side_order(side, ""))
+                    .with_pattern(format!(r"^{}{}$", regex::escape(&prefix), ARB_CHARS))
+                    .with_handler(handle_spacing);
+                if negative {
+                    pattern = pattern.with_negative();
+                }
+                self.register(pattern);
+            }
+        }
+
+        // Sizing
+        let mut height_values = fraction_values();
+        for (key, value) in [("screen", "100vh"), ("svh", "100svh"), ("lvh", "100lvh"), ("dvh", "100dvh")] {
+            height_values.insert(key.to_string(), value.to_string());
+        }
+
+        let sizes = [
+            ("w-", "width", vec!["width"], fraction_values()),
+            ("h-", "height", vec!["height"], height_values),
+            ("size-", "size", vec!["width", "height"], fraction_values()),
+        ];
+
+        for (prefix, name, props, values) in sizes {
+            self.register(
+                UtilityPattern::new(name, prefix)
+                    .with_css_properties(props)
+                    .with_pattern(format!(r"^{}{}$", regex::escape(prefix), ARB_CHARS))
+                    .with_values(values)
+                    .with_handler(handle_spacing),
+            );
+        }
     }
 
     fn register_display_utilities(&mut self) {
@@ -329,12 +387,8 @@ impl Matcher {
             ("hidden", "display", "none"),
         ];
 
-        for (name, prop, _value) in displays {
-            self.register(
-                UtilityPattern::new(name, name)
-                    .with_css_property(prop)
-                    .with_pattern(&format!(r"^{}$", regex::escape(name))),
-            );
+        for (name, prop, value) in displays {
+            self.register_static(name, prop, value);
         }
     }
 
@@ -347,12 +401,8 @@ impl Matcher {
             ("flex-col-reverse", "flex-direction", "column-reverse"),
         ];
 
-        for (name, prop, _value) in directions {
-            self.register(
-                UtilityPattern::new(name, name)
-                    .with_css_property(prop)
-                    .with_pattern(&format!(r"^{}$", regex::escape(name))),
-            );
+        for (name, prop, value) in directions {
+            self.register_static(name, prop, value);
         }
 
         // Flex wrap
@@ -362,12 +412,8 @@ impl Matcher {
             ("flex-nowrap", "flex-wrap", "nowrap"),
         ];
 
-        for (name, prop, _value) in wraps {
-            self.register(
-                UtilityPattern::new(name, name)
-                    .with_css_property(prop)
-                    .with_pattern(&format!(r"^{}$", regex::escape(name))),
-            );
+        for (name, prop, value) in wraps {
+            self.register_static(name, prop, value);
         }
 
         // Justify content
@@ -380,12 +426,8 @@ impl Matcher {
             ("justify-evenly", "justify-content", "space-evenly"),
         ];
 
-        for (name, prop, _value) in justifies {
-            self.register(
-                UtilityPattern::new(name, name)
-                    .with_css_property(prop)
-                    .with_pattern(&format!(r"^{}$", regex::escape(name))),
-            );
+        for (name, prop, value) in justifies {
+            self.register_static(name, prop, value);
         }
 
         // Align items
@@ -397,21 +439,18 @@ impl Matcher {
             ("items-stretch", "align-items", "stretch"),
         ];
 
-        for (name, prop, _value) in aligns {
-            self.register(
-                UtilityPattern::new(name, name)
-                    .with_css_property(prop)
-                    .with_pattern(&format!(r"^{}$", regex::escape(name))),
-            );
+        for (name, prop, value) in aligns {
+            self.register_static(name, prop, value);
         }
     }
 
     fn register_typography_utilities(&mut self) {
-        // Font size
+        // Font size, from the theme
         self.register(
             UtilityPattern::new("font-size", "text-")
                 .with_css_property("font-size")
-                .with_pattern(r"^text-(xs|sm|base|lg|xl|2xl|3xl|4xl|5xl|6xl|7xl|8xl|9xl)$"),
+                .with_pattern(r"^text-(\[.+\]|[\w\-\.]+)$")
+                .with_handler(handle_font_size),
         );
 
         // Font weight
@@ -427,12 +466,8 @@ impl Matcher {
             ("font-black", "900"),
         ];
 
-        for (name, _value) in weights {
-            self.register(
-                UtilityPattern::new(name, name)
-                    .with_css_property("font-weight")
-                    .with_pattern(&format!(r"^{}$", regex::escape(name))),
-            );
+        for (name, value) in weights {
+            self.register_static(name, "font-weight", value);
         }
 
         // Text alignment
@@ -443,36 +478,55 @@ impl Matcher {
             ("text-justify", "justify"),
         ];
 
-        for (name, _value) in aligns {
-            self.register(
-                UtilityPattern::new(name, name)
-                    .with_css_property("text-align")
-                    .with_pattern(&format!(r"^{}$", regex::escape(name))),
-            );
+        for (name, value) in aligns {
+            self.register_static(name, "text-align", value);
         }
     }
 
     fn register_color_utilities(&mut self) {
-        // Text color
-        self.register(
-            UtilityPattern::new("text-color", "text-")
-                .with_css_property("color")
-                .with_pattern(r"^text-[\w\-]+(-\d+)?(/\d+)?$"),
-        );
+        // (prefix, pattern name, CSS property, selector suffix)
+        let colors = [
+            ("text-", "text-color", "color", None),
+            ("bg-", "background-color", "background-color", None),
+            ("border-", "border-color", "border-color", None),
+            ("border-t-", "border-top-color", "border-top-color", None),
+            ("border-r-", "border-right-color", "border-right-color", None),
+            ("border-b-", "border-bottom-color", "border-bottom-color", None),
+            ("border-l-", "border-left-color", "border-left-color", None),
+            ("ring-", "ring-color", "--tw-ring-color", None),
+            ("ring-offset-", "ring-offset-color", "--tw-ring-offset-color", None),
+            ("outline-", "outline-color", "outline-color", None),
+            ("fill-", "fill-color", "fill", None),
+            ("stroke-", "stroke-color", "stroke", None),
+            ("decoration-", "text-decoration-color", "text-decoration-color", None),
+            ("accent-", "accent-color", "accent-color", None),
+            ("caret-", "caret-color", "caret-color", None),
+            ("placeholder-", "placeholder-color", "color", Some("::placeholder")),
+            ("divide-", "divide-color", "border-color", Some(" > :not([hidden]) ~ :not([hidden])")),
+        ];
 
-        // Background color
-        self.register(
-            UtilityPattern::new("background-color", "bg-")
-                .with_css_property("background-color")
-                .with_pattern(r"^bg-[\w\-]+(-\d+)?(/\d+)?$"),
-        );
+        for (prefix, name, prop, suffix) in colors {
+            let mut pattern = UtilityPattern::new(name, prefix)
+                .with_css_property(prop)
+                .with_pattern(format!(r"^{}(\[.+\]|[\w\-]+)$", regex::escape(prefix)))
+                .with_handler(handle_color);
+            if prefix.starts_with("border-") {
+                pattern = pattern.with_sort_order(side_order(prefix, "border"));
+            }
+            if let Some(suffix) = suffix {
+                pattern = pattern.with_selector_suffix(suffix);
+            }
+            self.register(pattern);
+        }
 
-        // Border color
-        self.register(
-            UtilityPattern::new("border-color", "border-")
-                .with_css_property("border-color")
-                .with_pattern(r"^border-[\w\-]+(-\d+)?(/\d+)?$"),
-        );
+        // Gradient color stops
+        for (prefix, name) in [("from-", "gradient-from"), ("via-", "gradient-via"), ("to-", "gradient-to")] {
+            self.register(
+                UtilityPattern::new(name, prefix)
+                    .with_pattern(format!(r"^{}(\[.+\]|[\w\-]+)$", regex::escape(prefix)))
+                    .with_handler(handle_gradient_stop),
+            );
+        }
     }
 
     fn register_border_utilities(&mut self) {
@@ -483,12 +537,34 @@ impl Matcher {
                 .with_pattern(r"^border(-[trbl])?(-\d+)?$"),
         );
 
-        // Border radius
-        self.register(
-            UtilityPattern::new("border-radius", "rounded")
-                .with_css_property("border-radius")
-                .with_pattern(r"^rounded(-[trblse]+)?(-[\w]+)?$"),
-        );
+        // Border radius, including per-side and per-corner variants
+        let radii = [
+            ("rounded", "border-radius", vec!["border-radius"]),
+            ("rounded-t", "border-radius-t", vec!["border-top-left-radius", "border-top-right-radius"]),
+            ("rounded-r", "border-radius-r", vec!["border-top-right-radius", "border-bottom-right-radius"]),
+            ("rounded-b", "border-radius-b", vec!["border-bottom-right-radius", "border-bottom-left-radius"]),
+            ("rounded-l", "border-radius-l", vec!["border-top-left-radius", "border-bottom-left-radius"]),
+            ("rounded-s", "border-radius-s", vec!["border-start-start-radius", "border-end-start-radius"]),
+            ("rounded-e", "border-radius-e", vec!["border-start-end-radius", "border-end-end-radius"]),
+            ("rounded-tl", "border-radius-tl", vec!["border-top-left-radius"]),
+            ("rounded-tr", "border-radius-tr", vec!["border-top-right-radius"]),
+            ("rounded-br", "border-radius-br", vec!["border-bottom-right-radius"]),
+            ("rounded-bl", "border-radius-bl", vec!["border-bottom-left-radius"]),
+            ("rounded-ss", "border-radius-ss", vec!["border-start-start-radius"]),
+            ("rounded-se", "border-radius-se", vec!["border-start-end-radius"]),
+            ("rounded-ee", "border-radius-ee", vec!["border-end-end-radius"]),
+            ("rounded-es", "border-radius-es", vec!["border-end-start-radius"]),
+        ];
+
+        for (prefix, name, props) in radii {
+            self.register(
+                UtilityPattern::new(name, prefix)
+                    .with_css_properties(props)
+                    .with_sort_order(side_order(prefix, "rounded"))
+                    .with_pattern(format!(r"^{}(-[\w\.]+|-\[.+\])?$", regex::escape(prefix)))
+                    .with_handler(handle_radius),
+            );
+        }
     }
 
     fn register_effects_utilities(&mut self) {
@@ -496,14 +572,41 @@ impl Matcher {
         self.register(
             UtilityPattern::new("opacity", "opacity-")
                 .with_css_property("opacity")
-                .with_pattern(r"^opacity-\d+$"),
+                .with_pattern(r"^opacity-(\d+|\[.+\])$")
+                .with_handler(handle_opacity),
         );
 
         // Shadow
         self.register(
             UtilityPattern::new("shadow", "shadow")
                 .with_css_property("box-shadow")
-                .with_pattern(r"^shadow(-[\w]+)?$"),
+                .with_pattern(r"^shadow(-[\w]+|-\[.+\])?$")
+                .with_handler(handle_shadow),
+        );
+
+        // Z-index
+        self.register(
+            UtilityPattern::new("z-index", "z-")
+                .with_css_property("z-index")
+                .with_pattern(r"^-?z-(\w+|\[.+\])$")
+                .with_negative()
+                .with_handler(handle_z_index),
+        );
+
+        // Transition duration
+        self.register(
+            UtilityPattern::new("transition-duration", "duration-")
+                .with_css_property("transition-duration")
+                .with_pattern(r"^duration-(\d+|\[.+\])$")
+                .with_handler(handle_duration),
+        );
+
+        // Container query containers, optionally named (`@container/sidebar`)
+        self.register(
+            UtilityPattern::new("container-type", "@container")
+                .with_css_property("container-type")
+                .with_pattern(r"^@container(-normal|-size)?(/[\w-]+)?$")
+                .with_handler(handle_container),
         );
     }
 }
@@ -514,34 +617,101 @@ impl Default for Matcher {
     }
 }
 
-/// Determine the appropriate handler for a pattern
-fn determine_handler(pattern: &UtilityPattern) -> PatternHandler {
-    match pattern.name.as_str() {
-        name if name.starts_with("padding") || name == "padding-x" || name == "padding-y" => {
-            handle_spacing
-        }
-        name if name.starts_with("margin") || name == "margin-x" || name == "margin-y" => {
-            handle_spacing
+/// Sort order of a side- or corner-specific utility within its family, so
+/// narrower utilities come later and override broader ones (`p-4 px-2 pl-1`)
+///
+/// `prefix` minus `base` names the side: nothing for the shorthand, `x`/`y`
+/// for an axis, one side (`t`, `top`, `s`) or a corner (`tl`, `ss`).
+fn side_order(prefix: &str, base: &str) -> u32 {
+    let side = prefix.strip_prefix(base).unwrap_or(prefix).trim_matches('-');
+    match side {
+        "" => 0,
+        "x" | "y" => 1,
+        _ if side.len() == 2 => 3,
+        _ => 2,
+    }
+}
+
+/// Emit an arbitrary property (`[prop:value]`) as a single declaration
+///
+/// Underscores in the value become spaces; `\_` keeps a literal underscore.
+fn match_arbitrary_property(parsed: &ParsedClass) -> Option<MatchResult> {
+    let value = decode_underscores(parsed.arbitrary.as_deref()?);
+
+    Some(MatchResult {
+        parsed: parsed.clone(),
+        properties: vec![CSSProperty::new(parsed.utility.clone(), value)],
+        pattern_name: "arbitrary-property".to_string(),
+        layer: Layer::Utilities,
+        sort_order: 0,
+        selector_suffix: None,
+        extra_rules: Vec::new(),
+    })
+}
+
+/// Replace `_` with a space, keeping escaped `\_` as an underscore
+fn decode_underscores(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' if chars.peek() == Some(&'_') => {
+                out.push('_');
+                chars.next();
+            }
+            '_' => out.push(' '),
+            _ => out.push(ch),
         }
-        name if name.starts_with("gap") => handle_spacing,
-        "text-color" => handle_color,
-        "background-color" => handle_color,
-        "border-color" => handle_color,
-        "opacity" => handle_opacity,
-        _ => handle_static,
     }
+    out
 }
 
-/// Handle spacing utilities (padding, margin, gap)
-fn handle_spacing(parsed: &ParsedClass, pattern: &UtilityPattern) -> Option<Vec<CSSProperty>> {
+/// Default handler: look the value up in the pattern's value map, falling
+/// back to the arbitrary value, and emit it for every target property
+fn handle_value(ctx: &HandlerContext<'_>) -> Option<UtilityOutput> {
+    let parsed = ctx.parsed;
+    let pattern = ctx.pattern;
+
+    if parsed.negative && !pattern.supports_negative {
+        return None;
+    }
+
+    let css_value = match parsed.arbitrary {
+        Some(ref arbitrary) if pattern.supports_arbitrary => arbitrary.clone(),
+        Some(_) => return None,
+        None => pattern.values.get(ctx.value_key()?)?.clone(),
+    };
+
+    let final_value = if parsed.negative {
+        format!("-{}", css_value)
+    } else {
+        css_value
+    };
+
+    Some(expand_properties(pattern, &final_value).into())
+}
+
+/// Handle spacing-scale utilities (padding, margin, gap, inset, scroll, sizing)
+///
+/// The value is expanded into every property in `pattern.css_properties`.
+fn handle_spacing(ctx: &HandlerContext<'_>) -> Option<UtilityOutput> {
+    let parsed = ctx.parsed;
+    let pattern = ctx.pattern;
+
+    if parsed.negative && !pattern.supports_negative {
+        return None;
+    }
+
     // Check for arbitrary value first
     let css_value = if let Some(ref arbitrary) = parsed.arbitrary {
         arbitrary.clone()
-    } else if let Some(ref value) = parsed.value {
-        // Look up in spacing scale
-        SPACING_SCALE.get(value.as_str()).map(|s| s.to_string())?
     } else {
-        return None;
+        let key = fraction_value_key(ctx)?;
+        // Pattern-specific values (fractions, viewport units) win over the scale
+        match pattern.values.get(&key) {
+            Some(value) => value.clone(),
+            None => ctx.theme.spacing.get(&key)?.clone(),
+        }
     };
 
     // Apply negative if needed
@@ -551,123 +721,231 @@ fn handle_spacing(parsed: &ParsedClass, pattern: &UtilityPattern) -> Option<Vec<
         css_value
     };
 
-    // Handle multi-property utilities (px, py, mx, my)
-    let props = match pattern.name.as_str() {
-        "padding-x" => vec![("padding-left", &final_value), ("padding-right", &final_value)],
-        "padding-y" => vec![("padding-top", &final_value), ("padding-bottom", &final_value)],
-        "margin-x" => vec![("margin-left", &final_value), ("margin-right", &final_value)],
-        "margin-y" => vec![("margin-top", &final_value), ("margin-bottom", &final_value)],
-        _ => vec![(&pattern.css_property as &str, &final_value)],
+    Some(expand_properties(pattern, &final_value).into())
+}
+
+/// Handle border radius utilities, including side and corner variants
+fn handle_radius(ctx: &HandlerContext<'_>) -> Option<UtilityOutput> {
+    // "rounded-t" alone uses the default radius
+    let css_value = scale_value(ctx, &ctx.theme.border_radius)?;
+    Some(expand_properties(ctx.pattern, &css_value).into())
+}
+
+/// Handle font size utilities, setting the theme's line height alongside
+///
+/// A `/N` modifier (`text-lg/7`) overrides the line height from the spacing
+/// scale. Arbitrary values that look like colors are left to `text-color`.
+fn handle_font_size(ctx: &HandlerContext<'_>) -> Option<UtilityOutput> {
+    let (size, line_height) = match ctx.parsed.arbitrary {
+        Some(ref arbitrary) if arbitrary_color(arbitrary).is_none() => {
+            (arbitrary.replace('_', " "), None)
+        }
+        Some(_) => return None,
+        None => {
+            let font_size = ctx.theme.font_size.get(ctx.value_key()?)?;
+            (font_size.size.clone(), font_size.line_height.clone())
+        }
     };
 
-    Some(
-        props
-            .into_iter()
-            .map(|(p, v)| CSSProperty::new(p, v.clone()))
-            .collect(),
-    )
+    let line_height = match ctx.parsed.opacity {
+        Some(step) => Some(ctx.theme.spacing.get(&step.to_string())?.clone()),
+        None => line_height,
+    };
+
+    let mut props = vec![CSSProperty::new("font-size", size)];
+    if let Some(line_height) = line_height {
+        props.push(CSSProperty::new("line-height", line_height));
+    }
+    Some(props.into())
 }
 
-/// Handle color utilities
-fn handle_color(parsed: &ParsedClass, pattern: &UtilityPattern) -> Option<Vec<CSSProperty>> {
-    let value = parsed.value.as_ref()?;
+/// Handle box shadow utilities
+fn handle_shadow(ctx: &HandlerContext<'_>) -> Option<UtilityOutput> {
+    let css_value = scale_value(ctx, &ctx.theme.box_shadow)?;
+    Some(vec![CSSProperty::new(&ctx.pattern.css_property, css_value)].into())
+}
 
-    // Check for arbitrary value
-    let css_value = if let Some(ref arbitrary) = parsed.arbitrary {
-        arbitrary.clone()
+/// Handle z-index utilities, including negative values
+fn handle_z_index(ctx: &HandlerContext<'_>) -> Option<UtilityOutput> {
+    let css_value = scale_value(ctx, &ctx.theme.z_index)?;
+    let css_value = if ctx.parsed.negative {
+        format!("-{}", css_value)
     } else {
-        // Look up in colors
-        COLORS
-            .get(value.as_str())
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| {
-                // Try to construct color name
-                format!("var(--color-{})", value.replace('-', "-"))
-            })
+        css_value
     };
+    Some(vec![CSSProperty::new(&ctx.pattern.css_property, css_value)].into())
+}
 
-    // Apply opacity if present
-    let final_value = if let Some(opacity) = parsed.opacity {
-        let alpha = opacity as f32 / 100.0;
-        format!("color-mix(in srgb, {} {}%, transparent)", css_value, (alpha * 100.0) as u8)
+/// Handle transition duration utilities
+fn handle_duration(ctx: &HandlerContext<'_>) -> Option<UtilityOutput> {
+    let css_value = scale_value(ctx, &ctx.theme.transition_duration)?;
+    Some(vec![CSSProperty::new(&ctx.pattern.css_property, css_value)].into())
+}
+
+/// Look a class value up in a theme scale, passing arbitrary values through
+/// with underscores decoded to spaces
+fn scale_value(ctx: &HandlerContext<'_>, scale: &BTreeMap<String, String>) -> Option<String> {
+    match ctx.parsed.arbitrary {
+        Some(ref arbitrary) => Some(arbitrary.replace('_', " ")),
+        None => scale.get(ctx.value_key()?).cloned(),
+    }
+}
+
+/// Value key for a class, with a trailing fraction restored (the parser reads
+/// "w-1/2" as value "1" with opacity 2)
+fn fraction_value_key(ctx: &HandlerContext<'_>) -> Option<String> {
+    let key = ctx.value_key()?;
+
+    match ctx.parsed.opacity {
+        Some(denominator) if key.bytes().all(|b| b.is_ascii_digit()) => {
+            Some(format!("{}/{}", key, denominator))
+        }
+        Some(_) => None,
+        None => Some(key.to_string()),
+    }
+}
+
+/// Emit one declaration per target property of the pattern
+fn expand_properties(pattern: &UtilityPattern, value: &str) -> Vec<CSSProperty> {
+    pattern
+        .css_properties
+        .iter()
+        .map(|prop| CSSProperty::new(prop.as_str(), value))
+        .collect()
+}
+
+/// Value map for a utility without a value part (`flex`, `hidden`, ...)
+fn default_value(value: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    values.insert(DEFAULT_VALUE_KEY.to_string(), value.to_string());
+    values
+}
+
+/// Fractional values (1/2, 2/3, ...) as percentages, shared by inset and sizing
+fn fraction_values() -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    for denominator in [2u32, 3, 4, 5, 6, 12] {
+        for numerator in 1..denominator {
+            let percent = format!("{:.6}", numerator as f64 * 100.0 / denominator as f64);
+            let percent = percent.trim_end_matches('0').trim_end_matches('.');
+            values.insert(format!("{}/{}", numerator, denominator), format!("{}%", percent));
+        }
+    }
+    values
+}
+
+/// Handle color utilities
+fn handle_color(ctx: &HandlerContext<'_>) -> Option<UtilityOutput> {
+    let color = resolve_color(ctx)?;
+    Some(vec![CSSProperty::new(&ctx.pattern.css_property, color)].into())
+}
+
+/// Handle gradient color stops (from-*, via-*, to-*)
+fn handle_gradient_stop(ctx: &HandlerContext<'_>) -> Option<UtilityOutput> {
+    let color = resolve_color(ctx)?;
+
+    let props = match ctx.pattern.name.as_str() {
+        "gradient-from" => vec![
+            CSSProperty::new("--tw-gradient-from", color),
+            CSSProperty::new("--tw-gradient-to", "transparent"),
+            CSSProperty::new("--tw-gradient-stops", "var(--tw-gradient-from), var(--tw-gradient-to)"),
+        ],
+        "gradient-via" => vec![
+            CSSProperty::new("--tw-gradient-to", "transparent"),
+            CSSProperty::new(
+                "--tw-gradient-stops",
+                format!("var(--tw-gradient-from), {}, var(--tw-gradient-to)", color),
+            ),
+        ],
+        _ => vec![CSSProperty::new("--tw-gradient-to", color)],
+    };
+
+    Some(props.into())
+}
+
+/// Resolve the color named after the pattern prefix, applying any `/NN` opacity
+///
+/// Returns `None` for names outside the palette and arbitrary values that do not
+/// look like colors, so that other patterns sharing the prefix (e.g. `text-lg`,
+/// `border-2`) get a chance to match.
+fn resolve_color(ctx: &HandlerContext<'_>) -> Option<String> {
+    let css_value = if let Some(ref arbitrary) = ctx.parsed.arbitrary {
+        arbitrary_color(arbitrary)?
     } else {
-        css_value
+        ctx.theme.color(ctx.value_key()?)?.to_string()
     };
 
-    Some(vec![CSSProperty::new(&pattern.css_property, final_value)])
+    // Apply opacity if present
+    match ctx.parsed.opacity {
+        Some(opacity) => Some(format!(
+            "color-mix(in srgb, {} {}%, transparent)",
+            css_value, opacity
+        )),
+        None => Some(css_value),
+    }
 }
 
-/// Handle opacity utility
-fn handle_opacity(parsed: &ParsedClass, pattern: &UtilityPattern) -> Option<Vec<CSSProperty>> {
-    let value = parsed.value.as_ref()?;
+/// Interpret an arbitrary value as a color, honoring an explicit `color:` hint
+fn arbitrary_color(value: &str) -> Option<String> {
+    const COLOR_FUNCTIONS: &[&str] = &[
+        "rgb(", "rgba(", "hsl(", "hsla(", "hwb(", "lab(", "lch(", "oklab(", "oklch(",
+        "color(", "color-mix(",
+    ];
 
-    // Parse opacity value (0-100)
-    let opacity: u8 = value.parse().ok()?;
-    let css_value = format!("{}", opacity as f32 / 100.0);
+    let decoded = value.replace('_', " ");
+    if let Some(hinted) = decoded.strip_prefix("color:") {
+        return Some(hinted.to_string());
+    }
 
-    Some(vec![CSSProperty::new(&pattern.css_property, css_value)])
+    let lower = decoded.to_ascii_lowercase();
+    if lower.starts_with('#') || COLOR_FUNCTIONS.iter().any(|f| lower.starts_with(f)) {
+        Some(decoded)
+    } else {
+        None
+    }
 }
 
-/// Handle static utilities (display, flex, etc.)
-fn handle_static(_parsed: &ParsedClass, pattern: &UtilityPattern) -> Option<Vec<CSSProperty>> {
-    // For static utilities, the value is usually embedded in the pattern name
-    let value = match pattern.name.as_str() {
-        // Display
-        "block" => "block",
-        "inline-block" => "inline-block",
-        "inline" => "inline",
-        "flex" => "flex",
-        "inline-flex" => "inline-flex",
-        "grid" => "grid",
-        "inline-grid" => "inline-grid",
-        "contents" => "contents",
-        "hidden" => "none",
-        // Flex direction
-        "flex-row" => "row",
-        "flex-row-reverse" => "row-reverse",
-        "flex-col" => "column",
-        "flex-col-reverse" => "column-reverse",
-        // Flex wrap
-        "flex-wrap" => "wrap",
-        "flex-wrap-reverse" => "wrap-reverse",
-        "flex-nowrap" => "nowrap",
-        // Justify content
-        "justify-start" => "flex-start",
-        "justify-end" => "flex-end",
-        "justify-center" => "center",
-        "justify-between" => "space-between",
-        "justify-around" => "space-around",
-        "justify-evenly" => "space-evenly",
-        // Align items
-        "items-start" => "flex-start",
-        "items-end" => "flex-end",
-        "items-center" => "center",
-        "items-baseline" => "baseline",
-        "items-stretch" => "stretch",
-        // Text alignment
-        "text-left" => "left",
-        "text-center" => "center",
-        "text-right" => "right",
-        "text-justify" => "justify",
-        // Font weight
-        "font-thin" => "100",
-        "font-extralight" => "200",
-        "font-light" => "300",
-        "font-normal" => "400",
-        "font-medium" => "500",
-        "font-semibold" => "600",
-        "font-bold" => "700",
-        "font-extrabold" => "800",
-        "font-black" => "900",
+/// Handle `@container` utilities, which set `container-type` and, for named
+/// containers, `container-name`
+fn handle_container(ctx: &HandlerContext<'_>) -> Option<UtilityOutput> {
+    let rest = &ctx.full_class[ctx.prefix_len..];
+    let (kind, name) = match rest.split_once('/') {
+        Some((kind, name)) => (kind, Some(name)),
+        None => (rest, None),
+    };
+    let container_type = match kind {
+        "" => "inline-size",
+        "-normal" => "normal",
+        "-size" => "size",
         _ => return None,
     };
 
-    Some(vec![CSSProperty::new(&pattern.css_property, value)])
+    let mut properties = vec![CSSProperty::new(&ctx.pattern.css_property, container_type)];
+    if let Some(name) = name {
+        properties.push(CSSProperty::new("container-name", name));
+    }
+    Some(properties.into())
+}
+
+/// Handle opacity utility
+fn handle_opacity(ctx: &HandlerContext<'_>) -> Option<UtilityOutput> {
+    let property = &ctx.pattern.css_property;
+
+    if let Some(ref arbitrary) = ctx.parsed.arbitrary {
+        return Some(vec![CSSProperty::new(property, arbitrary.as_str())].into());
+    }
+
+    // Parse opacity value (0-100)
+    let opacity: u8 = ctx.value_key()?.parse().ok().filter(|o| *o <= 100)?;
+    let css_value = format!("{}", opacity as f32 / 100.0);
+
+    Some(vec![CSSProperty::new(property, css_value)].into())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::ClassKind;
 
     #[test]
     fn test_spacing_match() {
@@ -681,6 +959,9 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -704,6 +985,9 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: true,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -725,6 +1009,9 @@ mod tests {
             arbitrary: Some("2rem".to_string()),
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -746,6 +1033,9 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -767,6 +1057,9 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -788,6 +1081,9 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -809,6 +1105,9 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -830,6 +1129,9 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -851,6 +1153,9 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -873,6 +1178,9 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -880,7 +1188,7 @@ mod tests {
 
         let result = result.unwrap();
         // px-4 should generate padding-left and padding-right
-        assert!(result.properties.len() >= 1);
+        assert!(!result.properties.is_empty());
     }
 
     #[test]
@@ -895,13 +1203,16 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
         assert!(result.is_some());
 
         let result = result.unwrap();
-        assert!(result.properties.len() >= 1);
+        assert!(!result.properties.is_empty());
     }
 
     #[test]
@@ -916,6 +1227,9 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -937,6 +1251,9 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -958,6 +1275,9 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -980,6 +1300,9 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -1010,6 +1333,9 @@ mod tests {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         };
 
         let result = matcher.match_class(&parsed);
@@ -1034,14 +1360,377 @@ mod tests {
                 arbitrary: None,
                 important: false,
                 negative: false,
+                source: None,
+                diagnostics: Vec::new(),
+                kind: ClassKind::Utility,
             };
 
             let result = matcher.match_class(&parsed);
             // Should match for valid scale values
-            if result.is_some() {
-                let r = result.unwrap();
+            if let Some(r) = result {
                 assert!(!r.properties.is_empty());
             }
         }
     }
+
+    fn color_class(raw: &str, utility: &str, value: &str, opacity: Option<u8>) -> ParsedClass {
+        let mut parsed = ParsedClass::new(raw);
+        parsed.utility = utility.to_string();
+        parsed.value = Some(value.to_string());
+        parsed.opacity = opacity;
+        parsed
+    }
+
+    #[test]
+    fn test_full_palette() {
+        let matcher = Matcher::new();
+
+        let result = matcher.match_class(&color_class("bg-rose-950", "bg", "rose-950", None)).unwrap();
+        assert_eq!(result.properties[0].value, "#4c0519");
+
+        let result = matcher.match_class(&color_class("text-sky-50", "text", "sky-50", None)).unwrap();
+        assert_eq!(result.properties[0].property, "color");
+        assert_eq!(result.properties[0].value, "#f0f9ff");
+
+        let theme = matcher.theme();
+        assert_eq!(theme.color("amber-400"), Some("#fbbf24"));
+    }
+
+    #[test]
+    fn test_color_utilities() {
+        let matcher = Matcher::new();
+        let cases = [
+            ("ring-red-500", "ring", "red-500", "--tw-ring-color"),
+            ("outline-red-500", "outline", "red-500", "outline-color"),
+            ("fill-red-500", "fill", "red-500", "fill"),
+            ("stroke-red-500", "stroke", "red-500", "stroke"),
+            ("decoration-red-500", "decoration", "red-500", "text-decoration-color"),
+            ("accent-red-500", "accent", "red-500", "accent-color"),
+            ("caret-red-500", "caret", "red-500", "caret-color"),
+            ("border-t-red-500", "border-t", "red-500", "border-top-color"),
+        ];
+
+        for (raw, utility, value, property) in cases {
+            let result = matcher.match_class(&color_class(raw, utility, value, None));
+            let result = result.unwrap_or_else(|| panic!("{} should match", raw));
+            assert_eq!(result.properties[0].property, property, "{}", raw);
+            assert_eq!(result.properties[0].value, "#ef4444", "{}", raw);
+        }
+    }
+
+    #[test]
+    fn test_color_selector_suffix() {
+        let matcher = Matcher::new();
+
+        let placeholder = matcher
+            .match_class(&color_class("placeholder-gray-400", "placeholder", "gray-400", None))
+            .unwrap();
+        assert_eq!(placeholder.selector_suffix.as_deref(), Some("::placeholder"));
+        assert_eq!(placeholder.properties[0].property, "color");
+
+        let divide = matcher
+            .match_class(&color_class("divide-gray-200", "divide", "gray-200", None))
+            .unwrap();
+        assert!(divide.selector_suffix.unwrap().contains(":not([hidden])"));
+    }
+
+    #[test]
+    fn test_gradient_stops() {
+        let matcher = Matcher::new();
+
+        let from = matcher.match_class(&color_class("from-blue-500", "from", "blue-500", None)).unwrap();
+        assert!(from.properties.iter().any(|p| p.property == "--tw-gradient-from" && p.value == "#3b82f6"));
+
+        let to = matcher.match_class(&color_class("to-blue-500/50", "to", "blue-500", Some(50))).unwrap();
+        assert_eq!(to.properties[0].value, "color-mix(in srgb, #3b82f6 50%, transparent)");
+    }
+
+    #[test]
+    fn test_opacity_modifier_everywhere() {
+        let matcher = Matcher::new();
+        let result = matcher
+            .match_class(&color_class("text-blue-500/50", "text", "blue-500", Some(50)))
+            .unwrap();
+        assert_eq!(result.properties[0].value, "color-mix(in srgb, #3b82f6 50%, transparent)");
+
+        let mut arbitrary = ParsedClass::new("bg-[#ff0000]/25");
+        arbitrary.utility = "bg".to_string();
+        arbitrary.arbitrary = Some("#ff0000".to_string());
+        arbitrary.opacity = Some(25);
+        let result = matcher.match_class(&arbitrary).unwrap();
+        assert_eq!(result.properties[0].value, "color-mix(in srgb, #ff0000 25%, transparent)");
+    }
+
+    #[test]
+    fn test_opacity_utility() {
+        let matcher = Matcher::new();
+        let mut parsed = ParsedClass::new("opacity-50");
+        parsed.utility = "opacity".to_string();
+        parsed.value = Some("50".to_string());
+
+        let result = matcher.match_class(&parsed).unwrap();
+        assert_eq!(result.properties[0].property, "opacity");
+        assert_eq!(result.properties[0].value, "0.5");
+    }
+
+    #[test]
+    fn test_unknown_color_does_not_match() {
+        let matcher = Matcher::new();
+        let result = matcher.match_class(&color_class("bg-nope-500", "bg", "nope-500", None));
+        assert!(result.is_none());
+    }
+
+    fn spacing_class(raw: &str) -> ParsedClass {
+        crate::parser::Parser::new().parse(raw)
+    }
+
+    fn declarations(result: &MatchResult) -> Vec<(&str, &str)> {
+        result
+            .properties
+            .iter()
+            .map(|p| (p.property.as_str(), p.value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_logical_spacing() {
+        let matcher = Matcher::new();
+
+        let ps = matcher.match_class(&spacing_class("ps-4")).unwrap();
+        assert_eq!(declarations(&ps), vec![("padding-inline-start", "1rem")]);
+
+        let me = matcher.match_class(&spacing_class("-me-2")).unwrap();
+        assert_eq!(declarations(&me), vec![("margin-inline-end", "-0.5rem")]);
+
+        // Padding has no negative form
+        assert!(matcher.match_class(&spacing_class("-p-4")).is_none());
+    }
+
+    #[test]
+    fn test_inset_and_scroll() {
+        let matcher = Matcher::new();
+
+        let inset = matcher.match_class(&spacing_class("inset-x-0")).unwrap();
+        assert_eq!(declarations(&inset), vec![("left", "0px"), ("right", "0px")]);
+
+        let top = matcher.match_class(&spacing_class("-top-1/2")).unwrap();
+        assert_eq!(declarations(&top), vec![("top", "-50%")]);
+
+        let scroll = matcher.match_class(&spacing_class("scroll-py-2")).unwrap();
+        assert_eq!(
+            declarations(&scroll),
+            vec![("scroll-padding-top", "0.5rem"), ("scroll-padding-bottom", "0.5rem")]
+        );
+    }
+
+    #[test]
+    fn test_sizing() {
+        let matcher = Matcher::new();
+
+        let size = matcher.match_class(&spacing_class("size-8")).unwrap();
+        assert_eq!(declarations(&size), vec![("width", "2rem"), ("height", "2rem")]);
+
+        let width = matcher.match_class(&spacing_class("w-1/3")).unwrap();
+        assert_eq!(declarations(&width), vec![("width", "33.333333%")]);
+
+        let height = matcher.match_class(&spacing_class("h-screen")).unwrap();
+        assert_eq!(declarations(&height), vec![("height", "100vh")]);
+    }
+
+    #[test]
+    fn test_border_radius_corners() {
+        let matcher = Matcher::new();
+
+        let rounded = matcher.match_class(&spacing_class("rounded")).unwrap();
+        assert_eq!(declarations(&rounded), vec![("border-radius", "0.25rem")]);
+
+        let lg = matcher.match_class(&spacing_class("rounded-lg")).unwrap();
+        assert_eq!(declarations(&lg), vec![("border-radius", "0.5rem")]);
+
+        let top = matcher.match_class(&spacing_class("rounded-t-xl")).unwrap();
+        assert_eq!(
+            declarations(&top),
+            vec![("border-top-left-radius", "0.75rem"), ("border-top-right-radius", "0.75rem")]
+        );
+
+        let start = matcher.match_class(&spacing_class("rounded-s")).unwrap();
+        assert_eq!(
+            declarations(&start),
+            vec![("border-start-start-radius", "0.25rem"), ("border-end-start-radius", "0.25rem")]
+        );
+    }
+
+    #[test]
+    fn test_arbitrary_property() {
+        let matcher = Matcher::new();
+        let parser = crate::parser::Parser::new();
+
+        let result = matcher.match_class(&parser.parse("[mask-type:luminance]")).unwrap();
+        assert_eq!(result.pattern_name, "arbitrary-property");
+        assert_eq!(result.properties, vec![CSSProperty::new("mask-type", "luminance")]);
+
+        let result = matcher
+            .match_class(&parser.parse("[--my-var:calc(100%_-_var(--gap\\_x))]"))
+            .unwrap();
+        assert_eq!(
+            result.properties,
+            vec![CSSProperty::new("--my-var", "calc(100% - var(--gap_x))")]
+        );
+
+        assert!(matcher.match_class(&parser.parse("[Foo:bar]")).is_none());
+        assert!(matcher.match_class(&parser.parse("[color:red;x]")).is_none());
+    }
+
+    #[test]
+    fn test_custom_handler() {
+        let mut matcher = Matcher::new();
+        matcher.register(
+            UtilityPattern::new("tab-size", "tab-")
+                .with_pattern(r"^tab-\d+$")
+                .with_handler(|ctx: &HandlerContext<'_>| {
+                    let size = ctx.value_key()?;
+                    Some(vec![CSSProperty::new("tab-size", size)].into())
+                }),
+        );
+
+        let result = matcher.match_class(&spacing_class("tab-4")).unwrap();
+        assert_eq!(result.pattern_name, "tab-size");
+        assert_eq!(declarations(&result), vec![("tab-size", "4")]);
+        assert!(result.extra_rules.is_empty());
+    }
+
+    #[test]
+    fn test_custom_handler_extra_rules() {
+        let mut matcher = Matcher::new();
+        matcher.register(
+            UtilityPattern::new("fade", "fade-")
+                .with_pattern(r"^fade-\d+$")
+                .with_handler(|ctx: &HandlerContext<'_>| {
+                    let duration = ctx.value_key()?;
+                    let output = UtilityOutput::from(vec![CSSProperty::new(
+                        "animation",
+                        format!("fade {}ms", duration),
+                    )]);
+                    Some(output.with_extra_rule("@keyframes fade {\n  from {\n    opacity: 0;\n  }\n}"))
+                }),
+        );
+
+        let result = matcher.match_class(&spacing_class("fade-150")).unwrap();
+        assert_eq!(declarations(&result), vec![("animation", "fade 150ms")]);
+        assert_eq!(result.extra_rules, vec!["@keyframes fade {\n  from {\n    opacity: 0;\n  }\n}"]);
+    }
+
+    #[test]
+    fn test_value_key_after_prefix() {
+        let parsed = spacing_class("rounded-t-lg");
+        let pattern = UtilityPattern::new("border-top-radius", "rounded-t");
+        let theme = Theme::default();
+
+        let ctx = HandlerContext {
+            parsed: &parsed,
+            pattern: &pattern,
+            full_class: "rounded-t-lg",
+            prefix_len: "rounded-t".len(),
+            theme: &theme,
+        };
+        assert_eq!(ctx.value_key(), Some("lg"));
+
+        let bare = HandlerContext { full_class: "rounded-t", ..ctx };
+        assert_eq!(bare.value_key(), Some(DEFAULT_VALUE_KEY));
+    }
+
+    #[test]
+    fn test_container_utilities() {
+        let matcher = Matcher::new();
+        let parser = crate::parser::Parser::new();
+
+        let result = matcher.match_class(&parser.parse("@container")).unwrap();
+        assert_eq!(result.properties, vec![CSSProperty::new("container-type", "inline-size")]);
+
+        let result = matcher.match_class(&parser.parse("@container/sidebar")).unwrap();
+        assert_eq!(
+            result.properties,
+            vec![
+                CSSProperty::new("container-type", "inline-size"),
+                CSSProperty::new("container-name", "sidebar"),
+            ]
+        );
+
+        let result = matcher.match_class(&parser.parse("@container-normal")).unwrap();
+        assert_eq!(result.properties, vec![CSSProperty::new("container-type", "normal")]);
+
+        assert!(matcher.match_class(&parser.parse("@container-foo")).is_none());
+    }
+
+    #[test]
+    fn test_side_order() {
+        assert_eq!(side_order("p-", "p"), 0);
+        assert_eq!(side_order("px-", "p"), 1);
+        assert_eq!(side_order("pt-", "p"), 2);
+        assert_eq!(side_order("top-", "inset"), 2);
+        assert_eq!(side_order("rounded", "rounded"), 0);
+        assert_eq!(side_order("rounded-tl", "rounded"), 3);
+
+        let matcher = Matcher::new();
+        let sort_order = |class: &str| matcher.match_class(&spacing_class(class)).unwrap().sort_order;
+        assert!(sort_order("m-4") < sort_order("my-2"));
+        assert!(sort_order("my-2") < sort_order("mt-1"));
+        assert!(sort_order("border-red-500") < sort_order("border-t-red-500"));
+    }
+
+    #[test]
+    fn test_font_size_from_theme() {
+        let matcher = Matcher::new();
+
+        let lg = matcher.match_class(&spacing_class("text-lg")).unwrap();
+        assert_eq!(
+            declarations(&lg),
+            vec![("font-size", "1.125rem"), ("line-height", "1.75rem")]
+        );
+
+        let leading = matcher.match_class(&spacing_class("text-sm/6")).unwrap();
+        assert_eq!(
+            declarations(&leading),
+            vec![("font-size", "0.875rem"), ("line-height", "1.5rem")]
+        );
+
+        let arbitrary = matcher.match_class(&spacing_class("text-[13px]")).unwrap();
+        assert_eq!(declarations(&arbitrary), vec![("font-size", "13px")]);
+
+        // Colors keep working on the shared prefix
+        let color = matcher.match_class(&spacing_class("text-[#123456]")).unwrap();
+        assert_eq!(declarations(&color), vec![("color", "#123456")]);
+    }
+
+    #[test]
+    fn test_theme_scales() {
+        let matcher = Matcher::new();
+
+        let shadow = matcher.match_class(&spacing_class("shadow")).unwrap();
+        assert_eq!(shadow.properties[0].property, "box-shadow");
+        assert!(shadow.properties[0].value.starts_with("0 1px 3px"));
+
+        let z = matcher.match_class(&spacing_class("-z-10")).unwrap();
+        assert_eq!(declarations(&z), vec![("z-index", "-10")]);
+
+        let duration = matcher.match_class(&spacing_class("duration-300")).unwrap();
+        assert_eq!(declarations(&duration), vec![("transition-duration", "300ms")]);
+    }
+
+    #[test]
+    fn test_custom_theme() {
+        let theme = Theme::default()
+            .with_spacing("gutter", "18px")
+            .with_font_size("body", crate::theme::FontSize::new("15px", "22px"));
+        let matcher = Matcher::with_theme(Arc::new(theme));
+
+        let gutter = matcher.match_class(&spacing_class("px-gutter")).unwrap();
+        assert_eq!(
+            declarations(&gutter),
+            vec![("padding-left", "18px"), ("padding-right", "18px")]
+        );
+
+        let body = matcher.match_class(&spacing_class("text-body")).unwrap();
+        assert_eq!(declarations(&body), vec![("font-size", "15px"), ("line-height", "22px")]);
+    }
 }
@@ -5,29 +5,67 @@
 //! - Utility name and value (p-4, bg-red-500)
 //! - Modifiers (opacity /50, important !, negative -)
 //! - Arbitrary values ([2rem], [#ff0000])
+//! - Arbitrary properties ([mask-type:luminance], [--my-var:10px])
 //! - Variant groups (hover:(text-white scale-105))
 
-use crate::types::ParsedClass;
+use crate::types::{ClassKind, ParsedClass};
 use memchr::memchr;
 
 /// High-performance CSS class parser
 #[derive(Debug, Clone)]
 pub struct Parser {
-    /// Cache for parsed classes (optional, managed externally)
-    _cache_enabled: bool,
+    /// Prefix every utility must carry (e.g., "tw-")
+    prefix: Option<String>,
 }
 
 impl Parser {
     /// Create a new Parser instance
     pub fn new() -> Self {
+        Self { prefix: None }
+    }
+
+    /// Create a Parser that only recognizes utilities carrying `prefix`
+    ///
+    /// The prefix sits between the variants and the utility, after any `!` or
+    /// `-` modifier (`hover:!-tw-mt-2`). It is removed from the parsed utility
+    /// but kept in `raw`, so selectors still use the prefixed name.
+    pub fn with_prefix(prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
         Self {
-            _cache_enabled: true,
+            prefix: (!prefix.is_empty()).then_some(prefix),
         }
     }
 
     /// Parse a single class name into its components
     #[inline]
     pub fn parse(&self, class: &str) -> ParsedClass {
+        match self.prefix {
+            Some(ref prefix) => self.parse_prefixed(class.trim(), prefix),
+            None => self.parse_class(class),
+        }
+    }
+
+    /// Strip the configured prefix and parse the rest
+    ///
+    /// Classes without the prefix come back with an empty utility, so they
+    /// never match a pattern.
+    fn parse_prefixed(&self, class: &str, prefix: &str) -> ParsedClass {
+        let start = utility_start(class);
+        let utility = class[start..].trim_start_matches(['!', '-']);
+        let at = class.len() - utility.len();
+
+        if !utility.starts_with(prefix) {
+            return ParsedClass::new(class);
+        }
+
+        let unprefixed = format!("{}{}", &class[..at], &utility[prefix.len()..]);
+        let mut parsed = self.parse_class(&unprefixed);
+        parsed.raw = class.to_string();
+        parsed
+    }
+
+    /// Parse a class name that carries no prefix
+    fn parse_class(&self, class: &str) -> ParsedClass {
         let class = class.trim();
         if class.is_empty() {
             return ParsedClass::new("");
@@ -65,7 +103,8 @@ impl Parser {
         }
 
         // Extract variants (everything before the last colon that's not inside brackets)
-        let utility_start = self.extract_variants(&class[pos..], &mut parsed.variants);
+        let utility_start =
+            self.extract_variants(&class[pos..], &mut parsed.variants, &mut parsed.diagnostics);
         pos += utility_start;
 
         // Parse the remaining utility
@@ -77,15 +116,51 @@ impl Parser {
     }
 
     /// Parse all classes in a whitespace-separated string
+    ///
+    /// Variant groups such as `hover:(bg-red-500 text-white)` are expanded into
+    /// one `ParsedClass` per grouped utility, each pointing back to its group
+    /// token through `source`.
     pub fn parse_all(&self, class_string: &str) -> Vec<ParsedClass> {
-        class_string
-            .split_whitespace()
-            .map(|c| self.parse(c))
-            .collect()
+        self.parse_all_with(class_string, |class| self.parse(class))
+    }
+
+    /// `parse_all` with a custom per-class parse step (e.g., a cached one)
+    pub(crate) fn parse_all_with(
+        &self,
+        class_string: &str,
+        parse: impl Fn(&str) -> ParsedClass,
+    ) -> Vec<ParsedClass> {
+        // Fast path: no groups possible without parentheses
+        if memchr(b'(', class_string.as_bytes()).is_none() {
+            return class_string.split_whitespace().map(parse).collect();
+        }
+
+        let mut result = Vec::new();
+        for token in split_classes(class_string) {
+            if find_variant_group(token).is_none() {
+                result.push(parse(token));
+                continue;
+            }
+
+            for class in expand_variant_group(token) {
+                let mut parsed = parse(&class);
+                parsed.source = Some(token.to_string());
+                result.push(parsed);
+            }
+        }
+        result
     }
 
     /// Extract variants from class string, returns position after variants
-    fn extract_variants(&self, input: &str, variants: &mut Vec<String>) -> usize {
+    ///
+    /// Arbitrary variants (`[&>*]`, `[@media(min-width:900px)]`) are kept as
+    /// written; invalid ones are dropped with a diagnostic.
+    fn extract_variants(
+        &self,
+        input: &str,
+        variants: &mut Vec<String>,
+        diagnostics: &mut Vec<String>,
+    ) -> usize {
         let bytes = input.as_bytes();
         let _len = bytes.len();
         let mut pos = 0;
@@ -100,7 +175,17 @@ impl Parser {
                 b':' if bracket_depth == 0 => {
                     // Extract variant
                     let variant = &input[pos..i];
-                    if !variant.is_empty() && is_valid_variant(variant) {
+                    if variant.starts_with('[') {
+                        if let Err(reason) = parse_arbitrary_variant(variant) {
+                            diagnostics.push(format!(
+                                "invalid arbitrary variant `{}`: {}",
+                                variant, reason
+                            ));
+                        } else {
+                            variants.push(variant.to_string());
+                        }
+                        last_colon = Some(i);
+                    } else if !variant.is_empty() && is_valid_variant(variant) {
                         variants.push(variant.to_string());
                         last_colon = Some(i);
                     }
@@ -147,6 +232,23 @@ impl Parser {
         let bytes = input.as_bytes();
         let len = bytes.len();
 
+        // Check for arbitrary property [prop:value]
+        if let Some(inner) = input.strip_prefix('[').and_then(|i| i.strip_suffix(']')) {
+            if let Some(colon) = top_level_colon(inner) {
+                parsed.kind = ClassKind::ArbitraryProperty;
+                parsed.utility = inner[..colon].to_string();
+                parsed.arbitrary = Some(inner[colon + 1..].to_string());
+
+                if let Err(reason) = validate_arbitrary_property(&inner[..colon], &inner[colon + 1..]) {
+                    parsed.diagnostics.push(format!(
+                        "invalid arbitrary property `{}`: {}",
+                        input, reason
+                    ));
+                }
+                return;
+            }
+        }
+
         // Check for arbitrary value [...]
         if let Some(bracket_start) = memchr(b'[', bytes) {
             if let Some(bracket_end) = memchr(b']', &bytes[bracket_start..]) {
@@ -172,8 +274,9 @@ impl Parser {
             }
         }
 
-        // Check for opacity modifier (/50)
-        if let Some(slash_pos) = memchr(b'/', bytes) {
+        // Check for opacity modifier (/50). In `@container/sidebar` the slash
+        // names the container instead, so it stays part of the utility.
+        if let Some(slash_pos) = memchr(b'/', bytes).filter(|_| !input.starts_with('@')) {
             let utility_part = &input[..slash_pos];
             self.parse_simple_utility(utility_part, parsed);
 
@@ -194,6 +297,258 @@ impl Default for Parser {
     }
 }
 
+/// An arbitrary variant such as `[&>*]` or `[@media(min-width:900px)]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArbitraryVariant {
+    /// Selector template where `&` stands for the class selector (e.g., "& > *")
+    Selector(String),
+
+    /// At-rule wrapping the rule (e.g., "@media (min-width:900px)")
+    AtRule(String),
+}
+
+/// Validate and normalize a bracketed arbitrary variant
+///
+/// Underscores become spaces. Selector variants must contain `&`; at-rule
+/// variants may use `@media`, `@supports` or `@container`. The error is a
+/// short reason suitable for a diagnostic.
+pub fn parse_arbitrary_variant(variant: &str) -> Result<ArbitraryVariant, String> {
+    let inner = variant
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+        .ok_or_else(|| "must be wrapped in `[...]`".to_string())?;
+    let inner = inner.replace('_', " ");
+    let inner = inner.trim();
+
+    if inner.is_empty() {
+        return Err("empty variant".to_string());
+    }
+    if inner.contains(['{', '}', ';']) {
+        return Err("must not contain `{`, `}` or `;`".to_string());
+    }
+    if !is_balanced(inner) {
+        return Err("unbalanced brackets or parentheses".to_string());
+    }
+
+    if let Some(rest) = inner.strip_prefix('@') {
+        let name_end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .unwrap_or(rest.len());
+        let name = &rest[..name_end];
+        let condition = rest[name_end..].trim();
+
+        if !matches!(name, "media" | "supports" | "container") {
+            return Err(format!("unsupported at-rule `@{}`", name));
+        }
+        if condition.is_empty() {
+            return Err(format!("`@{}` needs a condition", name));
+        }
+        return Ok(ArbitraryVariant::AtRule(format!("@{} {}", name, condition)));
+    }
+
+    if !inner.contains('&') {
+        return Err("selector variants must contain `&`".to_string());
+    }
+    Ok(ArbitraryVariant::Selector(inner.to_string()))
+}
+
+/// Position of the first `:` outside brackets, parentheses and quotes
+fn top_level_colon(s: &str) -> Option<usize> {
+    let mut depth = 0i32;
+    let mut quote = None;
+    for (i, b) in s.bytes().enumerate() {
+        match (quote, b) {
+            (Some(q), _) if b == q => quote = None,
+            (Some(_), _) => {}
+            (None, b'\'' | b'"') => quote = Some(b),
+            (None, b'(' | b'[') => depth += 1,
+            (None, b')' | b']') => depth -= 1,
+            (None, b':') if depth == 0 => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Validate the parts of an arbitrary property
+///
+/// The property must be a lowercase CSS property name (optionally
+/// vendor-prefixed) or a custom property. The value must be non-empty and
+/// unable to escape its declaration.
+fn validate_arbitrary_property(property: &str, value: &str) -> Result<(), String> {
+    let valid_name = if let Some(name) = property.strip_prefix("--") {
+        !name.is_empty()
+            && name
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    } else {
+        let name = property.strip_prefix('-').unwrap_or(property);
+        name.starts_with(|c: char| c.is_ascii_lowercase())
+            && !name.ends_with('-')
+            && name.bytes().all(|b| b.is_ascii_lowercase() || b == b'-')
+    };
+    if !valid_name {
+        return Err(format!("`{}` is not a valid property name", property));
+    }
+
+    if value.trim_matches('_').is_empty() {
+        return Err("empty value".to_string());
+    }
+    if value.contains(['{', '}', ';']) {
+        return Err("value must not contain `{`, `}` or `;`".to_string());
+    }
+    if value.starts_with("//") {
+        return Err("value looks like a URL".to_string());
+    }
+    if !is_balanced(value) {
+        return Err("unbalanced brackets or parentheses".to_string());
+    }
+    Ok(())
+}
+
+/// Check that brackets and parentheses nest properly
+fn is_balanced(s: &str) -> bool {
+    let mut stack = Vec::new();
+    for ch in s.chars() {
+        match ch {
+            '(' | '[' => stack.push(ch),
+            ')' if stack.pop() != Some('(') => return false,
+            ']' if stack.pop() != Some('[') => return false,
+            _ => {}
+        }
+    }
+    stack.is_empty()
+}
+
+/// Split a class string on whitespace, keeping variant groups and arbitrary
+/// values (which may contain spaces) together as single tokens.
+///
+/// Falls back to plain whitespace splitting when parentheses or brackets are
+/// unbalanced, so malformed input never swallows the rest of the string.
+pub fn split_classes(input: &str) -> Vec<&str> {
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::new();
+    let mut depth: u32 = 0;
+    let mut start = None;
+
+    for (i, &byte) in bytes.iter().enumerate() {
+        match byte {
+            b'(' | b'[' => depth += 1,
+            b')' | b']' => depth = depth.saturating_sub(1),
+            b if b.is_ascii_whitespace() && depth == 0 => {
+                if let Some(s) = start.take() {
+                    tokens.push(&input[s..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        if start.is_none() {
+            start = Some(i);
+        }
+    }
+
+    if depth != 0 {
+        return input.split_whitespace().collect();
+    }
+
+    if let Some(s) = start {
+        tokens.push(&input[s..]);
+    }
+
+    tokens
+}
+
+/// Expand a variant group token into the individual classes it stands for.
+///
+/// `md:(hover:(p-4 m-2) flex)` becomes `md:hover:p-4`, `md:hover:m-2` and
+/// `md:flex`. The group prefix (variants plus any `!`/`-` modifiers) is
+/// prepended to every member, so `!hover:(p-4 m-2)` yields `!hover:p-4` and
+/// `!hover:m-2`. Tokens without a group are returned unchanged.
+pub fn expand_variant_group(token: &str) -> Vec<String> {
+    let mut classes = Vec::new();
+    expand_group_into(token, &mut classes);
+    classes
+}
+
+fn expand_group_into(token: &str, classes: &mut Vec<String>) {
+    match find_variant_group(token) {
+        Some((open, close)) => {
+            let prefix = &token[..open];
+            for member in split_classes(&token[open + 1..close]) {
+                let mut class = String::with_capacity(prefix.len() + member.len());
+                class.push_str(prefix);
+                class.push_str(member);
+                expand_group_into(&class, classes);
+            }
+        }
+        None => classes.push(token.to_string()),
+    }
+}
+
+/// Locate the parentheses of a variant group in a token.
+///
+/// The group must open directly after a variant colon or a leading `!`/`-`
+/// modifier, and its matching `)` must close the token. Parentheses inside
+/// arbitrary values (`bg-[rgb(0,0,0)]`) are never treated as groups.
+pub(crate) fn find_variant_group(token: &str) -> Option<(usize, usize)> {
+    let bytes = token.as_bytes();
+    let mut bracket_depth: u32 = 0;
+    let mut open = None;
+
+    for (i, &byte) in bytes.iter().enumerate() {
+        match byte {
+            b'[' => bracket_depth += 1,
+            b']' => bracket_depth = bracket_depth.saturating_sub(1),
+            b'(' if bracket_depth == 0 => {
+                open = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let open = open?;
+    let prefix = token[..open].trim_end_matches(['!', '-']);
+    if !prefix.is_empty() && !prefix.ends_with(':') {
+        return None;
+    }
+
+    let mut depth: u32 = 0;
+    for (i, &byte) in bytes.iter().enumerate().skip(open) {
+        match byte {
+            b'(' | b'[' => depth += 1,
+            b')' | b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return (byte == b')' && i == bytes.len() - 1).then_some((open, i));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Byte offset just past the last variant separator (a colon outside brackets)
+fn utility_start(class: &str) -> usize {
+    let mut bracket_depth: u32 = 0;
+    let mut start = 0;
+
+    for (i, &byte) in class.as_bytes().iter().enumerate() {
+        match byte {
+            b'[' => bracket_depth += 1,
+            b']' => bracket_depth = bracket_depth.saturating_sub(1),
+            b':' if bracket_depth == 0 => start = i + 1,
+            _ => {}
+        }
+    }
+
+    start
+}
+
 /// Check if bytes contain any special parsing characters
 #[inline]
 fn contains_special_char(bytes: &[u8]) -> bool {
@@ -211,8 +566,8 @@ fn is_variant_start(bytes: &[u8]) -> bool {
     }
 
     // Look for a colon that comes before any dash (after the leading dash)
-    for i in 2..bytes.len() {
-        match bytes[i] {
+    for &byte in &bytes[2..] {
+        match byte {
             b':' => return true,  // Found variant separator
             b'-' => return false, // Found utility separator first
             _ => continue,
@@ -221,12 +576,54 @@ fn is_variant_start(bytes: &[u8]) -> bool {
     false
 }
 
-/// Check if a string is a valid variant name
+/// Check if a string is a valid variant name, optionally followed by a
+/// `/name` modifier (e.g., "group-hover/card")
+///
+/// Besides plain names this accepts `*`, functional variants with an
+/// arbitrary value (e.g., "data-[state=open]", "has-[:checked]") and container
+/// query variants (e.g., "@md", "@[500px]", "@max-lg/sidebar").
 #[inline]
 fn is_valid_variant(s: &str) -> bool {
+    if s == "*" {
+        return true;
+    }
+    let (s, is_container) = match s.strip_prefix('@') {
+        Some(rest) => (rest, true),
+        None => (s, false),
+    };
+    let is_base = |v: &str| {
+        is_variant_name(v) || is_functional_variant(v) || (is_container && is_arbitrary_value(v))
+    };
+    if let Some((variant, modifier)) = s.rsplit_once('/') {
+        if is_variant_name(modifier) && is_base(variant) {
+            return true;
+        }
+    }
+    is_base(s)
+}
+
+/// Check if a string is a plain variant name (e.g., "hover", "group-hover")
+#[inline]
+fn is_variant_name(s: &str) -> bool {
     !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
 }
 
+/// Check for `name-[value]`, where the value cannot break out of a selector
+fn is_functional_variant(s: &str) -> bool {
+    match s.split_once("-[") {
+        Some((name, _)) => is_variant_name(name) && is_arbitrary_value(&s[name.len() + 1..]),
+        None => false,
+    }
+}
+
+/// Check for a bracketed value that cannot break out of a selector or query
+fn is_arbitrary_value(s: &str) -> bool {
+    match s.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        Some(value) => !value.is_empty() && !value.contains(['{', '}', ';']) && is_balanced(value),
+        None => false,
+    }
+}
+
 /// Find the dash that separates utility name from value
 /// Handles multi-part utilities like "bg-gradient-to-r"
 #[inline]
@@ -371,10 +768,226 @@ mod tests {
         assert_eq!(parsed.opacity, Some(50));
     }
 
+    #[test]
+    fn test_prefix() {
+        let parser = Parser::with_prefix("tw-");
+
+        let parsed = parser.parse("hover:tw-bg-red-500");
+        assert_eq!(parsed.raw, "hover:tw-bg-red-500");
+        assert_eq!(parsed.variants, vec!["hover"]);
+        assert_eq!(parsed.utility, "bg");
+        assert_eq!(parsed.value, Some("red-500".to_string()));
+
+        let parsed = parser.parse("md:!-tw-mt-2");
+        assert!(parsed.important);
+        assert!(parsed.negative);
+        assert_eq!(parsed.full_utility(), "mt-2");
+
+        let parsed = parser.parse("tw--mt-2");
+        assert!(parsed.negative);
+        assert_eq!(parsed.full_utility(), "mt-2");
+
+        // Unprefixed classes are not utilities
+        let parsed = parser.parse("p-4");
+        assert_eq!(parsed.utility, "");
+    }
+
+    #[test]
+    fn test_named_group_variant() {
+        let parser = Parser::new();
+        let parsed = parser.parse("group-hover/card:peer-checked:underline");
+        assert_eq!(parsed.variants, vec!["group-hover/card", "peer-checked"]);
+        assert_eq!(parsed.utility, "underline");
+
+        assert!(!is_valid_variant("group-hover/"));
+        assert!(!is_valid_variant("group-hover/a/b"));
+    }
+
+    #[test]
+    fn test_structural_variants() {
+        let parser = Parser::new();
+
+        let parsed = parser.parse("aria-[sort=ascending]:data-[state=open]:underline");
+        assert_eq!(parsed.variants, vec!["aria-[sort=ascending]", "data-[state=open]"]);
+        assert_eq!(parsed.utility, "underline");
+
+        let parsed = parser.parse("has-[:checked]:*:nth-[2n+1]:p-4");
+        assert_eq!(parsed.variants, vec!["has-[:checked]", "*", "nth-[2n+1]"]);
+        assert_eq!(parsed.full_utility(), "p-4");
+
+        assert!(is_valid_variant("data-[x=a/b]"));
+        assert!(!is_valid_variant("data-[x{}]"));
+        assert!(!is_valid_variant("has-[:is(a]"));
+    }
+
+    #[test]
+    fn test_container_variants() {
+        let parser = Parser::new();
+
+        let parsed = parser.parse("@md:@max-lg/sidebar:@[500px]:flex");
+        assert_eq!(parsed.variants, vec!["@md", "@max-lg/sidebar", "@[500px]"]);
+        assert_eq!(parsed.utility, "flex");
+
+        // The slash names the container rather than an opacity
+        let parsed = parser.parse("@container/sidebar");
+        assert_eq!(parsed.full_utility(), "@container/sidebar");
+        assert_eq!(parsed.opacity, None);
+    }
+
+    #[test]
+    fn test_arbitrary_selector_variant() {
+        let parser = Parser::new();
+        let parsed = parser.parse("[&:nth-child(3)]:underline");
+        assert_eq!(parsed.variants, vec!["[&:nth-child(3)]"]);
+        assert_eq!(parsed.utility, "underline");
+        assert!(parsed.diagnostics.is_empty());
+
+        let parsed = parser.parse("hover:[&>*]:p-4");
+        assert_eq!(parsed.variants, vec!["hover", "[&>*]"]);
+        assert_eq!(parsed.full_utility(), "p-4");
+    }
+
+    #[test]
+    fn test_arbitrary_at_rule_variant() {
+        assert_eq!(
+            parse_arbitrary_variant("[@media(min-width:900px)]"),
+            Ok(ArbitraryVariant::AtRule("@media (min-width:900px)".to_string()))
+        );
+        assert_eq!(
+            parse_arbitrary_variant("[@supports_not_(display:grid)]"),
+            Ok(ArbitraryVariant::AtRule("@supports not (display:grid)".to_string()))
+        );
+        assert_eq!(
+            parse_arbitrary_variant("[.dark_&]"),
+            Ok(ArbitraryVariant::Selector(".dark &".to_string()))
+        );
+
+        let parsed = Parser::new().parse("[@supports(display:grid)]:grid");
+        assert_eq!(parsed.variants, vec!["[@supports(display:grid)]"]);
+        assert_eq!(parsed.utility, "grid");
+    }
+
+    #[test]
+    fn test_invalid_arbitrary_variant() {
+        let parser = Parser::new();
+
+        let parsed = parser.parse("[p]:underline");
+        assert!(parsed.variants.is_empty());
+        assert_eq!(parsed.utility, "underline");
+        assert_eq!(
+            parsed.diagnostics,
+            vec!["invalid arbitrary variant `[p]`: selector variants must contain `&`"]
+        );
+
+        assert!(parse_arbitrary_variant("[@font-face(x)]").is_err());
+        assert!(parse_arbitrary_variant("[@media]").is_err());
+        assert!(parse_arbitrary_variant("[&(]").is_err());
+        assert!(parse_arbitrary_variant("[&{color:red}]").is_err());
+    }
+
+    #[test]
+    fn test_arbitrary_property() {
+        let parser = Parser::new();
+
+        let parsed = parser.parse("[mask-type:luminance]");
+        assert_eq!(parsed.kind, ClassKind::ArbitraryProperty);
+        assert_eq!(parsed.utility, "mask-type");
+        assert_eq!(parsed.arbitrary, Some("luminance".to_string()));
+        assert_eq!(parsed.full_utility(), "[mask-type:luminance]");
+        assert!(parsed.diagnostics.is_empty());
+
+        let parsed = parser.parse("md:hover:![--my-var:calc(100%_-_10px)]");
+        assert_eq!(parsed.variants, vec!["md", "hover"]);
+        assert!(parsed.important);
+        assert_eq!(parsed.utility, "--my-var");
+        assert_eq!(parsed.arbitrary, Some("calc(100%_-_10px)".to_string()));
+
+        let parsed = parser.parse("[background:url(data:image/png;base64,x)]");
+        assert_eq!(parsed.utility, "background");
+        assert!(!parsed.diagnostics.is_empty());
+
+        // Bare arbitrary values stay as they were
+        let parsed = parser.parse("[2rem]");
+        assert_eq!(parsed.kind, ClassKind::Utility);
+        assert_eq!(parsed.arbitrary, Some("2rem".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_arbitrary_property() {
+        assert!(validate_arbitrary_property("-webkit-mask", "none").is_ok());
+        assert!(validate_arbitrary_property("--x_1", "0").is_ok());
+
+        assert!(validate_arbitrary_property("Key", "value").is_err());
+        assert!(validate_arbitrary_property("0", "1").is_err());
+        assert!(validate_arbitrary_property("--", "1").is_err());
+        assert!(validate_arbitrary_property("color-", "red").is_err());
+        assert!(validate_arbitrary_property("color", "").is_err());
+        assert!(validate_arbitrary_property("http", "//example.com").is_err());
+        assert!(validate_arbitrary_property("color", "red}body{x").is_err());
+        assert!(validate_arbitrary_property("width", "calc(1px").is_err());
+    }
+
     #[test]
     fn test_parse_all() {
         let parser = Parser::new();
         let parsed = parser.parse_all("p-4 m-2 bg-red-500");
         assert_eq!(parsed.len(), 3);
     }
+
+    #[test]
+    fn test_parse_all_variant_group() {
+        let parser = Parser::new();
+        let parsed = parser.parse_all("flex hover:(bg-red-500 text-white)");
+        assert_eq!(parsed.len(), 3);
+
+        assert_eq!(parsed[0].raw, "flex");
+        assert_eq!(parsed[0].source, None);
+
+        assert_eq!(parsed[1].raw, "hover:bg-red-500");
+        assert_eq!(parsed[1].variants, vec!["hover"]);
+        assert_eq!(parsed[1].utility, "bg");
+        assert_eq!(parsed[1].source.as_deref(), Some("hover:(bg-red-500 text-white)"));
+
+        assert_eq!(parsed[2].raw, "hover:text-white");
+        assert_eq!(parsed[2].value, Some("white".to_string()));
+    }
+
+    #[test]
+    fn test_parse_all_nested_variant_group() {
+        let parser = Parser::new();
+        let parsed = parser.parse_all("md:(hover:(p-4 m-2) flex)");
+        let raws: Vec<&str> = parsed.iter().map(|p| p.raw.as_str()).collect();
+        assert_eq!(raws, vec!["md:hover:p-4", "md:hover:m-2", "md:flex"]);
+        assert_eq!(parsed[1].variants, vec!["md", "hover"]);
+    }
+
+    #[test]
+    fn test_parse_all_variant_group_shared_modifiers() {
+        let parser = Parser::new();
+
+        let important = parser.parse_all("!hover:(p-4 m-2)");
+        assert!(important.iter().all(|p| p.important));
+        assert_eq!(important[0].variants, vec!["hover"]);
+
+        let negative = parser.parse_all("md:-(mt-4 ml-2)");
+        assert_eq!(negative.len(), 2);
+        assert!(negative.iter().all(|p| p.negative));
+        assert_eq!(negative[1].utility, "ml");
+    }
+
+    #[test]
+    fn test_parse_all_keeps_arbitrary_parens() {
+        let parser = Parser::new();
+        let parsed = parser.parse_all("bg-[rgb(0,0,0)] w-[calc(100%_-_2rem)]");
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].arbitrary, Some("rgb(0,0,0)".to_string()));
+        assert!(parsed.iter().all(|p| p.source.is_none()));
+    }
+
+    #[test]
+    fn test_parse_all_unbalanced_group() {
+        let parser = Parser::new();
+        let parsed = parser.parse_all("hover:(p-4 m-2");
+        assert_eq!(parsed.len(), 2);
+    }
 }
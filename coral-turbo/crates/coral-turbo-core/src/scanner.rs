@@ -0,0 +1,232 @@
+//! Single-pass class candidate scanner
+//!
+//! Walks file contents byte by byte and yields every token that could be a
+//! class name, whatever the file type: attribute values, props, plain
+//! strings, arrays, template literals and server-side templates alike.
+//! Candidates are only checked for balanced brackets and variant groups
+//! here; the extractor validates them and the matcher has the final say.
+
+/// Bytes that can appear in a class name outside brackets
+const CLASS_BYTES: [bool; 256] = {
+    let mut table = [false; 256];
+    let mut b = 0;
+    while b < 256 {
+        table[b] = matches!(
+            b as u8,
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9'
+                | b'-' | b'_' | b':' | b'/' | b'.' | b'!' | b'#' | b'%' | b'@' | b'*' | b'&' | b'+'
+                | b'[' | b']' | b'(' | b')'
+        );
+        b += 1;
+    }
+    table
+};
+
+/// Prefix of Svelte class directives (`class:hidden={cond}`)
+const SVELTE_DIRECTIVE: &str = "class:";
+
+/// Iterator over `(byte offset, candidate)` pairs in a source file
+#[derive(Debug, Clone)]
+pub struct Scanner<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    /// Scan `input` from the start
+    pub fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for Scanner<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.input.as_bytes();
+
+        while self.pos < bytes.len() {
+            let start = self.pos;
+            if !CLASS_BYTES[bytes[start] as usize] {
+                self.pos += 1;
+                continue;
+            }
+
+            let end = match scan_candidate(bytes, start) {
+                Ok(end) => end,
+                Err(resume) => {
+                    // Unbalanced: what follows its last opening bracket or
+                    // group may still hold candidates
+                    self.pos = resume;
+                    continue;
+                }
+            };
+            self.pos = end.max(start + 1);
+
+            let candidate = &self.input[start..end];
+            if let Some(class) = candidate.strip_prefix(SVELTE_DIRECTIVE) {
+                if !class.is_empty() {
+                    return Some((start + SVELTE_DIRECTIVE.len(), class));
+                }
+            }
+
+            // Tag names (`<div`), attribute and prop names (`class=`), and
+            // function names (`cn(`)
+            if (start > 0 && bytes[start - 1] == b'<') || matches!(bytes.get(end), Some(b'=' | b'(')) {
+                continue;
+            }
+
+            // Punctuation runs such as `/` or `&&`
+            if candidate.bytes().any(|b| b.is_ascii_alphanumeric()) {
+                return Some((start, candidate));
+            }
+        }
+
+        None
+    }
+}
+
+/// Find the end of the candidate starting at `start`
+///
+/// When its brackets or variant groups are not balanced, returns the position
+/// after the last bracket or group it opened instead. Nothing before that
+/// position opens another one, so resuming there scans every byte of the
+/// input a bounded number of times, however many brackets are left open.
+///
+/// Inside `[...]` anything but whitespace and double or back quotes is
+/// allowed, since arbitrary values encode spaces as `_`. A `(` directly after
+/// a variant colon (or a leading `!`/`-`) opens a variant group, whose
+/// members are separated by whitespace.
+fn scan_candidate(bytes: &[u8], start: usize) -> Result<usize, usize> {
+    let mut brackets: u32 = 0;
+    let mut groups: u32 = 0;
+    let mut last_open = start;
+    let mut i = start;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+
+        if brackets > 0 {
+            match b {
+                b'[' => {
+                    brackets += 1;
+                    last_open = i;
+                }
+                b']' => brackets -= 1,
+                b'"' | b'`' => return Err(last_open + 1),
+                _ if b.is_ascii_whitespace() => return Err(last_open + 1),
+                _ => {}
+            }
+            i += 1;
+            continue;
+        }
+
+        match b {
+            b'[' => {
+                brackets += 1;
+                last_open = i;
+            }
+            b']' => break,
+            b'(' if opens_group(bytes, start, i) => {
+                groups += 1;
+                last_open = i;
+            }
+            b'(' => break,
+            b')' if groups > 0 => groups -= 1,
+            b')' => break,
+            _ if groups > 0 && b.is_ascii_whitespace() => {}
+            _ if CLASS_BYTES[b as usize] => {}
+            _ if groups > 0 => return Err(last_open + 1),
+            _ => break,
+        }
+        i += 1;
+    }
+
+    if brackets > 0 || groups > 0 {
+        return Err(last_open + 1);
+    }
+    Ok(i)
+}
+
+/// Whether the `(` at `i` opens a variant group
+fn opens_group(bytes: &[u8], start: usize, i: usize) -> bool {
+    match i.checked_sub(1).map(|prev| bytes[prev]) {
+        Some(b':') => i > start,
+        Some(b'!' | b'-') => i == start + 1,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(input: &str) -> Vec<&str> {
+        Scanner::new(input).map(|(_, candidate)| candidate).collect()
+    }
+
+    #[test]
+    fn test_scan_markup() {
+        let found = candidates(r#"<div class="p-4 hover:bg-red-500" data-id="x">Hi</div>"#);
+        assert_eq!(found, vec!["p-4", "hover:bg-red-500", "x", "Hi"]);
+    }
+
+    #[test]
+    fn test_scan_any_file_type() {
+        // Plain strings, arrays and component props
+        assert_eq!(candidates(r#"const a = ["flex", 'p-2']"#), vec!["const", "a", "flex", "p-2"]);
+        assert!(candidates(r#"<Card cls="rounded-lg shadow">"#).contains(&"rounded-lg"));
+
+        // Rust, Go, PHP and Elixir templates
+        assert!(candidates(r#"html! { <p class={"text-sm"}></p> }"#).contains(&"text-sm"));
+        assert!(candidates(r#"<p class="{{ .Cls }} mt-2">"#).contains(&"mt-2"));
+        assert!(candidates(r#"<?php echo "gap-4"; ?>"#).contains(&"gap-4"));
+        assert!(candidates(r#"<.link class={["px-3", @active && "font-bold"]}>"#).contains(&"font-bold"));
+    }
+
+    #[test]
+    fn test_scan_skips_names_and_calls() {
+        assert_eq!(candidates(r#"clsx("m-2", { "text-red-500": ok })"#), vec!["m-2", "text-red-500", "ok"]);
+        assert_eq!(candidates(r#"<div className={cn("flex")} />"#), vec!["flex"]);
+    }
+
+    #[test]
+    fn test_scan_arbitrary_values() {
+        assert_eq!(
+            candidates(r#"class="grid-cols-[1fr,_2fr] bg-[rgb(0,0,0)] content-['hi'] [&>*]:p-1""#),
+            vec!["grid-cols-[1fr,_2fr]", "bg-[rgb(0,0,0)]", "content-['hi']", "[&>*]:p-1"]
+        );
+
+        // Brackets never swallow whitespace
+        assert_eq!(candidates("w-[10px p-4"), vec!["10px", "p-4"]);
+    }
+
+    #[test]
+    fn test_scan_variant_groups() {
+        assert_eq!(
+            candidates(r#"class="flex md:(hover:(p-4 m-2) underline) !(mt-1)""#),
+            vec!["flex", "md:(hover:(p-4 m-2) underline)", "!(mt-1)"]
+        );
+        assert_eq!(candidates("md:(p-4\"x"), vec!["p-4", "x"]);
+    }
+
+    #[test]
+    fn test_scan_unbalanced_is_linear() {
+        assert_eq!(candidates("[[a [b]c hover:(p-4"), vec!["a", "[b]c", "p-4"]);
+
+        // Each of these took seconds when every open bracket or group
+        // rescanned the rest of the input
+        for unit in ["[", "a:(", "[a:(", "w-[(", "!("] {
+            let input = format!("{} p-4", unit.repeat(50_000));
+            let started = std::time::Instant::now();
+            assert_eq!(Scanner::new(&input).last().map(|(_, c)| c), Some("p-4"));
+            assert!(started.elapsed() < std::time::Duration::from_secs(1), "{:?}", unit);
+        }
+    }
+
+    #[test]
+    fn test_scan_svelte_directive() {
+        let found: Vec<_> = Scanner::new("<p class:hidden={!open}>").collect();
+        assert!(found.contains(&(9, "hidden")));
+    }
+}
@@ -0,0 +1,433 @@
+//! Incremental extraction across a set of files
+//!
+//! An `ExtractionSession` remembers the classes of every file it has seen
+//! and how many files use each class, so re-extracting one changed file
+//! tells exactly which classes entered or left the project.
+
+use crate::extractor::Extractor;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{self, Component, Path, PathBuf};
+
+#[cfg(feature = "fs")]
+use crate::content::{content_files, read_text, GlobError};
+#[cfg(feature = "fs")]
+use crate::types::ContentConfig;
+#[cfg(feature = "fs")]
+use rayon::prelude::*;
+
+/// Classes that entered or left the session with an update, sorted
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassChanges {
+    /// Classes no file used before
+    pub added: Vec<String>,
+
+    /// Classes no file uses anymore
+    pub removed: Vec<String>,
+}
+
+impl ClassChanges {
+    /// Whether the set of classes stayed the same
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    pub(crate) fn sort(mut self) -> Self {
+        self.added.sort_unstable();
+        self.removed.sort_unstable();
+        self
+    }
+}
+
+/// What the session knows about one file
+#[derive(Debug, Clone)]
+struct FileEntry {
+    hash: u64,
+    classes: FxHashSet<String>,
+}
+
+/// Stateful extraction keyed by file path
+///
+/// Paths are made absolute against the current directory and cleared of `.`
+/// and `..` components (without resolving symlinks), so a file named by a
+/// relative path in one call and an absolute one in the next is tracked once.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractionSession {
+    extractor: Extractor,
+    files: FxHashMap<PathBuf, FileEntry>,
+
+    /// Number of files using each class
+    counts: FxHashMap<String, usize>,
+}
+
+impl ExtractionSession {
+    /// Create an empty session with the default extractor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty session extracting with `extractor`
+    pub fn with_extractor(extractor: Extractor) -> Self {
+        Self {
+            extractor,
+            ..Self::default()
+        }
+    }
+
+    /// Add or re-extract a file
+    ///
+    /// Contents identical to the last update are not extracted again.
+    pub fn update_file(&mut self, path: impl Into<PathBuf>, contents: &str) -> ClassChanges {
+        let path = normalize(&path.into());
+        let hash = content_hash(contents);
+        if self.files.get(&path).is_some_and(|entry| entry.hash == hash) {
+            return ClassChanges::default();
+        }
+
+        let classes = self.extractor.extract_set(contents);
+        self.apply(path, hash, classes)
+    }
+
+    /// Forget a file, releasing its classes
+    pub fn remove_file(&mut self, path: &Path) -> ClassChanges {
+        let Some(entry) = self.files.remove(&normalize(path)) else {
+            return ClassChanges::default();
+        };
+
+        let mut changes = ClassChanges::default();
+        for class in entry.classes {
+            self.release(class, &mut changes);
+        }
+        changes.sort()
+    }
+
+    /// Forget every file under `dir`
+    pub fn remove_dir(&mut self, dir: &Path) -> ClassChanges {
+        let dir = normalize(dir);
+        let files: Vec<PathBuf> = self.files.keys().filter(|path| path.starts_with(&dir)).cloned().collect();
+
+        let mut changes = ClassChanges::default();
+        for path in files {
+            if let Some(entry) = self.files.remove(&path) {
+                for class in entry.classes {
+                    self.release(class, &mut changes);
+                }
+            }
+        }
+        changes.sort()
+    }
+
+    /// Bring the session in line with the files `config` selects on disk:
+    /// changed files are re-extracted (in parallel) and vanished ones removed
+    ///
+    /// This runs on the current rayon pool; `TurboEngine::scan_session` runs
+    /// it on the engine's.
+    #[cfg(feature = "fs")]
+    pub fn scan(&mut self, config: &ContentConfig) -> Result<ClassChanges, GlobError> {
+        let paths = content_files(config)?;
+
+        let extracted: Vec<(PathBuf, u64, Option<FxHashSet<String>>)> = paths
+            .into_par_iter()
+            .filter_map(|path| {
+                let contents = read_text(&path, config.max_file_size)?;
+                let path = normalize(&path);
+                let hash = content_hash(&contents);
+                let unchanged = self.files.get(&path).is_some_and(|entry| entry.hash == hash);
+                let classes = (!unchanged).then(|| self.extractor.extract_set(&contents));
+                Some((path, hash, classes))
+            })
+            .collect();
+
+        let mut changes = ChangeSet::default();
+        let seen: FxHashSet<&Path> = extracted.iter().map(|(path, ..)| path.as_path()).collect();
+        let vanished: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| !seen.contains(path.as_path()))
+            .cloned()
+            .collect();
+        for path in vanished {
+            let removed = self.remove_file(&path);
+            changes.merge(removed);
+        }
+
+        for (path, hash, classes) in extracted {
+            if let Some(classes) = classes {
+                let updated = self.apply(path, hash, classes);
+                changes.merge(updated);
+            }
+        }
+        Ok(changes.into_changes())
+    }
+
+    /// Whether the session tracks `path`
+    pub fn contains_file(&self, path: &Path) -> bool {
+        self.files.contains_key(&normalize(path))
+    }
+
+    /// Whether the session tracks any file under `dir`
+    pub fn contains_dir(&self, dir: &Path) -> bool {
+        let dir = normalize(dir);
+        self.files.keys().any(|path| path.starts_with(&dir))
+    }
+
+    /// Whether any tracked file uses `class`
+    pub fn contains_class(&self, class: &str) -> bool {
+        self.counts.contains_key(class)
+    }
+
+    /// Number of tracked files
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// All classes used by tracked files, sorted
+    pub fn classes(&self) -> Vec<String> {
+        let mut classes: Vec<String> = self.counts.keys().cloned().collect();
+        classes.sort_unstable();
+        classes
+    }
+
+    /// Classes used by one file, sorted
+    pub fn file_classes(&self, path: &Path) -> Option<Vec<String>> {
+        let entry = self.files.get(&normalize(path))?;
+        let mut classes: Vec<String> = entry.classes.iter().cloned().collect();
+        classes.sort_unstable();
+        Some(classes)
+    }
+
+    /// Forget every file
+    pub fn clear(&mut self) {
+        self.files.clear();
+        self.counts.clear();
+    }
+
+    /// Replace the classes of `path`, adjusting reference counts
+    fn apply(&mut self, path: PathBuf, hash: u64, classes: FxHashSet<String>) -> ClassChanges {
+        let mut changes = ClassChanges::default();
+        let old = self.files.remove(&path).map(|entry| entry.classes).unwrap_or_default();
+
+        for class in classes.difference(&old) {
+            let count = self.counts.entry(class.clone()).or_insert(0);
+            *count += 1;
+            if *count == 1 {
+                changes.added.push(class.clone());
+            }
+        }
+        for class in old.into_iter().filter(|class| !classes.contains(class)) {
+            self.release(class, &mut changes);
+        }
+
+        self.files.insert(path, FileEntry { hash, classes });
+        changes.sort()
+    }
+
+    /// Drop one reference to `class`
+    fn release(&mut self, class: String, changes: &mut ClassChanges) {
+        if let Some(count) = self.counts.get_mut(&class) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(&class);
+                changes.removed.push(class);
+            }
+        }
+    }
+}
+
+/// Changes of several updates folded together
+#[cfg(feature = "fs")]
+#[derive(Debug, Default)]
+pub(crate) struct ChangeSet {
+    added: FxHashSet<String>,
+    removed: FxHashSet<String>,
+}
+
+#[cfg(feature = "fs")]
+impl ChangeSet {
+    /// Fold in the changes of a later update; a class removed by one and
+    /// added back by the other cancels out
+    pub(crate) fn merge(&mut self, later: ClassChanges) {
+        for class in later.added {
+            if !self.removed.remove(&class) {
+                self.added.insert(class);
+            }
+        }
+        for class in later.removed {
+            if !self.added.remove(&class) {
+                self.removed.insert(class);
+            }
+        }
+    }
+
+    /// The folded changes, sorted
+    pub(crate) fn into_changes(self) -> ClassChanges {
+        ClassChanges {
+            added: self.added.into_iter().collect(),
+            removed: self.removed.into_iter().collect(),
+        }
+        .sort()
+    }
+}
+
+/// Absolute form of `path` without `.` and `..` components
+///
+/// Symlinks are left alone, so paths of deleted files normalize too.
+fn normalize(path: &Path) -> PathBuf {
+    let absolute = path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Fingerprint of a file's contents
+fn content_hash(contents: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(classes: &[&str]) -> Vec<String> {
+        classes.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_update_and_remove() {
+        let mut session = ExtractionSession::new();
+
+        let changes = session.update_file("a.html", r#"class="p-4 flex""#);
+        assert_eq!(changes.added, strings(&["flex", "p-4"]));
+        assert!(changes.removed.is_empty());
+
+        let changes = session.update_file("a.html", r#"class="p-4 m-2""#);
+        assert_eq!(changes.added, strings(&["m-2"]));
+        assert_eq!(changes.removed, strings(&["flex"]));
+
+        let changes = session.remove_file(Path::new("a.html"));
+        assert_eq!(changes.removed, strings(&["m-2", "p-4"]));
+        assert_eq!(session.file_count(), 0);
+        assert!(session.classes().is_empty());
+    }
+
+    #[test]
+    fn test_reference_counts_across_files() {
+        let mut session = ExtractionSession::new();
+        session.update_file("a.html", r#"class="p-4 flex""#);
+
+        // Already used by a.html
+        let changes = session.update_file("b.html", r#"class="p-4""#);
+        assert!(changes.is_empty());
+
+        // Still used by b.html
+        let changes = session.remove_file(Path::new("a.html"));
+        assert_eq!(changes.removed, strings(&["flex"]));
+        assert!(session.contains_class("p-4"));
+
+        let changes = session.update_file("b.html", "");
+        assert_eq!(changes.removed, strings(&["p-4"]));
+        assert_eq!(session.file_classes(Path::new("b.html")), Some(Vec::new()));
+    }
+
+    #[test]
+    fn test_remove_dir() {
+        let mut session = ExtractionSession::new();
+        session.update_file("src/a.html", r#"class="p-4 flex""#);
+        session.update_file("src/nested/b.html", r#"class="m-2""#);
+        session.update_file("index.html", r#"class="flex""#);
+
+        assert!(session.contains_dir(Path::new("src/nested")));
+        let changes = session.remove_dir(Path::new("src"));
+        assert_eq!(changes.removed, strings(&["m-2", "p-4"]));
+        assert_eq!(session.file_count(), 1);
+        assert!(!session.contains_dir(Path::new("src")));
+        assert!(session.remove_dir(Path::new("src")).is_empty());
+    }
+
+    #[test]
+    fn test_relative_and_absolute_paths_are_one_file() {
+        let mut session = ExtractionSession::new();
+        let absolute = std::env::current_dir().unwrap().join("src/a.html");
+        session.update_file("src/a.html", r#"class="p-4""#);
+
+        assert!(session.update_file(absolute.clone(), r#"class="p-4""#).is_empty());
+        assert_eq!(session.file_count(), 1);
+        assert!(session.contains_file(&absolute));
+        assert!(session.contains_dir(Path::new("./src")));
+
+        let changes = session.remove_file(Path::new("src/../src/./a.html"));
+        assert_eq!(changes.removed, strings(&["p-4"]));
+        assert_eq!(session.file_count(), 0);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_change_set_cancels_out() {
+        let mut set = ChangeSet::default();
+        set.merge(ClassChanges {
+            added: strings(&["p-4", "flex"]),
+            removed: strings(&["m-2"]),
+        });
+        set.merge(ClassChanges {
+            added: strings(&["m-2", "gap-2"]),
+            removed: strings(&["flex"]),
+        });
+
+        let changes = set.into_changes();
+        assert_eq!(changes.added, strings(&["gap-2", "p-4"]));
+        assert!(changes.removed.is_empty());
+    }
+
+    #[test]
+    fn test_unchanged_contents_are_skipped() {
+        let mut session = ExtractionSession::new();
+        let contents = r#"class="p-4""#;
+        session.update_file("a.html", contents);
+
+        assert!(session.update_file("a.html", contents).is_empty());
+        assert!(session.remove_file(Path::new("missing.html")).is_empty());
+        assert_eq!(session.classes(), strings(&["p-4"]));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_scan_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ContentConfig {
+            root: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        std::fs::write(dir.path().join("a.html"), r#"class="p-4 flex""#).unwrap();
+        std::fs::write(dir.path().join("b.html"), r#"class="m-2""#).unwrap();
+
+        let mut session = ExtractionSession::new();
+        let changes = session.scan(&config).unwrap();
+        assert_eq!(changes.added, strings(&["flex", "m-2", "p-4"]));
+        assert_eq!(session.file_count(), 2);
+
+        // `flex` moves from a.html to b.html; m-2 leaves with b.html's rewrite
+        std::fs::write(dir.path().join("a.html"), r#"class="p-4""#).unwrap();
+        std::fs::write(dir.path().join("b.html"), r#"class="flex""#).unwrap();
+        std::fs::write(dir.path().join("c.html"), r#"class="gap-2""#).unwrap();
+        let changes = session.scan(&config).unwrap();
+        assert_eq!(changes.added, strings(&["gap-2"]));
+        assert_eq!(changes.removed, strings(&["m-2"]));
+
+        std::fs::remove_file(dir.path().join("c.html")).unwrap();
+        let changes = session.scan(&config).unwrap();
+        assert_eq!(changes.removed, strings(&["gap-2"]));
+        assert!(!session.contains_file(&dir.path().join("c.html")));
+    }
+}
@@ -0,0 +1,240 @@
+//! Source maps from generated CSS back to class usages
+//!
+//! The extractor records where each class occurs, `SourceIndex` collects
+//! those locations, and the generator maps every emitted rule to them in a
+//! Source Map v3 document.
+
+use crate::types::{ClassOccurrence, SourceLocation};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+/// Base64 digits used by VLQ encoding
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Locations of class occurrences, keyed by class name
+#[derive(Debug, Clone, Default)]
+pub struct SourceIndex {
+    classes: FxHashMap<String, Vec<SourceLocation>>,
+}
+
+impl SourceIndex {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one occurrence of `class`
+    pub fn insert(&mut self, class: impl Into<String>, location: SourceLocation) {
+        self.classes.entry(class.into()).or_default().push(location);
+    }
+
+    /// Locations of `class` in the order they were recorded
+    pub fn locations(&self, class: &str) -> &[SourceLocation] {
+        self.classes.get(class).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Distinct class names, in no particular order
+    pub fn classes(&self) -> impl Iterator<Item = &str> {
+        self.classes.keys().map(String::as_str)
+    }
+
+    /// Number of distinct classes
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Whether no class has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+}
+
+impl Extend<ClassOccurrence> for SourceIndex {
+    fn extend<I: IntoIterator<Item = ClassOccurrence>>(&mut self, occurrences: I) {
+        for occurrence in occurrences {
+            self.insert(occurrence.class, occurrence.location);
+        }
+    }
+}
+
+impl FromIterator<ClassOccurrence> for SourceIndex {
+    fn from_iter<I: IntoIterator<Item = ClassOccurrence>>(occurrences: I) -> Self {
+        let mut index = Self::new();
+        index.extend(occurrences);
+        index
+    }
+}
+
+/// A Source Map v3 document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceMap {
+    pub version: u8,
+
+    /// Name of the generated file, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+
+    pub sources: Vec<String>,
+    pub names: Vec<String>,
+
+    /// Base64 VLQ encoded segments, one group per generated line
+    pub mappings: String,
+}
+
+impl SourceMap {
+    /// Serialize to the JSON form read by browsers and bundlers
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Collects mappings in generated order and encodes them on the fly
+#[derive(Debug, Default)]
+pub(crate) struct SourceMapBuilder {
+    sources: Vec<String>,
+    source_ids: FxHashMap<String, i64>,
+    mappings: String,
+
+    /// Generated line the last segment was written on
+    line: u32,
+    line_has_segment: bool,
+
+    /// Previous segment fields, which each segment is encoded relative to
+    prev_column: i64,
+    prev_source: i64,
+    prev_source_line: i64,
+    prev_source_column: i64,
+}
+
+impl SourceMapBuilder {
+    /// Map a generated position (0-based, in UTF-16 units) to `location`
+    ///
+    /// Positions must be added in generated order.
+    pub(crate) fn add(&mut self, line: u32, column: u32, location: &SourceLocation) {
+        while self.line < line {
+            self.mappings.push(';');
+            self.line += 1;
+            self.line_has_segment = false;
+            self.prev_column = 0;
+        }
+        if self.line_has_segment {
+            self.mappings.push(',');
+        }
+        self.line_has_segment = true;
+
+        let next_id = self.sources.len() as i64;
+        let source = *self.source_ids.entry(location.file.clone()).or_insert_with(|| {
+            self.sources.push(location.file.clone());
+            next_id
+        });
+
+        // Source maps count lines and columns from zero
+        let column = i64::from(column);
+        let source_line = i64::from(location.line.saturating_sub(1));
+        let source_column = i64::from(location.column.saturating_sub(1));
+
+        encode_vlq(&mut self.mappings, column - self.prev_column);
+        encode_vlq(&mut self.mappings, source - self.prev_source);
+        encode_vlq(&mut self.mappings, source_line - self.prev_source_line);
+        encode_vlq(&mut self.mappings, source_column - self.prev_source_column);
+
+        self.prev_column = column;
+        self.prev_source = source;
+        self.prev_source_line = source_line;
+        self.prev_source_column = source_column;
+    }
+
+    /// Finish the document
+    pub(crate) fn build(self) -> SourceMap {
+        SourceMap {
+            version: 3,
+            file: None,
+            sources: self.sources,
+            names: Vec::new(),
+            mappings: self.mappings,
+        }
+    }
+}
+
+/// Append `value` as a Base64 VLQ
+fn encode_vlq(output: &mut String, value: i64) {
+    // The sign goes in the lowest bit
+    let mut vlq = if value < 0 { ((-value) << 1) | 1 } else { value << 1 } as u64;
+
+    loop {
+        let mut digit = (vlq & 0b11111) as usize;
+        vlq >>= 5;
+        if vlq > 0 {
+            digit |= 0b100000;
+        }
+        output.push(BASE64[digit] as char);
+        if vlq == 0 {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(file: &str, line: u32, column: u32) -> SourceLocation {
+        SourceLocation {
+            file: file.to_string(),
+            line,
+            column,
+        }
+    }
+
+    #[test]
+    fn test_encode_vlq() {
+        let encode = |value| {
+            let mut output = String::new();
+            encode_vlq(&mut output, value);
+            output
+        };
+
+        assert_eq!(encode(0), "A");
+        assert_eq!(encode(1), "C");
+        assert_eq!(encode(-1), "D");
+        assert_eq!(encode(15), "e");
+        assert_eq!(encode(16), "gB");
+        assert_eq!(encode(-1000), "x+B");
+    }
+
+    #[test]
+    fn test_builder_mappings() {
+        let mut builder = SourceMapBuilder::default();
+        builder.add(0, 0, &location("src/App.tsx", 1, 1));
+        builder.add(2, 4, &location("src/App.tsx", 3, 5));
+        builder.add(2, 10, &location("src/Nav.tsx", 1, 1));
+
+        let map = builder.build();
+        assert_eq!(map.sources, vec!["src/App.tsx", "src/Nav.tsx"]);
+        assert_eq!(map.mappings, "AAAA;;IAEI,MCFJ");
+        assert_eq!(
+            map.to_json(),
+            r#"{"version":3,"sources":["src/App.tsx","src/Nav.tsx"],"names":[],"mappings":"AAAA;;IAEI,MCFJ"}"#
+        );
+    }
+
+    #[test]
+    fn test_source_index() {
+        let index: SourceIndex = vec![
+            ClassOccurrence {
+                class: "p-4".to_string(),
+                location: location("a.html", 1, 13),
+            },
+            ClassOccurrence {
+                class: "p-4".to_string(),
+                location: location("b.html", 2, 1),
+            },
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.locations("p-4")[1].file, "b.html");
+        assert!(index.locations("m-2").is_empty());
+    }
+}
@@ -0,0 +1,299 @@
+//! Incrementally maintained stylesheet
+//!
+//! A `LiveStylesheet` keeps one rule per matched class in the order the
+//! generator would emit them. Applying the class changes of an extraction
+//! session inserts and deletes only the affected rules, and reports where,
+//! so a dev server can patch a page's stylesheet instead of replacing it.
+//!
+//! Each rule is generated on its own. With `merge_rules`, `css()` groups
+//! selectors that share declarations while the rules and patches keep one
+//! rule per class; both cascade the same, but their text differs.
+
+use crate::session::ClassChanges;
+use crate::types::MatchResult;
+use crate::TurboEngine;
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::slice;
+use std::sync::Arc;
+
+/// A rule inserted into or removed from a live stylesheet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatchedRule {
+    /// Position in the rule list (see `StylesheetPatch`)
+    pub index: usize,
+
+    /// Class the rule was generated from
+    pub class: String,
+
+    /// The rule on its own, wrapped in its layer and at-rules (never merged
+    /// with other classes' rules)
+    pub css: String,
+}
+
+/// Rule-level difference between two states of a live stylesheet
+///
+/// Removals come first, from the highest index down, each index counted
+/// before the removal; insertions follow in ascending order, each index
+/// counted after the insertion. Replaying them in that order (e.g., with
+/// `CSSStyleSheet.deleteRule` and `insertRule`) turns the old rule list
+/// into the new one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StylesheetPatch {
+    pub removed: Vec<PatchedRule>,
+    pub inserted: Vec<PatchedRule>,
+}
+
+impl StylesheetPatch {
+    /// Whether the stylesheet stayed the same
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.inserted.is_empty()
+    }
+}
+
+/// One rule of the stylesheet
+#[derive(Debug, Clone)]
+struct LiveRule {
+    class: String,
+    css: String,
+}
+
+/// Stylesheet kept in generated order as classes come and go
+///
+/// Holds the engine it resolves and generates classes with, so its theme and
+/// configuration cannot change under it.
+#[derive(Debug, Clone)]
+pub struct LiveStylesheet {
+    engine: Arc<TurboEngine>,
+
+    /// Match results in generated order, parallel to `rules`
+    results: Vec<MatchResult>,
+    rules: Vec<LiveRule>,
+
+    /// Classes applied so far, including those that matched nothing
+    classes: FxHashSet<String>,
+}
+
+impl LiveStylesheet {
+    /// Create an empty stylesheet resolving and generating with `engine`
+    pub fn new(engine: Arc<TurboEngine>) -> Self {
+        Self {
+            engine,
+            results: Vec::new(),
+            rules: Vec::new(),
+            classes: FxHashSet::default(),
+        }
+    }
+
+    /// Insert rules for added classes and delete those of removed ones
+    pub fn apply(&mut self, changes: &ClassChanges) -> StylesheetPatch {
+        let mut patch = StylesheetPatch::default();
+
+        let removed: FxHashSet<&str> = changes
+            .removed
+            .iter()
+            .filter(|class| self.classes.remove(class.as_str()))
+            .map(String::as_str)
+            .collect();
+        if !removed.is_empty() {
+            for index in (0..self.rules.len()).rev() {
+                if removed.contains(self.rules[index].class.as_str()) {
+                    self.results.remove(index);
+                    let LiveRule { class, css } = self.rules.remove(index);
+                    patch.removed.push(PatchedRule { index, class, css });
+                }
+            }
+        }
+
+        let mut added: FxHashSet<&str> = FxHashSet::default();
+        for class in &changes.added {
+            if !self.classes.insert(class.clone()) {
+                continue;
+            }
+            for parsed in self.engine.parse(class) {
+                if let Some(result) = self.engine.match_class(&parsed) {
+                    self.insert(class, result);
+                    added.insert(class);
+                }
+            }
+        }
+        if !added.is_empty() {
+            for (index, rule) in self.rules.iter().enumerate() {
+                if added.contains(rule.class.as_str()) {
+                    patch.inserted.push(PatchedRule {
+                        index,
+                        class: rule.class.clone(),
+                        css: rule.css.clone(),
+                    });
+                }
+            }
+        }
+
+        patch
+    }
+
+    /// The whole stylesheet, as the generator writes it from scratch
+    pub fn css(&self) -> String {
+        self.engine.generator.generate(&self.results)
+    }
+
+    /// Rules in generated order, as `(class, css)` pairs, one per class even
+    /// when `css()` merges them
+    pub fn rules(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.rules.iter().map(|rule| (rule.class.as_str(), rule.css.as_str()))
+    }
+
+    /// Number of rules
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Whether the stylesheet has no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Remove every rule
+    pub fn clear(&mut self) {
+        self.results.clear();
+        self.rules.clear();
+        self.classes.clear();
+    }
+
+    /// Insert `result` at its sorted position
+    fn insert(&mut self, class: &str, result: MatchResult) {
+        let generator = &self.engine.generator;
+        let index = self
+            .results
+            .partition_point(|other| generator.cascade_order(other, &result) == Ordering::Less);
+        let css = generator.generate(slice::from_ref(&result));
+
+        self.results.insert(index, result);
+        self.rules.insert(
+            index,
+            LiveRule {
+                class: class.to_string(),
+                css,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::EngineConfig;
+
+    fn changes(added: &[&str], removed: &[&str]) -> ClassChanges {
+        ClassChanges {
+            added: added.iter().map(|c| c.to_string()).collect(),
+            removed: removed.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    fn classes(sheet: &LiveStylesheet) -> Vec<&str> {
+        sheet.rules().map(|(class, _)| class).collect()
+    }
+
+    #[test]
+    fn test_inserts_in_generated_order() {
+        let engine = Arc::new(TurboEngine::new());
+        let mut sheet = engine.live_stylesheet();
+
+        let patch = sheet.apply(&changes(&["md:p-2", "p-4", "hover:p-4"], &[]));
+        assert_eq!(classes(&sheet), vec!["p-4", "hover:p-4", "md:p-2"]);
+        let indices: Vec<usize> = patch.inserted.iter().map(|rule| rule.index).collect();
+        assert_eq!(indices, vec![0, 1, 2]);
+        assert!(patch.inserted[2].css.contains("@media"));
+
+        // A class sorting between existing rules lands between them
+        let patch = sheet.apply(&changes(&["p-2"], &[]));
+        assert_eq!(patch.inserted.len(), 1);
+        assert_eq!(patch.inserted[0].index, 0);
+        assert_eq!(classes(&sheet), vec!["p-2", "p-4", "hover:p-4", "md:p-2"]);
+    }
+
+    #[test]
+    fn test_removes_with_indices_before_removal() {
+        let engine = Arc::new(TurboEngine::new());
+        let mut sheet = engine.live_stylesheet();
+        sheet.apply(&changes(&["flex", "p-4", "m-2", "hover:p-4"], &[]));
+        let before = classes(&sheet).iter().map(|c| c.to_string()).collect::<Vec<_>>();
+
+        let patch = sheet.apply(&changes(&[], &["flex", "hover:p-4"]));
+        let removed: Vec<(usize, &str)> = patch.removed.iter().map(|r| (r.index, r.class.as_str())).collect();
+        let hover = before.iter().position(|c| c == "hover:p-4").unwrap();
+        let flex = before.iter().position(|c| c == "flex").unwrap();
+        assert_eq!(removed, vec![(hover, "hover:p-4"), (flex, "flex")]);
+        assert_eq!(sheet.len(), 2);
+
+        // Unknown and already removed classes are ignored
+        assert!(sheet.apply(&changes(&[], &["flex", "not-a-class"])).is_empty());
+    }
+
+    #[test]
+    fn test_replaying_patches_matches_rules() {
+        let engine = Arc::new(TurboEngine::new());
+        let mut sheet = engine.live_stylesheet();
+        let mut replayed: Vec<String> = Vec::new();
+
+        let steps = [
+            changes(&["p-4", "flex", "md:flex"], &[]),
+            changes(&["m-2", "hover:bg-red-500", "unknown-thing"], &["flex"]),
+            changes(&["flex", "lg:m-2"], &["p-4", "md:flex"]),
+        ];
+        for step in &steps {
+            let patch = sheet.apply(step);
+            for rule in &patch.removed {
+                assert_eq!(replayed.remove(rule.index), rule.css);
+            }
+            for rule in &patch.inserted {
+                replayed.insert(rule.index, rule.css.clone());
+            }
+
+            let expected: Vec<&str> = sheet.rules().map(|(_, css)| css).collect();
+            assert_eq!(replayed, expected);
+        }
+    }
+
+    #[test]
+    fn test_css_matches_full_rebuild() {
+        for config in [
+            EngineConfig::default(),
+            EngineConfig {
+                merge_rules: true,
+                ..Default::default()
+            },
+        ] {
+            let engine = Arc::new(TurboEngine::with_config(config));
+            let mut sheet = engine.live_stylesheet();
+            sheet.apply(&changes(&["p-4", "md:m-2", "hover:p-4", "m-2"], &[]));
+            sheet.apply(&changes(&["flex"], &["m-2"]));
+
+            assert_eq!(sheet.css(), engine.process("flex p-4 hover:p-4 md:m-2"));
+        }
+
+        let engine = Arc::new(TurboEngine::new());
+        let mut sheet = engine.live_stylesheet();
+        sheet.apply(&changes(&["p-4"], &[]));
+        sheet.clear();
+        assert!(sheet.is_empty());
+        assert_eq!(sheet.css(), "");
+    }
+
+    #[test]
+    fn test_patches_stay_unmerged() {
+        let engine = Arc::new(TurboEngine::with_config(EngineConfig {
+            merge_rules: true,
+            ..Default::default()
+        }));
+        let mut sheet = engine.live_stylesheet();
+        let patch = sheet.apply(&changes(&["p-4", "hover:p-4"], &[]));
+
+        // The patches carry a rule per class while css() shares one
+        assert!(patch.inserted[0].css.contains("\n.p-4 {\n  padding: 1rem;"));
+        assert!(patch.inserted[1].css.contains("\n.hover\\:p-4:hover {\n  padding: 1rem;"));
+        assert!(sheet.css().contains(".p-4,\n.hover\\:p-4:hover {"));
+    }
+}
@@ -0,0 +1,553 @@
+//! Theme configuration
+//!
+//! A `Theme` holds the design tokens utilities and variants resolve against:
+//! spacing, colors, screens, container sizes, font sizes, radii, shadows,
+//! z-index, durations and the named states behind `aria-*` and `data-*`
+//! variants.
+//! Each engine owns its own theme, so several design systems can live in one
+//! process. Themes can be built in Rust or loaded from JSON/TOML; sections left
+//! out of a config file keep their default (Tailwind) values.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Design tokens used by the matcher and generator
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Spacing scale for padding, margin, gap, inset and sizing (e.g., "4" => "1rem")
+    pub spacing: BTreeMap<String, String>,
+
+    /// Colors, either a single value or a map of shades
+    pub colors: BTreeMap<String, ColorValue>,
+
+    /// Responsive breakpoints as min-widths (e.g., "md" => "768px")
+    pub screens: BTreeMap<String, String>,
+
+    /// Container query sizes for `@md:`-style variants (e.g., "md" => "28rem")
+    pub containers: BTreeMap<String, String>,
+
+    /// Font sizes with optional line heights
+    #[serde(alias = "fontSize")]
+    pub font_size: BTreeMap<String, FontSize>,
+
+    /// Border radius scale; "DEFAULT" is used by bare `rounded`
+    #[serde(alias = "borderRadius")]
+    pub border_radius: BTreeMap<String, String>,
+
+    /// Box shadow scale; "DEFAULT" is used by bare `shadow`
+    #[serde(alias = "boxShadow")]
+    pub box_shadow: BTreeMap<String, String>,
+
+    /// Z-index scale
+    #[serde(alias = "zIndex")]
+    pub z_index: BTreeMap<String, String>,
+
+    /// Transition duration scale
+    #[serde(alias = "transitionDuration")]
+    pub transition_duration: BTreeMap<String, String>,
+
+    /// Named `aria-*` variants as attribute conditions
+    /// (e.g., "checked" => `checked="true"` gives `[aria-checked="true"]`)
+    pub aria: BTreeMap<String, String>,
+
+    /// Named `data-*` variants as attribute conditions
+    /// (e.g., "open" => `state="open"` gives `[data-state="open"]`).
+    /// Names not listed here test for the bare attribute (`[data-active]`).
+    pub data: BTreeMap<String, String>,
+}
+
+/// A theme color: one value, or shades keyed by name (e.g., "500")
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ColorValue {
+    /// Single color (e.g., "#000000")
+    Value(String),
+
+    /// Shades of one hue; "DEFAULT" is used by the bare hue name
+    Shades(BTreeMap<String, String>),
+}
+
+/// Font size with an optional line height
+///
+/// Deserializes from `"1rem"`, `["1rem", "1.5rem"]` or
+/// `{ "size": "1rem", "line_height": "1.5rem" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "FontSizeValue")]
+pub struct FontSize {
+    /// Value for `font-size`
+    pub size: String,
+
+    /// Value for `line-height`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_height: Option<String>,
+}
+
+impl FontSize {
+    /// Create a font size with a line height
+    pub fn new(size: impl Into<String>, line_height: impl Into<String>) -> Self {
+        Self {
+            size: size.into(),
+            line_height: Some(line_height.into()),
+        }
+    }
+}
+
+/// Accepted config forms for `FontSize`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FontSizeValue {
+    Size(String),
+    Pair(String, String),
+    Full {
+        size: String,
+        #[serde(default, alias = "lineHeight")]
+        line_height: Option<String>,
+    },
+}
+
+impl From<FontSizeValue> for FontSize {
+    fn from(value: FontSizeValue) -> Self {
+        match value {
+            FontSizeValue::Size(size) => Self {
+                size,
+                line_height: None,
+            },
+            FontSizeValue::Pair(size, line_height) => Self::new(size, line_height),
+            FontSizeValue::Full { size, line_height } => Self { size, line_height },
+        }
+    }
+}
+
+impl Theme {
+    /// Parse a theme from JSON
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Parse a theme from TOML
+    pub fn from_toml(toml: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(toml)
+    }
+
+    /// Set a spacing value
+    pub fn with_spacing(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.spacing.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set a single-value color
+    pub fn with_color(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.colors.insert(name.into(), ColorValue::Value(value.into()));
+        self
+    }
+
+    /// Set a breakpoint
+    pub fn with_screen(mut self, name: impl Into<String>, min_width: impl Into<String>) -> Self {
+        self.screens.insert(name.into(), min_width.into());
+        self
+    }
+
+    /// Set a named `aria-*` state
+    pub fn with_aria(mut self, name: impl Into<String>, condition: impl Into<String>) -> Self {
+        self.aria.insert(name.into(), condition.into());
+        self
+    }
+
+    /// Set a named `data-*` state
+    pub fn with_data(mut self, name: impl Into<String>, condition: impl Into<String>) -> Self {
+        self.data.insert(name.into(), condition.into());
+        self
+    }
+
+    /// Set a container query size
+    pub fn with_container(mut self, name: impl Into<String>, min_width: impl Into<String>) -> Self {
+        self.containers.insert(name.into(), min_width.into());
+        self
+    }
+
+    /// Set a font size
+    pub fn with_font_size(mut self, name: impl Into<String>, size: FontSize) -> Self {
+        self.font_size.insert(name.into(), size);
+        self
+    }
+
+    /// Resolve a color name such as "red-500", "black" or "brand"
+    ///
+    /// Shade maps are looked up by splitting at each `-` from the right, so
+    /// hue names may themselves contain dashes ("light-blue-500").
+    pub fn color(&self, name: &str) -> Option<&str> {
+        match self.colors.get(name) {
+            Some(ColorValue::Value(value)) => return Some(value),
+            Some(ColorValue::Shades(shades)) => return shades.get("DEFAULT").map(String::as_str),
+            None => {}
+        }
+
+        for (idx, _) in name.rmatch_indices('-') {
+            if let Some(ColorValue::Shades(shades)) = self.colors.get(&name[..idx]) {
+                if let Some(value) = shades.get(&name[idx + 1..]) {
+                    return Some(value);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Breakpoints ordered from narrowest to widest
+    pub fn sorted_screens(&self) -> Vec<(&str, &str)> {
+        let mut screens: Vec<(&str, &str)> = self
+            .screens
+            .iter()
+            .map(|(name, width)| (name.as_str(), width.as_str()))
+            .collect();
+
+        // Stable sort keeps name order for widths that cannot be compared
+        screens.sort_by(|a, b| length_px(a.1).total_cmp(&length_px(b.1)));
+        screens
+    }
+}
+
+/// Approximate a CSS length in pixels for ordering (rem/em at 16px)
+///
+/// Lengths that cannot be converted come back as `f64::MAX`.
+pub(crate) fn length_px(value: &str) -> f64 {
+    let value = value.trim();
+    let (number, scale) = if let Some(n) = value.strip_suffix("px") {
+        (n, 1.0)
+    } else if let Some(n) = value.strip_suffix("rem").or_else(|| value.strip_suffix("em")) {
+        (n, 16.0)
+    } else {
+        (value, 1.0)
+    };
+
+    number.trim().parse::<f64>().map_or(f64::MAX, |n| n * scale)
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            spacing: scale(DEFAULT_SPACING),
+            colors: default_colors(),
+            screens: scale(DEFAULT_SCREENS),
+            containers: scale(DEFAULT_CONTAINERS),
+            font_size: DEFAULT_FONT_SIZES
+                .iter()
+                .map(|(name, size, line_height)| (name.to_string(), FontSize::new(*size, *line_height)))
+                .collect(),
+            border_radius: scale(DEFAULT_RADII),
+            box_shadow: scale(DEFAULT_SHADOWS),
+            z_index: scale(DEFAULT_Z_INDEX),
+            transition_duration: scale(DEFAULT_DURATIONS),
+            aria: DEFAULT_ARIA
+                .iter()
+                .map(|name| (name.to_string(), format!("{}=\"true\"", name)))
+                .collect(),
+            data: BTreeMap::new(),
+        }
+    }
+}
+
+fn scale(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+    entries
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+fn default_colors() -> BTreeMap<String, ColorValue> {
+    let mut colors: BTreeMap<String, ColorValue> = PALETTE
+        .iter()
+        .map(|(hue, values)| {
+            let shades = SHADES
+                .iter()
+                .zip(values)
+                .map(|(shade, value)| (shade.to_string(), value.to_string()))
+                .collect();
+            (hue.to_string(), ColorValue::Shades(shades))
+        })
+        .collect();
+
+    // Special values
+    for (name, value) in [
+        ("black", "#000000"),
+        ("white", "#ffffff"),
+        ("transparent", "transparent"),
+        ("current", "currentColor"),
+        ("inherit", "inherit"),
+    ] {
+        colors.insert(name.to_string(), ColorValue::Value(value.to_string()));
+    }
+
+    colors
+}
+
+/// Spacing scale values (Tailwind default)
+const DEFAULT_SPACING: &[(&str, &str)] = &[
+    ("0", "0px"),
+    ("px", "1px"),
+    ("0.5", "0.125rem"),
+    ("1", "0.25rem"),
+    ("1.5", "0.375rem"),
+    ("2", "0.5rem"),
+    ("2.5", "0.625rem"),
+    ("3", "0.75rem"),
+    ("3.5", "0.875rem"),
+    ("4", "1rem"),
+    ("5", "1.25rem"),
+    ("6", "1.5rem"),
+    ("7", "1.75rem"),
+    ("8", "2rem"),
+    ("9", "2.25rem"),
+    ("10", "2.5rem"),
+    ("11", "2.75rem"),
+    ("12", "3rem"),
+    ("14", "3.5rem"),
+    ("16", "4rem"),
+    ("20", "5rem"),
+    ("24", "6rem"),
+    ("28", "7rem"),
+    ("32", "8rem"),
+    ("36", "9rem"),
+    ("40", "10rem"),
+    ("44", "11rem"),
+    ("48", "12rem"),
+    ("52", "13rem"),
+    ("56", "14rem"),
+    ("60", "15rem"),
+    ("64", "16rem"),
+    ("72", "18rem"),
+    ("80", "20rem"),
+    ("96", "24rem"),
+    ("auto", "auto"),
+    ("full", "100%"),
+    ("screen", "100vw"),
+    ("svw", "100svw"),
+    ("lvw", "100lvw"),
+    ("dvw", "100dvw"),
+    ("min", "min-content"),
+    ("max", "max-content"),
+    ("fit", "fit-content"),
+];
+
+/// Breakpoints (Tailwind default)
+const DEFAULT_SCREENS: &[(&str, &str)] = &[
+    ("sm", "640px"),
+    ("md", "768px"),
+    ("lg", "1024px"),
+    ("xl", "1280px"),
+    ("2xl", "1536px"),
+];
+
+/// Container query sizes
+const DEFAULT_CONTAINERS: &[(&str, &str)] = &[
+    ("sm", "20rem"),
+    ("md", "28rem"),
+    ("lg", "32rem"),
+    ("xl", "36rem"),
+    ("2xl", "42rem"),
+    ("3xl", "48rem"),
+    ("4xl", "56rem"),
+    ("5xl", "64rem"),
+    ("6xl", "72rem"),
+    ("7xl", "80rem"),
+];
+
+/// Font sizes with line heights (Tailwind default)
+const DEFAULT_FONT_SIZES: &[(&str, &str, &str)] = &[
+    ("xs", "0.75rem", "1rem"),
+    ("sm", "0.875rem", "1.25rem"),
+    ("base", "1rem", "1.5rem"),
+    ("lg", "1.125rem", "1.75rem"),
+    ("xl", "1.25rem", "1.75rem"),
+    ("2xl", "1.5rem", "2rem"),
+    ("3xl", "1.875rem", "2.25rem"),
+    ("4xl", "2.25rem", "2.5rem"),
+    ("5xl", "3rem", "1"),
+    ("6xl", "3.75rem", "1"),
+    ("7xl", "4.5rem", "1"),
+    ("8xl", "6rem", "1"),
+    ("9xl", "8rem", "1"),
+];
+
+/// Border radius scale (Tailwind default)
+const DEFAULT_RADII: &[(&str, &str)] = &[
+    ("none", "0px"),
+    ("sm", "0.125rem"),
+    ("DEFAULT", "0.25rem"),
+    ("md", "0.375rem"),
+    ("lg", "0.5rem"),
+    ("xl", "0.75rem"),
+    ("2xl", "1rem"),
+    ("3xl", "1.5rem"),
+    ("full", "9999px"),
+];
+
+/// Box shadow scale (Tailwind default)
+const DEFAULT_SHADOWS: &[(&str, &str)] = &[
+    ("sm", "0 1px 2px 0 rgb(0 0 0 / 0.05)"),
+    ("DEFAULT", "0 1px 3px 0 rgb(0 0 0 / 0.1), 0 1px 2px -1px rgb(0 0 0 / 0.1)"),
+    ("md", "0 4px 6px -1px rgb(0 0 0 / 0.1), 0 2px 4px -2px rgb(0 0 0 / 0.1)"),
+    ("lg", "0 10px 15px -3px rgb(0 0 0 / 0.1), 0 4px 6px -4px rgb(0 0 0 / 0.1)"),
+    ("xl", "0 20px 25px -5px rgb(0 0 0 / 0.1), 0 8px 10px -6px rgb(0 0 0 / 0.1)"),
+    ("2xl", "0 25px 50px -12px rgb(0 0 0 / 0.25)"),
+    ("inner", "inset 0 2px 4px 0 rgb(0 0 0 / 0.05)"),
+    ("none", "none"),
+];
+
+/// Z-index scale (Tailwind default)
+const DEFAULT_Z_INDEX: &[(&str, &str)] = &[
+    ("0", "0"),
+    ("10", "10"),
+    ("20", "20"),
+    ("30", "30"),
+    ("40", "40"),
+    ("50", "50"),
+    ("auto", "auto"),
+];
+
+/// Transition durations (Tailwind default)
+const DEFAULT_DURATIONS: &[(&str, &str)] = &[
+    ("0", "0s"),
+    ("75", "75ms"),
+    ("100", "100ms"),
+    ("150", "150ms"),
+    ("200", "200ms"),
+    ("300", "300ms"),
+    ("500", "500ms"),
+    ("700", "700ms"),
+    ("1000", "1000ms"),
+];
+
+/// Boolean ARIA attributes with a default `aria-*` variant
+const DEFAULT_ARIA: &[&str] = &[
+    "busy", "checked", "disabled", "expanded", "hidden", "pressed", "readonly", "required",
+    "selected",
+];
+
+/// Shade steps shared by every hue in the default palette
+const SHADES: [&str; 11] = [
+    "50", "100", "200", "300", "400", "500", "600", "700", "800", "900", "950",
+];
+
+/// Default palette (Tailwind v3), one entry per hue with a value per shade
+const PALETTE: &[(&str, [&str; 11])] = &[
+    ("slate", ["#f8fafc", "#f1f5f9", "#e2e8f0", "#cbd5e1", "#94a3b8", "#64748b", "#475569", "#334155", "#1e293b", "#0f172a", "#020617"]),
+    ("gray", ["#f9fafb", "#f3f4f6", "#e5e7eb", "#d1d5db", "#9ca3af", "#6b7280", "#4b5563", "#374151", "#1f2937", "#111827", "#030712"]),
+    ("zinc", ["#fafafa", "#f4f4f5", "#e4e4e7", "#d4d4d8", "#a1a1aa", "#71717a", "#52525b", "#3f3f46", "#27272a", "#18181b", "#09090b"]),
+    ("neutral", ["#fafafa", "#f5f5f5", "#e5e5e5", "#d4d4d4", "#a3a3a3", "#737373", "#525252", "#404040", "#262626", "#171717", "#0a0a0a"]),
+    ("stone", ["#fafaf9", "#f5f5f4", "#e7e5e4", "#d6d3d1", "#a8a29e", "#78716c", "#57534e", "#44403c", "#292524", "#1c1917", "#0c0a09"]),
+    ("red", ["#fef2f2", "#fee2e2", "#fecaca", "#fca5a5", "#f87171", "#ef4444", "#dc2626", "#b91c1c", "#991b1b", "#7f1d1d", "#450a0a"]),
+    ("orange", ["#fff7ed", "#ffedd5", "#fed7aa", "#fdba74", "#fb923c", "#f97316", "#ea580c", "#c2410c", "#9a3412", "#7c2d12", "#431407"]),
+    ("amber", ["#fffbeb", "#fef3c7", "#fde68a", "#fcd34d", "#fbbf24", "#f59e0b", "#d97706", "#b45309", "#92400e", "#78350f", "#451a03"]),
+    ("yellow", ["#fefce8", "#fef9c3", "#fef08a", "#fde047", "#facc15", "#eab308", "#ca8a04", "#a16207", "#854d0e", "#713f12", "#422006"]),
+    ("lime", ["#f7fee7", "#ecfccb", "#d9f99d", "#bef264", "#a3e635", "#84cc16", "#65a30d", "#4d7c0f", "#3f6212", "#365314", "#1a2e05"]),
+    ("green", ["#f0fdf4", "#dcfce7", "#bbf7d0", "#86efac", "#4ade80", "#22c55e", "#16a34a", "#15803d", "#166534", "#14532d", "#052e16"]),
+    ("emerald", ["#ecfdf5", "#d1fae5", "#a7f3d0", "#6ee7b7", "#34d399", "#10b981", "#059669", "#047857", "#065f46", "#064e3b", "#022c22"]),
+    ("teal", ["#f0fdfa", "#ccfbf1", "#99f6e4", "#5eead4", "#2dd4bf", "#14b8a6", "#0d9488", "#0f766e", "#115e59", "#134e4a", "#042f2e"]),
+    ("cyan", ["#ecfeff", "#cffafe", "#a5f3fc", "#67e8f9", "#22d3ee", "#06b6d4", "#0891b2", "#0e7490", "#155e75", "#164e63", "#083344"]),
+    ("sky", ["#f0f9ff", "#e0f2fe", "#bae6fd", "#7dd3fc", "#38bdf8", "#0ea5e9", "#0284c7", "#0369a1", "#075985", "#0c4a6e", "#082f49"]),
+    ("blue", ["#eff6ff", "#dbeafe", "#bfdbfe", "#93c5fd", "#60a5fa", "#3b82f6", "#2563eb", "#1d4ed8", "#1e40af", "#1e3a8a", "#172554"]),
+    ("indigo", ["#eef2ff", "#e0e7ff", "#c7d2fe", "#a5b4fc", "#818cf8", "#6366f1", "#4f46e5", "#4338ca", "#3730a3", "#312e81", "#1e1b4b"]),
+    ("violet", ["#f5f3ff", "#ede9fe", "#ddd6fe", "#c4b5fd", "#a78bfa", "#8b5cf6", "#7c3aed", "#6d28d9", "#5b21b6", "#4c1d95", "#2e1065"]),
+    ("purple", ["#faf5ff", "#f3e8ff", "#e9d5ff", "#d8b4fe", "#c084fc", "#a855f7", "#9333ea", "#7e22ce", "#6b21a8", "#581c87", "#3b0764"]),
+    ("fuchsia", ["#fdf4ff", "#fae8ff", "#f5d0fe", "#f0abfc", "#e879f9", "#d946ef", "#c026d3", "#a21caf", "#86198f", "#701a75", "#4a044e"]),
+    ("pink", ["#fdf2f8", "#fce7f3", "#fbcfe8", "#f9a8d4", "#f472b6", "#ec4899", "#db2777", "#be185d", "#9d174d", "#831843", "#500724"]),
+    ("rose", ["#fff1f2", "#ffe4e6", "#fecdd3", "#fda4af", "#fb7185", "#f43f5e", "#e11d48", "#be123c", "#9f1239", "#881337", "#4c0519"]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_palette() {
+        let theme = Theme::default();
+        assert_eq!(theme.color("red-500"), Some("#ef4444"));
+        assert_eq!(theme.color("rose-950"), Some("#4c0519"));
+        assert_eq!(theme.color("white"), Some("#ffffff"));
+        assert_eq!(theme.color("red"), None);
+        assert_eq!(theme.color("red-501"), None);
+        assert_eq!(theme.colors.len(), PALETTE.len() + 5);
+    }
+
+    #[test]
+    fn test_color_shades_with_dashes() {
+        let mut theme = Theme::default();
+        let mut shades = BTreeMap::new();
+        shades.insert("DEFAULT".to_string(), "#0ea5e9".to_string());
+        shades.insert("500".to_string(), "#0284c7".to_string());
+        theme.colors.insert("light-blue".to_string(), ColorValue::Shades(shades));
+
+        assert_eq!(theme.color("light-blue"), Some("#0ea5e9"));
+        assert_eq!(theme.color("light-blue-500"), Some("#0284c7"));
+    }
+
+    #[test]
+    fn test_sorted_screens() {
+        let theme = Theme::default().with_screen("xs", "20rem").with_screen("3xl", "1920px");
+        let names: Vec<&str> = theme.sorted_screens().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["xs", "sm", "md", "lg", "xl", "2xl", "3xl"]);
+    }
+
+    #[test]
+    fn test_from_json() {
+        let theme = Theme::from_json(
+            r##"{
+                "colors": { "brand": { "DEFAULT": "#ff5500", "dark": "#aa3300" }, "ink": "#111111" },
+                "fontSize": { "body": ["15px", "22px"], "tiny": "10px" },
+                "screens": { "tablet": "800px" }
+            }"##,
+        )
+        .unwrap();
+
+        assert_eq!(theme.color("brand-dark"), Some("#aa3300"));
+        assert_eq!(theme.color("ink"), Some("#111111"));
+        assert_eq!(theme.color("red-500"), None);
+        assert_eq!(theme.font_size["body"], FontSize::new("15px", "22px"));
+        assert_eq!(theme.font_size["tiny"].line_height, None);
+        assert_eq!(theme.sorted_screens(), vec![("tablet", "800px")]);
+
+        // Sections left out keep their defaults
+        assert_eq!(theme.spacing["4"], "1rem");
+    }
+
+    #[test]
+    fn test_from_toml() {
+        let theme = Theme::from_toml(
+            r##"
+            [spacing]
+            "4" = "20px"
+
+            [colors.brand]
+            500 = "#336699"
+
+            [font_size]
+            lg = { size = "18px", line_height = "26px" }
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(theme.spacing["4"], "20px");
+        assert_eq!(theme.color("brand-500"), Some("#336699"));
+        assert_eq!(theme.font_size["lg"], FontSize::new("18px", "26px"));
+    }
+
+    #[test]
+    fn test_aria_and_data_states() {
+        let theme = Theme::default();
+        assert_eq!(theme.aria.get("checked").map(String::as_str), Some(r#"checked="true""#));
+        assert!(theme.data.is_empty());
+
+        let theme = Theme::from_json(r#"{ "data": { "open": "state=\"open\"" } }"#).unwrap();
+        assert_eq!(theme.data.get("open").map(String::as_str), Some(r#"state="open""#));
+        assert!(theme.aria.contains_key("expanded"));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let theme = Theme::default().with_color("brand", "#123456");
+        let json = serde_json::to_string(&theme).unwrap();
+        assert_eq!(Theme::from_json(&json).unwrap(), theme);
+    }
+}
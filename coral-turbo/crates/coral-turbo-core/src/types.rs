@@ -1,7 +1,11 @@
 //! Core types for the CoralCSS Turbo engine
 
+use crate::handler::{HandlerRef, UtilityHandler};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 /// Represents a parsed CSS class name with all its components
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -29,6 +33,44 @@ pub struct ParsedClass {
 
     /// Negative prefix (-)
     pub negative: bool,
+
+    /// Source token this class was expanded from, for classes written inside
+    /// a variant group (e.g., "hover:(bg-red-500 text-white)"). `raw` holds
+    /// the expanded class name; the generated selector is built from the
+    /// group's class tokens instead, so it matches the markup as written.
+    pub source: Option<String>,
+
+    /// Problems found while parsing (e.g., an invalid arbitrary variant).
+    /// Classes with diagnostics never match.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<String>,
+
+    /// What kind of class this is. For arbitrary properties, `utility` holds
+    /// the property name and `arbitrary` the undecoded value.
+    #[serde(default)]
+    pub kind: ClassKind,
+}
+
+/// Kind of a parsed class
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClassKind {
+    /// Regular utility matched against registered patterns (e.g., "p-4")
+    #[default]
+    Utility,
+
+    /// Arbitrary property emitted as-is (e.g., "[mask-type:luminance]")
+    ArbitraryProperty,
+}
+
+impl ClassKind {
+    /// Kebab-case name, as used in serialized output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClassKind::Utility => "utility",
+            ClassKind::ArbitraryProperty => "arbitrary-property",
+        }
+    }
 }
 
 impl ParsedClass {
@@ -43,6 +85,9 @@ impl ParsedClass {
             arbitrary: None,
             important: false,
             negative: false,
+            source: None,
+            diagnostics: Vec::new(),
+            kind: ClassKind::Utility,
         }
     }
 
@@ -58,8 +103,18 @@ impl ParsedClass {
         self.arbitrary.is_some()
     }
 
+    /// Check if this class is an arbitrary property (`[prop:value]`)
+    #[inline]
+    pub fn is_arbitrary_property(&self) -> bool {
+        self.kind == ClassKind::ArbitraryProperty
+    }
+
     /// Get the full utility string (utility + value)
     pub fn full_utility(&self) -> String {
+        if self.is_arbitrary_property() {
+            return format!("[{}:{}]", self.utility, self.arbitrary.as_deref().unwrap_or(""));
+        }
+
         // Include arbitrary value with brackets if present
         if let Some(ref arb) = self.arbitrary {
             match &self.value {
@@ -92,10 +147,19 @@ pub struct MatchResult {
 
     /// Sort order for CSS output
     pub sort_order: u32,
+
+    /// Appended to the class selector after variants are applied
+    /// (e.g., "::placeholder")
+    #[serde(default)]
+    pub selector_suffix: Option<String>,
+
+    /// Top-level rules this utility depends on (e.g., `@keyframes`)
+    #[serde(default)]
+    pub extra_rules: Vec<String>,
 }
 
 /// A single CSS property-value pair
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct CSSProperty {
     pub property: String,
     pub value: String,
@@ -111,19 +175,14 @@ impl CSSProperty {
 }
 
 /// CSS layer for proper cascade ordering
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Layer {
     Base = 0,
     Components = 1,
+    #[default]
     Utilities = 2,
 }
 
-impl Default for Layer {
-    fn default() -> Self {
-        Layer::Utilities
-    }
-}
-
 /// Utility pattern definition for the matcher
 #[derive(Debug, Clone)]
 pub struct UtilityPattern {
@@ -142,10 +201,16 @@ pub struct UtilityPattern {
     /// CSS property to generate
     pub css_property: String,
 
+    /// All CSS properties this pattern expands to (e.g., `px-` sets both
+    /// "padding-left" and "padding-right"). Always contains `css_property`
+    /// first when set through the builder.
+    pub css_properties: Vec<String>,
+
     /// Layer this pattern belongs to
     pub layer: Layer,
 
-    /// Sort order
+    /// Sort order among utilities sharing the same variants; higher values
+    /// are emitted later and win the cascade
     pub sort_order: u32,
 
     /// Value mappings (e.g., "4" -> "1rem", "full" -> "100%")
@@ -156,6 +221,14 @@ pub struct UtilityPattern {
 
     /// Whether this pattern supports arbitrary values
     pub supports_arbitrary: bool,
+
+    /// Appended to the generated class selector (e.g., "::placeholder",
+    /// " > :not([hidden]) ~ :not([hidden])")
+    pub selector_suffix: Option<String>,
+
+    /// Handler that resolves matched classes into CSS. Patterns without one
+    /// look their value up in `values`, falling back to the arbitrary value.
+    pub handler: Option<HandlerRef>,
 }
 
 impl UtilityPattern {
@@ -166,11 +239,14 @@ impl UtilityPattern {
             prefix: prefix.into(),
             handler_id: 0,
             css_property: String::new(),
+            css_properties: Vec::new(),
             layer: Layer::Utilities,
             sort_order: 0,
             values: HashMap::new(),
             supports_negative: false,
             supports_arbitrary: true,
+            selector_suffix: None,
+            handler: None,
         }
     }
 
@@ -181,6 +257,17 @@ impl UtilityPattern {
 
     pub fn with_css_property(mut self, prop: impl Into<String>) -> Self {
         self.css_property = prop.into();
+        self.css_properties = vec![self.css_property.clone()];
+        self
+    }
+
+    pub fn with_css_properties<I, S>(mut self, props: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.css_properties = props.into_iter().map(Into::into).collect();
+        self.css_property = self.css_properties.first().cloned().unwrap_or_default();
         self
     }
 
@@ -193,6 +280,21 @@ impl UtilityPattern {
         self.supports_negative = true;
         self
     }
+
+    pub fn with_sort_order(mut self, sort_order: u32) -> Self {
+        self.sort_order = sort_order;
+        self
+    }
+
+    pub fn with_selector_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.selector_suffix = Some(suffix.into());
+        self
+    }
+
+    pub fn with_handler(mut self, handler: impl UtilityHandler + 'static) -> Self {
+        self.handler = Some(Arc::new(handler));
+        self
+    }
 }
 
 /// Variant definition
@@ -245,8 +347,29 @@ pub struct EngineConfig {
     /// Custom prefix (e.g., "tw-")
     pub prefix: Option<String>,
 
-    /// Important modifier
-    pub important: bool,
+    /// Important modifier, for every declaration or under a selector scope
+    pub important: Important,
+
+    /// How the `dark` variant is applied
+    #[serde(default)]
+    pub dark_mode: DarkMode,
+
+    /// Write responsive queries with range syntax (`width >= 768px`)
+    /// instead of `min-width`/`max-width`
+    #[serde(default)]
+    pub media_range_syntax: bool,
+
+    /// Merge rules with identical declarations into selector lists
+    #[serde(default)]
+    pub merge_rules: bool,
+
+    /// Precede each rule with the `file:line` of its class's first usage
+    #[serde(default)]
+    pub source_comments: bool,
+
+    /// Source map produced by `TurboEngine::process_files`
+    #[serde(default)]
+    pub source_map: SourceMapMode,
 }
 
 impl Default for EngineConfig {
@@ -256,11 +379,75 @@ impl Default for EngineConfig {
             cache_size: 10000,
             thread_count: num_cpus(),
             prefix: None,
-            important: false,
+            important: Important::default(),
+            dark_mode: DarkMode::default(),
+            media_range_syntax: false,
+            merge_rules: false,
+            source_comments: false,
+            source_map: SourceMapMode::Off,
         }
     }
 }
 
+/// Global important behavior
+///
+/// Deserializes from `true`/`false` or a selector string such as `"#app"`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Important {
+    /// When `true`, add `!important` to every declaration
+    Flag(bool),
+
+    /// Nest every selector under this one (e.g., "#app") to raise specificity
+    Selector(String),
+}
+
+impl Default for Important {
+    fn default() -> Self {
+        Important::Flag(false)
+    }
+}
+
+impl From<bool> for Important {
+    fn from(flag: bool) -> Self {
+        Important::Flag(flag)
+    }
+}
+
+impl From<&str> for Important {
+    fn from(selector: &str) -> Self {
+        Important::Selector(selector.to_string())
+    }
+}
+
+/// Strategy for the `dark` variant
+///
+/// Deserializes from `"media"` or a single-key object such as
+/// `{ "class": "dark" }` or `{ "attribute": "[data-theme=\"dark\"]" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DarkMode {
+    /// Ancestor with this class name (e.g., "dark" gives `.dark .x`)
+    Class(String),
+
+    /// Ancestor matching this attribute selector
+    /// (e.g., `[data-theme="dark"]` gives `[data-theme="dark"] .x`)
+    Attribute(String),
+
+    /// `@media (prefers-color-scheme: dark)`
+    Media,
+
+    /// Zero-specificity match against this selector on the element or an
+    /// ancestor (e.g., ".dark" gives `.x:where(.dark, .dark *)`)
+    Where(String),
+}
+
+impl Default for DarkMode {
+    fn default() -> Self {
+        DarkMode::Class("dark".to_string())
+    }
+}
+
 /// Get the number of CPUs for parallel processing
 fn num_cpus() -> usize {
     std::thread::available_parallelism()
@@ -281,20 +468,143 @@ pub struct ExtractionResult {
     pub time_us: u64,
 }
 
+/// Files to scan for class names on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContentConfig {
+    /// Directory to walk; globs are matched against paths relative to it
+    pub root: PathBuf,
+
+    /// Globs of files to scan (e.g., "src/**/*.{ts,tsx}"); empty scans every
+    /// file under `root`
+    pub include: Vec<String>,
+
+    /// Globs of files and directories to skip
+    pub exclude: Vec<String>,
+
+    /// Skip files ignored by `.gitignore`, `.ignore` and git excludes
+    pub gitignore: bool,
+
+    /// Skip files larger than this many bytes
+    pub max_file_size: u64,
+}
+
+impl Default for ContentConfig {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::from("."),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            gitignore: true,
+            max_file_size: 1024 * 1024,
+        }
+    }
+}
+
+/// Content to watch and where to write the CSS built from it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchConfig {
+    /// Files to extract classes from
+    pub content: ContentConfig,
+
+    /// File the stylesheet is written to after every change, if any
+    pub output: Option<PathBuf>,
+
+    /// Quiet period in milliseconds before a burst of file events is handled
+    pub debounce_ms: u64,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            content: ContentConfig::default(),
+            output: None,
+            debounce_ms: 50,
+        }
+    }
+}
+
+/// Position of a class occurrence in a source file
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SourceLocation {
+    /// File path as given to the extractor
+    pub file: String,
+
+    /// 1-based line
+    pub line: u32,
+
+    /// 1-based column, in UTF-16 code units as source maps count them
+    pub column: u32,
+}
+
+impl fmt::Display for SourceLocation {
+    /// `file:line`, as used in source comments
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// A class found by the extractor, with where it was found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassOccurrence {
+    pub class: String,
+    pub location: SourceLocation,
+}
+
+/// Which usages of a class a source map points its rule to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceMapMode {
+    /// No source map
+    #[default]
+    Off,
+
+    /// The first recorded usage of each class
+    First,
+
+    /// Every recorded usage of each class
+    All,
+}
+
 /// CSS generation options
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GenerateOptions {
     /// Minify output
     pub minify: bool,
 
-    /// Add source comments
+    /// Precede each rule with the `file:line` of its class's first usage
+    /// (needs source locations, see `Generator::generate_with_sources`)
     pub source_comments: bool,
 
-    /// Sort utilities by property
+    /// Break ties between utilities with equal variants and sort order by
+    /// their property count and names
     pub sort_by_property: bool,
 
     /// Use CSS layers
     pub use_layers: bool,
+
+    /// Global important behavior
+    #[serde(default)]
+    pub important: Important,
+
+    /// How the `dark` variant is applied
+    #[serde(default)]
+    pub dark_mode: DarkMode,
+
+    /// Write responsive queries with range syntax (`width >= 768px`)
+    /// instead of `min-width`/`max-width`
+    #[serde(default)]
+    pub media_range_syntax: bool,
+
+    /// Merge rules with identical declarations in the same at-rule context
+    /// into one rule with a selector list
+    #[serde(default)]
+    pub merge_rules: bool,
+
+    /// Source map produced alongside the CSS when source locations are given
+    #[serde(default)]
+    pub source_map: SourceMapMode,
 }
 
 impl Default for GenerateOptions {
@@ -304,6 +614,11 @@ impl Default for GenerateOptions {
             source_comments: false,
             sort_by_property: true,
             use_layers: true,
+            important: Important::default(),
+            dark_mode: DarkMode::default(),
+            media_range_syntax: false,
+            merge_rules: false,
+            source_map: SourceMapMode::Off,
         }
     }
 }
@@ -312,6 +627,25 @@ impl Default for GenerateOptions {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_dark_mode_serde() {
+        let parse = |json: &str| serde_json::from_str::<DarkMode>(json).unwrap();
+
+        assert_eq!(parse(r#""media""#), DarkMode::Media);
+        assert_eq!(parse(r#"{ "class": "night" }"#), DarkMode::Class("night".to_string()));
+        assert_eq!(
+            parse(r#"{ "attribute": "[data-theme=\"dark\"]" }"#),
+            DarkMode::Attribute(r#"[data-theme="dark"]"#.to_string())
+        );
+        assert_eq!(parse(r#"{ "where": ".dark" }"#), DarkMode::Where(".dark".to_string()));
+
+        let options: GenerateOptions = serde_json::from_str(
+            r#"{ "minify": false, "source_comments": false, "sort_by_property": true, "use_layers": true }"#,
+        )
+        .unwrap();
+        assert_eq!(options.dark_mode, DarkMode::Class("dark".to_string()));
+    }
+
     #[test]
     fn test_parsed_class_new() {
         let pc = ParsedClass::new("p-4");
@@ -396,10 +730,20 @@ mod tests {
         assert_eq!(pattern.prefix, "p-");
         assert_eq!(pattern.pattern, r"^p-\d+$");
         assert_eq!(pattern.css_property, "padding");
+        assert_eq!(pattern.css_properties, vec!["padding"]);
         assert!(pattern.supports_negative);
         assert!(pattern.supports_arbitrary);
     }
 
+    #[test]
+    fn test_utility_pattern_multiple_properties() {
+        let pattern = UtilityPattern::new("padding-x", "px-")
+            .with_css_properties(["padding-left", "padding-right"]);
+
+        assert_eq!(pattern.css_property, "padding-left");
+        assert_eq!(pattern.css_properties, vec!["padding-left", "padding-right"]);
+    }
+
     #[test]
     fn test_utility_pattern_with_values() {
         let mut values = HashMap::new();
@@ -420,7 +764,7 @@ mod tests {
         assert_eq!(config.cache_size, 10000);
         assert!(config.thread_count > 0);
         assert!(config.prefix.is_none());
-        assert!(!config.important);
+        assert_eq!(config.important, Important::Flag(false));
     }
 
     #[test]
@@ -477,6 +821,8 @@ mod tests {
             pattern_name: "padding".to_string(),
             layer: Layer::Utilities,
             sort_order: 0,
+            selector_suffix: None,
+            extra_rules: Vec::new(),
         };
 
         assert_eq!(result.pattern_name, "padding");
@@ -0,0 +1,498 @@
+//! Rebuilding CSS as content files change
+//!
+//! A `Watcher` scans the content directory once, then listens for file
+//! system events. Each debounced burst re-extracts only the files it names,
+//! patches a live stylesheet, and optionally rewrites the output file.
+//!
+//! Only directories the scan would enter are watched, one watch each, so
+//! ignored trees such as `node_modules` never use up the system's watches.
+
+use crate::content::{ContentFilter, GlobError, read_text};
+use crate::session::{ChangeSet, ClassChanges, ExtractionSession};
+use crate::stylesheet::{LiveStylesheet, StylesheetPatch};
+use crate::types::{ContentConfig, WatchConfig};
+use crate::TurboEngine;
+use notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use rustc_hash::FxHashSet;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Files whose edits change which files are content, forcing a rescan
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// Why a watcher could not start or an update failed
+#[derive(Debug)]
+pub enum WatchError {
+    /// An include or exclude glob does not compile
+    Glob(GlobError),
+
+    /// The file system watcher failed
+    Notify(notify::Error),
+
+    /// Reading the root or writing the output failed
+    Io(io::Error),
+}
+
+impl fmt::Display for WatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchError::Glob(err) => write!(f, "invalid glob: {}", err),
+            WatchError::Notify(err) => write!(f, "watch failed: {}", err),
+            WatchError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for WatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WatchError::Glob(err) => Some(err),
+            WatchError::Notify(err) => Some(err),
+            WatchError::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<GlobError> for WatchError {
+    fn from(err: GlobError) -> Self {
+        WatchError::Glob(err)
+    }
+}
+
+impl From<notify::Error> for WatchError {
+    fn from(err: notify::Error) -> Self {
+        WatchError::Notify(err)
+    }
+}
+
+impl From<io::Error> for WatchError {
+    fn from(err: io::Error) -> Self {
+        WatchError::Io(err)
+    }
+}
+
+/// One rebuild of the stylesheet
+#[derive(Debug, Clone)]
+pub struct WatchUpdate {
+    /// Content files re-read or removed; empty after a full scan
+    pub files: Vec<PathBuf>,
+
+    /// Classes that entered or left the project
+    pub changes: ClassChanges,
+
+    /// Rules to insert and delete (see `StylesheetPatch`)
+    pub patch: StylesheetPatch,
+
+    /// The whole stylesheet after the update
+    pub css: String,
+}
+
+/// Debouncer shared by the watcher and its worker, gone once stopped
+type SharedDebouncer = Arc<Mutex<Option<Debouncer<RecommendedWatcher>>>>;
+
+/// Watches content files and rebuilds CSS until stopped or dropped
+pub struct Watcher {
+    debouncer: SharedDebouncer,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Watcher {
+    /// Scan `config.content`, then keep rebuilding as its files change
+    ///
+    /// `on_update` receives the initial build before `start` returns, then
+    /// every later update from a background thread. Updates whose files
+    /// leave the class set unchanged are still reported, with an empty
+    /// patch; the output file is only rewritten when the stylesheet changes.
+    pub fn start<F>(engine: Arc<TurboEngine>, config: WatchConfig, mut on_update: F) -> Result<Self, WatchError>
+    where
+        F: FnMut(Result<WatchUpdate, WatchError>) + Send + 'static,
+    {
+        let mut content = config.content;
+        content.root = fs::canonicalize(&content.root)?;
+
+        // Absolute paths keep event paths and walked paths comparable, and
+        // the output must not be read back as content
+        let output = config.output.map(|output| absolute_output(&output)).transpose()?;
+        if let Some(relative) = output.as_deref().and_then(|output| output.strip_prefix(&content.root).ok()) {
+            content.exclude.push(globset::escape(&relative.to_string_lossy()));
+        }
+
+        // Watch before scanning, so edits made during the scan are not lost
+        let (tx, rx) = mpsc::channel::<DebounceEventResult>();
+        let debouncer = new_debouncer(Duration::from_millis(config.debounce_ms), tx)?;
+        let debouncer: SharedDebouncer = Arc::new(Mutex::new(Some(debouncer)));
+
+        let mut build = Build::new(engine, content, output, Arc::clone(&debouncer))?;
+        on_update(Ok(build.rescan()?));
+
+        let worker = thread::Builder::new()
+            .name("coral-turbo-watch".to_string())
+            .spawn(move || {
+                for events in rx {
+                    let paths = events.map(|events| events.into_iter().map(|event| event.path).collect());
+                    match paths.map_err(WatchError::from).and_then(|paths| build.update(paths)) {
+                        Ok(Some(update)) => on_update(Ok(update)),
+                        Ok(None) => {}
+                        Err(err) => on_update(Err(err)),
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            debouncer,
+            worker: Some(worker),
+        })
+    }
+
+    /// Stop watching, waiting for an update in progress to be delivered
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        // Dropping the debouncer closes the channel the worker reads
+        self.debouncer.lock().unwrap().take();
+
+        if let Some(worker) = self.worker.take() {
+            // Stopped from inside `on_update`: the worker exits on its own
+            if worker.thread().id() != thread::current().id() {
+                let _ = worker.join();
+            }
+        }
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// State carried from one update to the next
+struct Build {
+    engine: Arc<TurboEngine>,
+    content: ContentConfig,
+    filter: ContentFilter,
+    output: Option<PathBuf>,
+    session: ExtractionSession,
+    sheet: LiveStylesheet,
+    debouncer: SharedDebouncer,
+
+    /// Directories being watched
+    watched: FxHashSet<PathBuf>,
+
+    /// Whether the output file has been written yet
+    written: bool,
+}
+
+impl Build {
+    fn new(
+        engine: Arc<TurboEngine>,
+        content: ContentConfig,
+        output: Option<PathBuf>,
+        debouncer: SharedDebouncer,
+    ) -> Result<Self, WatchError> {
+        Ok(Self {
+            filter: ContentFilter::new(&content)?,
+            session: engine.extraction_session(),
+            sheet: engine.live_stylesheet(),
+            engine,
+            content,
+            output,
+            debouncer,
+            watched: FxHashSet::default(),
+            written: false,
+        })
+    }
+
+    /// Sync every content file with the disk
+    fn rescan(&mut self) -> Result<WatchUpdate, WatchError> {
+        self.filter = ContentFilter::new(&self.content)?;
+        let root = self.content.root.clone();
+        self.watch_dirs(&root)?;
+        let changes = self.engine.scan_session(&mut self.session, &self.content)?;
+        self.finish(Vec::new(), changes)
+    }
+
+    /// Re-extract the files at `paths`, or `None` when none is content
+    ///
+    /// Ignore file edits can change which files are content, so they fall
+    /// back to a full scan. Directories are walked only when no tracked file
+    /// lies under them (i.e., they were just created or moved in); otherwise
+    /// their files report their own changes.
+    fn update(&mut self, paths: Vec<PathBuf>) -> Result<Option<WatchUpdate>, WatchError> {
+        let rescan = paths
+            .iter()
+            .any(|path| path.file_name().is_some_and(|name| IGNORE_FILES.iter().any(|file| name == *file)));
+        if rescan {
+            return self.rescan().map(Some);
+        }
+
+        // New directories are watched before being walked, so files
+        // written into them meanwhile are not lost
+        let mut expanded = Vec::with_capacity(paths.len());
+        for path in paths {
+            if !path.is_dir() {
+                self.watched.retain(|dir| !dir.starts_with(&path));
+                expanded.push(path);
+            } else if !self.session.contains_dir(&path) {
+                self.watch_dirs(&path)?;
+                expanded.extend(self.filter.files_under(&path));
+            }
+        }
+        expanded.sort_unstable();
+        expanded.dedup();
+
+        let mut files = Vec::new();
+        let mut changes = ChangeSet::default();
+        for path in expanded {
+            let contents = if self.filter.selects(&path) {
+                read_text(&path, self.content.max_file_size)
+            } else {
+                None
+            };
+
+            let update = match contents {
+                Some(contents) => self.session.update_file(path.clone(), &contents),
+                None if self.session.contains_file(&path) => self.session.remove_file(&path),
+                // A deleted directory reports only its own path
+                None if self.session.contains_dir(&path) => self.session.remove_dir(&path),
+                None => continue,
+            };
+            files.push(path);
+            changes.merge(update);
+        }
+
+        if files.is_empty() {
+            return Ok(None);
+        }
+        self.finish(files, changes.into_changes()).map(Some)
+    }
+
+    /// Watch the directories under `dir` the scan would enter and that are
+    /// not watched yet
+    fn watch_dirs(&mut self, dir: &Path) -> Result<(), WatchError> {
+        let mut debouncer = self.debouncer.lock().unwrap();
+        let Some(debouncer) = debouncer.as_mut() else {
+            return Ok(());
+        };
+
+        for dir in self.filter.dirs_under(dir) {
+            if self.watched.contains(&dir) {
+                continue;
+            }
+            match debouncer.watcher().watch(&dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    self.watched.insert(dir);
+                }
+                // Removed since the walk; its parent reports that
+                Err(err) if matches!(err.kind, notify::ErrorKind::PathNotFound) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Patch the stylesheet and write it out if it changed
+    fn finish(&mut self, files: Vec<PathBuf>, changes: ClassChanges) -> Result<WatchUpdate, WatchError> {
+        let patch = self.sheet.apply(&changes);
+        let css = self.sheet.css();
+
+        if let Some(output) = &self.output {
+            if !self.written || !patch.is_empty() {
+                write_atomic(output, &css)?;
+                self.written = true;
+            }
+        }
+
+        Ok(WatchUpdate {
+            files,
+            changes,
+            patch,
+            css,
+        })
+    }
+}
+
+/// `output` made absolute through its (created) parent directory
+fn absolute_output(output: &Path) -> io::Result<PathBuf> {
+    let name = output
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "output is not a file path"))?;
+    let parent = match output.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    fs::create_dir_all(parent)?;
+    Ok(fs::canonicalize(parent)?.join(name))
+}
+
+/// Replace `path` with `contents` so readers never see a partial file
+///
+/// The contents go to a hidden sibling first (skipped as content), which is
+/// then renamed over `path`.
+fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(".tmp");
+    let temp = path.with_file_name(name);
+
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::Receiver;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn start(root: &Path, output: Option<PathBuf>) -> (Watcher, Receiver<Result<WatchUpdate, WatchError>>) {
+        let (tx, rx) = mpsc::channel();
+        let config = WatchConfig {
+            content: ContentConfig {
+                root: root.to_path_buf(),
+                ..Default::default()
+            },
+            output,
+            debounce_ms: 20,
+        };
+        let watcher = Watcher::start(Arc::new(TurboEngine::new()), config, move |update| {
+            let _ = tx.send(update);
+        })
+        .unwrap();
+        (watcher, rx)
+    }
+
+    /// Wait for an update reporting classes or files matching `done`
+    fn next_update(rx: &Receiver<Result<WatchUpdate, WatchError>>, done: impl Fn(&WatchUpdate) -> bool) -> WatchUpdate {
+        loop {
+            let update = rx.recv_timeout(TIMEOUT).expect("no update").unwrap();
+            if done(&update) {
+                return update;
+            }
+        }
+    }
+
+    #[test]
+    fn test_watch_rebuilds_on_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.html"), r#"class="p-4""#).unwrap();
+        let output = dir.path().join("dist/out.css");
+
+        let (watcher, rx) = start(dir.path(), Some(output.clone()));
+        let initial = rx.recv_timeout(TIMEOUT).unwrap().unwrap();
+        assert_eq!(initial.changes.added, vec!["p-4".to_string()]);
+        assert_eq!(fs::read_to_string(&output).unwrap(), initial.css);
+
+        fs::write(dir.path().join("b.html"), r#"class="m-2""#).unwrap();
+        let update = next_update(&rx, |update| !update.changes.is_empty());
+        assert_eq!(update.changes.added, vec!["m-2".to_string()]);
+        assert_eq!(update.patch.inserted.len(), 1);
+        assert!(update.files.iter().any(|file| file.ends_with("b.html")));
+        assert!(fs::read_to_string(&output).unwrap().contains(".m-2"));
+
+        fs::remove_file(dir.path().join("a.html")).unwrap();
+        let update = next_update(&rx, |update| !update.changes.is_empty());
+        assert_eq!(update.changes.removed, vec!["p-4".to_string()]);
+        assert!(!fs::read_to_string(&output).unwrap().contains(".p-4"));
+
+        // Deleting a directory releases the files tracked under it
+        fs::create_dir(dir.path().join("nested")).unwrap();
+        fs::write(dir.path().join("nested/c.html"), r#"class="gap-2""#).unwrap();
+        next_update(&rx, |update| update.changes.added.contains(&"gap-2".to_string()));
+        fs::remove_dir_all(dir.path().join("nested")).unwrap();
+        let update = next_update(&rx, |update| !update.changes.is_empty());
+        assert_eq!(update.changes.removed, vec!["gap-2".to_string()]);
+
+        // Directories created later are watched too, however deep
+        fs::create_dir_all(dir.path().join("nested/deeper")).unwrap();
+        thread::sleep(Duration::from_millis(100));
+        fs::write(dir.path().join("nested/deeper/d.html"), r#"class="gap-4""#).unwrap();
+        next_update(&rx, |update| update.changes.added.contains(&"gap-4".to_string()));
+        fs::write(dir.path().join("nested/deeper/d.html"), r#"class="gap-8""#).unwrap();
+        let update = next_update(&rx, |update| !update.changes.is_empty());
+        assert_eq!(update.changes.added, vec!["gap-8".to_string()]);
+
+        // Updates delivered before stopping may still be queued, but the
+        // worker is gone with its callback
+        watcher.stop();
+        rx.try_iter().for_each(drop);
+        assert_eq!(rx.try_recv().unwrap_err(), mpsc::TryRecvError::Disconnected);
+    }
+
+    #[test]
+    fn test_watch_skips_ignored_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".gitignore"), "node_modules/\n").unwrap();
+        fs::create_dir_all(dir.path().join("node_modules/pkg")).unwrap();
+        fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+
+        let root = fs::canonicalize(dir.path()).unwrap();
+        let content = ContentConfig {
+            root: root.clone(),
+            ..Default::default()
+        };
+        let debouncer = new_debouncer(Duration::from_millis(20), |_: DebounceEventResult| {}).unwrap();
+        let mut build = Build::new(
+            Arc::new(TurboEngine::new()),
+            content,
+            None,
+            Arc::new(Mutex::new(Some(debouncer))),
+        )
+        .unwrap();
+        build.rescan().unwrap();
+
+        let mut watched: Vec<PathBuf> = build.watched.iter().cloned().collect();
+        watched.sort();
+        assert_eq!(watched, vec![root.clone(), root.join("src"), root.join("src/nested")]);
+    }
+
+    #[test]
+    fn test_watch_invalid_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = WatchConfig {
+            content: ContentConfig {
+                root: dir.path().to_path_buf(),
+                include: vec!["[".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let result = Watcher::start(Arc::new(TurboEngine::new()), config, |_| {});
+        assert!(matches!(result, Err(WatchError::Glob(_))));
+
+        let config = WatchConfig {
+            content: ContentConfig {
+                root: dir.path().join("missing"),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let result = Watcher::start(Arc::new(TurboEngine::new()), config, |_| {});
+        assert!(matches!(result, Err(WatchError::Io(_))));
+    }
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.css");
+
+        write_atomic(&path, ".a{}").unwrap();
+        write_atomic(&path, ".b{}").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), ".b{}");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
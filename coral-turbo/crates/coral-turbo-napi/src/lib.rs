@@ -4,11 +4,16 @@
 //! in Node.js build tools like Vite, Webpack, etc.
 
 use coral_turbo_core::{
-    Extractor, Generator, Matcher, Parser, TurboEngine,
-    ParsedClass, MatchResult, GenerateOptions, ExtractionResult,
+    Extractor, Generator, Parser, TurboEngine,
+    ParsedClass, MatchResult, GenerateOptions, EngineConfig, Important, DarkMode, Theme, CacheStats,
+    ClassOccurrence, SourceIndex, SourceLocation, SourceMapMode, ContentConfig,
+    ClassChanges, ExtractionSession, LiveStylesheet, PatchedRule, StylesheetPatch,
+    WatchConfig, WatchUpdate, Watcher,
 };
-use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::Either;
 use napi_derive::napi;
+use std::path::Path;
 use std::sync::Arc;
 
 /// Engine version
@@ -28,6 +33,10 @@ pub struct JsParsedClass {
     pub arbitrary: Option<String>,
     pub important: bool,
     pub negative: bool,
+    pub source: Option<String>,
+    pub diagnostics: Vec<String>,
+    /// "utility" or "arbitrary-property"
+    pub kind: String,
 }
 
 impl From<ParsedClass> for JsParsedClass {
@@ -41,6 +50,9 @@ impl From<ParsedClass> for JsParsedClass {
             arbitrary: p.arbitrary,
             important: p.important,
             negative: p.negative,
+            source: p.source,
+            diagnostics: p.diagnostics,
+            kind: p.kind.as_str().to_string(),
         }
     }
 }
@@ -89,6 +101,105 @@ pub struct JsExtractionResult {
     pub time_us: u32,
 }
 
+/// Files to scan for class names; unset fields keep their defaults
+#[napi(object)]
+pub struct JsContentConfig {
+    pub root: String,
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+    pub gitignore: Option<bool>,
+    pub max_file_size: Option<u32>,
+}
+
+impl From<JsContentConfig> for ContentConfig {
+    fn from(c: JsContentConfig) -> Self {
+        let defaults = ContentConfig::default();
+        Self {
+            root: c.root.into(),
+            include: c.include.unwrap_or_default(),
+            exclude: c.exclude.unwrap_or_default(),
+            gitignore: c.gitignore.unwrap_or(defaults.gitignore),
+            max_file_size: c.max_file_size.map(u64::from).unwrap_or(defaults.max_file_size),
+        }
+    }
+}
+
+/// Content to watch and where to write the CSS; unset fields keep their
+/// defaults
+#[napi(object)]
+pub struct JsWatchConfig {
+    pub content: JsContentConfig,
+    pub output: Option<String>,
+    pub debounce_ms: Option<u32>,
+}
+
+impl From<JsWatchConfig> for WatchConfig {
+    fn from(c: JsWatchConfig) -> Self {
+        let defaults = WatchConfig::default();
+        Self {
+            content: c.content.into(),
+            output: c.output.map(Into::into),
+            debounce_ms: c.debounce_ms.map(u64::from).unwrap_or(defaults.debounce_ms),
+        }
+    }
+}
+
+/// A class occurrence with its 1-based line and column
+#[napi(object)]
+pub struct JsClassOccurrence {
+    pub class: String,
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl From<ClassOccurrence> for JsClassOccurrence {
+    fn from(o: ClassOccurrence) -> Self {
+        Self {
+            class: o.class,
+            file: o.location.file,
+            line: o.location.line,
+            column: o.location.column,
+        }
+    }
+}
+
+/// Generated CSS with its source map as JSON
+#[napi(object)]
+pub struct JsGeneratedCss {
+    pub css: String,
+    pub map: Option<String>,
+}
+
+/// Statistics for one cache
+#[napi(object)]
+pub struct JsCacheStats {
+    pub hits: i64,
+    pub misses: i64,
+    pub evictions: i64,
+    pub insertions: i64,
+    pub hit_rate: f64,
+}
+
+impl From<CacheStats> for JsCacheStats {
+    fn from(s: CacheStats) -> Self {
+        Self {
+            hits: s.hits as i64,
+            misses: s.misses as i64,
+            evictions: s.evictions as i64,
+            insertions: s.insertions as i64,
+            hit_rate: s.hit_rate(),
+        }
+    }
+}
+
+/// Statistics for the engine's parse and match caches
+#[napi(object)]
+pub struct JsEngineCacheStats {
+    pub parse: JsCacheStats,
+    pub matcher: JsCacheStats,
+}
+
 /// Main Turbo engine for Node.js
 #[napi]
 pub struct NapiTurboEngine {
@@ -105,6 +216,46 @@ impl NapiTurboEngine {
         }
     }
 
+    /// Create a TurboEngine using a theme given as JSON
+    #[napi(factory)]
+    pub fn with_theme(theme_json: String) -> napi::Result<Self> {
+        let theme = Theme::from_json(&theme_json)
+            .map_err(|e| napi::Error::from_reason(format!("Invalid theme: {}", e)))?;
+
+        Ok(Self {
+            engine: Arc::new(TurboEngine::with_theme(theme)),
+        })
+    }
+
+    /// Create a TurboEngine with a configuration, and optionally a theme
+    /// given as JSON
+    #[napi(factory)]
+    pub fn with_config(config: JsEngineConfig, theme_json: Option<String>) -> napi::Result<Self> {
+        let theme = match theme_json {
+            Some(json) => Theme::from_json(&json)
+                .map_err(|e| napi::Error::from_reason(format!("Invalid theme: {}", e)))?,
+            None => Theme::default(),
+        };
+
+        let defaults = EngineConfig::default();
+        let config = EngineConfig {
+            cache_enabled: config.cache_enabled.unwrap_or(defaults.cache_enabled),
+            cache_size: config.cache_size.map_or(defaults.cache_size, |size| size as usize),
+            thread_count: config.thread_count.map_or(defaults.thread_count, |count| count as usize),
+            prefix: config.prefix,
+            important: important(config.important),
+            dark_mode: dark_mode(config.dark_mode)?,
+            media_range_syntax: config.media_range_syntax.unwrap_or(false),
+            merge_rules: config.merge_rules.unwrap_or(false),
+            source_comments: config.source_comments.unwrap_or(false),
+            source_map: source_map_mode(config.source_map)?,
+        };
+
+        Ok(Self {
+            engine: Arc::new(TurboEngine::with_config_and_theme(config, theme)),
+        })
+    }
+
     /// Parse a class string into parsed class objects
     #[napi]
     pub fn parse(&self, class_string: String) -> Vec<JsParsedClass> {
@@ -147,6 +298,62 @@ impl NapiTurboEngine {
         let refs: Vec<&str> = contents.iter().map(|s| s.as_str()).collect();
         self.engine.extract_from_files(&refs)
     }
+
+    /// Scan files on disk, reading them in parallel on the Rust side
+    #[napi]
+    pub fn scan(&self, config: JsContentConfig) -> napi::Result<JsExtractionResult> {
+        let result = self
+            .engine
+            .scan(&config.into())
+            .map_err(|e| napi::Error::from_reason(format!("Invalid glob: {}", e)))?;
+
+        Ok(JsExtractionResult {
+            classes: result.classes,
+            file_count: result.file_count as u32,
+            time_us: result.time_us.min(u64::from(u32::MAX)) as u32,
+        })
+    }
+
+    /// Start an empty live stylesheet generating with this engine
+    #[napi]
+    pub fn live_stylesheet(&self) -> NapiLiveStylesheet {
+        NapiLiveStylesheet {
+            sheet: self.engine.live_stylesheet(),
+        }
+    }
+
+    /// Watch content files on a background thread, calling
+    /// `callback(err, update)` with the initial build and every rebuild
+    #[napi]
+    pub fn watch(&self, config: JsWatchConfig, callback: ThreadsafeFunction<JsWatchUpdate>) -> napi::Result<NapiWatcher> {
+        let watcher = Watcher::start(Arc::clone(&self.engine), config.into(), move |update| {
+            let update = update
+                .map(JsWatchUpdate::from)
+                .map_err(|e| napi::Error::from_reason(e.to_string()));
+            callback.call(update, ThreadsafeFunctionCallMode::NonBlocking);
+        })
+        .map_err(|e| napi::Error::from_reason(e.to_string()))?;
+
+        Ok(NapiWatcher {
+            watcher: Some(watcher),
+        })
+    }
+
+    /// Hit/miss statistics of the parse and match caches
+    #[napi]
+    pub fn cache_stats(&self) -> JsEngineCacheStats {
+        let stats = self.engine.cache_stats();
+        JsEngineCacheStats {
+            parse: stats.parse.into(),
+            matcher: stats.matcher.into(),
+        }
+    }
+
+    /// Drop all cached parse and match results
+    #[napi]
+    pub fn clear_caches(&self) {
+        self.engine.clear_caches();
+    }
 }
 
 impl Default for NapiTurboEngine {
@@ -228,6 +435,16 @@ impl NapiExtractor {
         let refs: Vec<&str> = contents.iter().map(|s| s.as_str()).collect();
         self.extractor.extract_parallel(&refs)
     }
+
+    /// Extract every class occurrence with its location in `file`
+    #[napi]
+    pub fn extract_locations(&self, file: String, content: String) -> Vec<JsClassOccurrence> {
+        self.extractor
+            .extract_locations(&file, &content)
+            .into_iter()
+            .map(|o| o.into())
+            .collect()
+    }
 }
 
 impl Default for NapiExtractor {
@@ -236,6 +453,209 @@ impl Default for NapiExtractor {
     }
 }
 
+/// Classes that entered or left an extraction session
+#[napi(object)]
+pub struct JsClassChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl From<ClassChanges> for JsClassChanges {
+    fn from(c: ClassChanges) -> Self {
+        Self {
+            added: c.added,
+            removed: c.removed,
+        }
+    }
+}
+
+/// Incremental extraction session for watch mode
+#[napi]
+pub struct NapiExtractionSession {
+    session: ExtractionSession,
+}
+
+#[napi]
+impl NapiExtractionSession {
+    /// Create an empty session
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Self {
+            session: ExtractionSession::new(),
+        }
+    }
+
+    /// Add or re-extract a file
+    #[napi]
+    pub fn update_file(&mut self, path: String, contents: String) -> JsClassChanges {
+        self.session.update_file(path, &contents).into()
+    }
+
+    /// Forget a file
+    #[napi]
+    pub fn remove_file(&mut self, path: String) -> JsClassChanges {
+        self.session.remove_file(Path::new(&path)).into()
+    }
+
+    /// Sync the session with the files on disk
+    #[napi]
+    pub fn scan(&mut self, config: JsContentConfig) -> napi::Result<JsClassChanges> {
+        self.session
+            .scan(&config.into())
+            .map(|changes| changes.into())
+            .map_err(|e| napi::Error::from_reason(format!("Invalid glob: {}", e)))
+    }
+
+    /// All classes used by tracked files, sorted
+    #[napi]
+    pub fn classes(&self) -> Vec<String> {
+        self.session.classes()
+    }
+
+    /// Number of tracked files
+    #[napi(getter)]
+    pub fn file_count(&self) -> u32 {
+        self.session.file_count() as u32
+    }
+}
+
+impl Default for NapiExtractionSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<JsClassChanges> for ClassChanges {
+    fn from(c: JsClassChanges) -> Self {
+        Self {
+            added: c.added,
+            removed: c.removed,
+        }
+    }
+}
+
+/// A rule inserted into or removed from a live stylesheet
+#[napi(object)]
+pub struct JsPatchedRule {
+    pub index: u32,
+    pub class: String,
+    pub css: String,
+}
+
+impl From<PatchedRule> for JsPatchedRule {
+    fn from(r: PatchedRule) -> Self {
+        Self {
+            index: r.index as u32,
+            class: r.class,
+            css: r.css,
+        }
+    }
+}
+
+/// Rule-level stylesheet update for HMR
+#[napi(object)]
+pub struct JsStylesheetPatch {
+    pub removed: Vec<JsPatchedRule>,
+    pub inserted: Vec<JsPatchedRule>,
+}
+
+impl From<StylesheetPatch> for JsStylesheetPatch {
+    fn from(p: StylesheetPatch) -> Self {
+        Self {
+            removed: p.removed.into_iter().map(|r| r.into()).collect(),
+            inserted: p.inserted.into_iter().map(|r| r.into()).collect(),
+        }
+    }
+}
+
+/// Stylesheet kept in sync with an extraction session, created by
+/// `NapiTurboEngine.liveStylesheet`
+#[napi]
+pub struct NapiLiveStylesheet {
+    sheet: LiveStylesheet,
+}
+
+#[napi]
+impl NapiLiveStylesheet {
+    /// Insert and delete rules for the classes a session update changed
+    #[napi]
+    pub fn apply(&mut self, changes: JsClassChanges) -> JsStylesheetPatch {
+        self.sheet.apply(&changes.into()).into()
+    }
+
+    /// The whole stylesheet
+    #[napi]
+    pub fn css(&self) -> String {
+        self.sheet.css()
+    }
+
+    /// Number of rules
+    #[napi(getter)]
+    pub fn rule_count(&self) -> u32 {
+        self.sheet.len() as u32
+    }
+}
+
+/// One rebuild of a watched stylesheet
+#[napi(object)]
+pub struct JsWatchUpdate {
+    /// Content files re-read or removed; empty after a full scan
+    pub files: Vec<String>,
+    pub changes: JsClassChanges,
+    pub patch: JsStylesheetPatch,
+    pub css: String,
+}
+
+impl From<WatchUpdate> for JsWatchUpdate {
+    fn from(u: WatchUpdate) -> Self {
+        Self {
+            files: u.files.iter().map(|f| f.to_string_lossy().into_owned()).collect(),
+            changes: u.changes.into(),
+            patch: u.patch.into(),
+            css: u.css,
+        }
+    }
+}
+
+/// Running file watcher, created by `NapiTurboEngine.watch`
+#[napi]
+pub struct NapiWatcher {
+    watcher: Option<Watcher>,
+}
+
+#[napi]
+impl NapiWatcher {
+    /// Stop watching once a rebuild in progress has been handed to the
+    /// callback
+    #[napi]
+    pub fn close(&mut self) {
+        if let Some(watcher) = self.watcher.take() {
+            watcher.stop();
+        }
+    }
+}
+
+/// Engine configuration for Node.js
+#[napi(object)]
+pub struct JsEngineConfig {
+    pub cache_enabled: Option<bool>,
+    pub cache_size: Option<u32>,
+    pub thread_count: Option<u32>,
+    pub prefix: Option<String>,
+    /// `true` for `!important` everywhere, or a scope selector such as "#app"
+    pub important: Option<Either<bool, String>>,
+    /// `"media"`, or `{ class }`, `{ attribute }` or `{ where }` with a selector
+    pub dark_mode: Option<serde_json::Value>,
+    /// Write responsive queries as `width >= 768px` instead of `min-width`
+    pub media_range_syntax: Option<bool>,
+    /// Merge rules with identical declarations into selector lists
+    pub merge_rules: Option<bool>,
+    /// Precede each rule with the `file:line` of its class's first usage
+    pub source_comments: Option<bool>,
+    /// `"off"`, `"first"` or `"all"` usages of each class
+    pub source_map: Option<String>,
+}
+
 /// Generator options for Node.js
 #[napi(object)]
 pub struct JsGenerateOptions {
@@ -243,6 +663,43 @@ pub struct JsGenerateOptions {
     pub source_comments: Option<bool>,
     pub sort_by_property: Option<bool>,
     pub use_layers: Option<bool>,
+    /// `true` for `!important` everywhere, or a scope selector such as "#app"
+    pub important: Option<Either<bool, String>>,
+    /// `"media"`, or `{ class }`, `{ attribute }` or `{ where }` with a selector
+    pub dark_mode: Option<serde_json::Value>,
+    /// Write responsive queries as `width >= 768px` instead of `min-width`
+    pub media_range_syntax: Option<bool>,
+    /// Merge rules with identical declarations into selector lists
+    pub merge_rules: Option<bool>,
+    /// `"off"`, `"first"` or `"all"` usages of each class
+    pub source_map: Option<String>,
+}
+
+/// Important behavior from a flag or a scope selector
+fn important(value: Option<Either<bool, String>>) -> Important {
+    match value {
+        Some(Either::A(flag)) => Important::Flag(flag),
+        Some(Either::B(selector)) => Important::Selector(selector),
+        None => Important::default(),
+    }
+}
+
+/// Dark mode from its JSON form
+fn dark_mode(value: Option<serde_json::Value>) -> napi::Result<DarkMode> {
+    match value {
+        Some(value) => serde_json::from_value::<DarkMode>(value)
+            .map_err(|e| napi::Error::from_reason(format!("Invalid dark mode: {}", e))),
+        None => Ok(DarkMode::default()),
+    }
+}
+
+/// Source map mode from its name
+fn source_map_mode(value: Option<String>) -> napi::Result<SourceMapMode> {
+    match value {
+        Some(mode) => serde_json::from_value::<SourceMapMode>(serde_json::Value::String(mode))
+            .map_err(|e| napi::Error::from_reason(format!("Invalid source map mode: {}", e))),
+        None => Ok(SourceMapMode::default()),
+    }
 }
 
 /// Standalone generator for Node.js
@@ -263,16 +720,56 @@ impl NapiGenerator {
 
     /// Create a Generator with custom options
     #[napi(factory)]
-    pub fn with_options(options: JsGenerateOptions) -> Self {
-        Self {
+    pub fn with_options(options: JsGenerateOptions) -> napi::Result<Self> {
+        let dark_mode = dark_mode(options.dark_mode)?;
+        let source_map = source_map_mode(options.source_map)?;
+
+        Ok(Self {
             generator: Generator::with_options(GenerateOptions {
                 minify: options.minify.unwrap_or(false),
                 source_comments: options.source_comments.unwrap_or(false),
                 sort_by_property: options.sort_by_property.unwrap_or(true),
                 use_layers: options.use_layers.unwrap_or(true),
+                important: important(options.important),
+                dark_mode,
+                media_range_syntax: options.media_range_syntax.unwrap_or(false),
+                merge_rules: options.merge_rules.unwrap_or(false),
+                source_map,
             }),
+        })
+    }
+
+    /// Generate CSS from match results (as JSON)
+    #[napi]
+    pub fn generate(&self, results_json: String) -> String {
+        match serde_json::from_str::<Vec<MatchResult>>(&results_json) {
+            Ok(results) => self.generator.generate(&results),
+            Err(_) => String::new(),
         }
     }
+
+    /// Generate CSS from match results (as JSON) with source comments and a
+    /// source map pointing at the given class occurrences
+    #[napi]
+    pub fn generate_with_sources(
+        &self,
+        results_json: String,
+        sources: Vec<JsClassOccurrence>,
+    ) -> napi::Result<JsGeneratedCss> {
+        let results = serde_json::from_str::<Vec<MatchResult>>(&results_json)
+            .map_err(|e| napi::Error::from_reason(format!("Invalid results: {}", e)))?;
+
+        let mut index = SourceIndex::new();
+        for o in sources {
+            index.insert(o.class, SourceLocation { file: o.file, line: o.line, column: o.column });
+        }
+
+        let output = self.generator.generate_with_sources(&results, &index);
+        Ok(JsGeneratedCss {
+            css: output.css,
+            map: output.map.map(|map| map.to_json()),
+        })
+    }
 }
 
 impl Default for NapiGenerator {
@@ -340,6 +837,40 @@ mod tests {
         assert!(classes.contains(&"p-4".to_string()));
     }
 
+    #[test]
+    fn test_engine_with_config() {
+        let config = JsEngineConfig {
+            cache_enabled: None,
+            cache_size: None,
+            thread_count: None,
+            prefix: None,
+            important: Some(Either::A(true)),
+            dark_mode: None,
+            media_range_syntax: None,
+            merge_rules: Some(true),
+            source_comments: None,
+            source_map: None,
+        };
+        let engine = NapiTurboEngine::with_config(config, None).unwrap();
+        let css = engine.process("flex md:flex".to_string());
+        assert!(css.contains("display: flex !important;"));
+
+        // Live stylesheets generate with the engine's configuration too
+        let expected = TurboEngine::with_config(EngineConfig {
+            important: Important::Flag(true),
+            merge_rules: true,
+            ..Default::default()
+        })
+        .process("flex md:flex");
+        assert_eq!(css, expected);
+        let mut sheet = engine.live_stylesheet();
+        sheet.apply(JsClassChanges {
+            added: vec!["flex".to_string(), "md:flex".to_string()],
+            removed: Vec::new(),
+        });
+        assert_eq!(sheet.css(), expected);
+    }
+
     #[test]
     fn test_parser() {
         let parser = NapiParser::new();
@@ -354,6 +885,17 @@ mod tests {
         assert!(classes.contains(&"text-red-500".to_string()));
     }
 
+    #[test]
+    fn test_generate_with_sources_rejects_invalid_results() {
+        let generator = NapiGenerator::new();
+        assert!(generator.generate_with_sources("[]".to_string(), Vec::new()).is_ok());
+
+        match generator.generate_with_sources("{".to_string(), Vec::new()) {
+            Err(err) => assert!(err.reason.starts_with("Invalid results")),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
     #[test]
     fn test_quick_functions() {
         let parsed = quick_parse("p-4 m-2".to_string());
@@ -4,10 +4,10 @@
 //! allowing it to be used in both Node.js and browser environments.
 
 use coral_turbo_core::{
-    Extractor, Generator, Matcher, Parser, TurboEngine,
-    ParsedClass, MatchResult, GenerateOptions,
+    Extractor, Generator, Parser, TurboEngine, MatchResult, GenerateOptions, Theme,
+    DarkMode, EngineConfig, Important, SourceMapMode,
 };
-use serde_wasm_bindgen;
+use serde::Deserialize;
 use wasm_bindgen::prelude::*;
 
 // Use wee_alloc as the global allocator for smaller WASM size
@@ -44,6 +44,34 @@ impl WasmTurboEngine {
         }
     }
 
+    /// Create a TurboEngine using a theme given as JSON
+    #[wasm_bindgen(js_name = "withTheme")]
+    pub fn with_theme(theme_json: &str) -> Result<WasmTurboEngine, JsValue> {
+        let theme = Theme::from_json(theme_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid theme: {}", e)))?;
+
+        Ok(Self {
+            engine: TurboEngine::with_theme(theme),
+        })
+    }
+
+    /// Create a TurboEngine from an engine config and an optional theme,
+    /// both given as JSON
+    #[wasm_bindgen(js_name = "withConfig")]
+    pub fn with_config(config_json: &str, theme_json: Option<String>) -> Result<WasmTurboEngine, JsValue> {
+        let config: WasmEngineConfig = serde_json::from_str(config_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid config: {}", e)))?;
+        let theme = match theme_json {
+            Some(json) => Theme::from_json(&json)
+                .map_err(|e| JsValue::from_str(&format!("Invalid theme: {}", e)))?,
+            None => Theme::default(),
+        };
+
+        Ok(Self {
+            engine: TurboEngine::with_config_and_theme(config.into(), theme),
+        })
+    }
+
     /// Parse a class string and return JSON array of parsed classes
     #[wasm_bindgen]
     pub fn parse(&self, class_string: &str) -> JsValue {
@@ -77,6 +105,18 @@ impl WasmTurboEngine {
         let refs: Vec<&str> = class_strings.iter().map(|s| s.as_str()).collect();
         self.engine.process_batch(&refs)
     }
+
+    /// Hit/miss statistics of the parse and match caches
+    #[wasm_bindgen(js_name = "cacheStats")]
+    pub fn cache_stats(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.engine.cache_stats()).unwrap_or(JsValue::NULL)
+    }
+
+    /// Drop all cached parse and match results
+    #[wasm_bindgen(js_name = "clearCaches")]
+    pub fn clear_caches(&self) {
+        self.engine.clear_caches();
+    }
 }
 
 impl Default for WasmTurboEngine {
@@ -201,6 +241,41 @@ impl Default for WasmGenerator {
     }
 }
 
+/// Engine config as the TypeScript `EngineConfig` writes it
+///
+/// `threadCount` is not read: WASM runs on a single thread.
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct WasmEngineConfig {
+    cache_enabled: Option<bool>,
+    cache_size: Option<usize>,
+    prefix: Option<String>,
+    important: Option<Important>,
+    dark_mode: Option<DarkMode>,
+    media_range_syntax: bool,
+    merge_rules: bool,
+    source_comments: bool,
+    source_map: SourceMapMode,
+}
+
+impl From<WasmEngineConfig> for EngineConfig {
+    fn from(config: WasmEngineConfig) -> Self {
+        let defaults = EngineConfig::default();
+        EngineConfig {
+            cache_enabled: config.cache_enabled.unwrap_or(defaults.cache_enabled),
+            cache_size: config.cache_size.unwrap_or(defaults.cache_size),
+            thread_count: 0,
+            prefix: config.prefix,
+            important: config.important.unwrap_or(defaults.important),
+            dark_mode: config.dark_mode.unwrap_or(defaults.dark_mode),
+            media_range_syntax: config.media_range_syntax,
+            merge_rules: config.merge_rules,
+            source_comments: config.source_comments,
+            source_map: config.source_map,
+        }
+    }
+}
+
 // Utility functions
 
 /// Quick parse function for simple use cases
@@ -242,6 +317,17 @@ mod tests {
         assert!(classes.contains(&"p-4".to_string()));
     }
 
+    #[wasm_bindgen_test]
+    fn test_engine_with_config() {
+        let config = r#"{"important": true, "darkMode": "media", "mergeRules": true}"#;
+        let engine = WasmTurboEngine::with_config(config, None).unwrap();
+        let css = engine.process("flex dark:flex");
+        assert!(css.contains("display: flex !important;"));
+        assert!(css.contains("prefers-color-scheme: dark"));
+
+        assert!(WasmTurboEngine::with_config(r#"{"darkMode": 1}"#, None).is_err());
+    }
+
     #[wasm_bindgen_test]
     fn test_parser() {
         let parser = WasmParser::new();
@@ -29,6 +29,7 @@ import type {
   Extractor,
   Generator,
   GenerateOptions,
+  Theme,
 } from './types';
 
 // Re-export types
@@ -144,14 +145,15 @@ export async function version(): Promise<string> {
  * Create a TurboEngine instance
  */
 export async function createEngine(
-  backend: Backend = 'auto'
+  backend: Backend = 'auto',
+  theme?: Theme
 ): Promise<TurboEngine> {
   const mod = await loadBackend(backend);
 
   if (currentBackend === 'native') {
-    return new (mod as typeof import('./native')).TurboEngine();
+    return new (mod as typeof import('./native')).TurboEngine(theme);
   } else {
-    return await (mod as typeof import('./wasm')).TurboEngine.create();
+    return await (mod as typeof import('./wasm')).TurboEngine.create(theme);
   }
 }
 
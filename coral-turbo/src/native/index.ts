@@ -11,7 +11,18 @@ import type {
   Extractor as IExtractor,
   Generator as IGenerator,
   GenerateOptions,
+  EngineConfig,
   MatchResult,
+  ClassOccurrence,
+  GeneratedCss,
+  Theme,
+  EngineCacheStats,
+  ContentConfig,
+  ExtractionResult,
+  ClassChanges,
+  StylesheetPatch,
+  WatchConfig,
+  WatchUpdate,
 } from '../types';
 
 // Native module will be loaded dynamically based on platform
@@ -69,9 +80,18 @@ export function version(): string {
 export class TurboEngine implements ITurboEngine {
   private engine: any;
 
-  constructor() {
+  constructor(theme?: Theme, config?: EngineConfig) {
     const native = loadNative();
-    this.engine = new native.NapiTurboEngine();
+    if (config) {
+      this.engine = native.NapiTurboEngine.withConfig(
+        config,
+        theme ? JSON.stringify(theme) : undefined
+      );
+    } else {
+      this.engine = theme
+        ? native.NapiTurboEngine.withTheme(JSON.stringify(theme))
+        : new native.NapiTurboEngine();
+    }
   }
 
   parse(classString: string): ParsedClass[] {
@@ -97,6 +117,32 @@ export class TurboEngine implements ITurboEngine {
   extractFromFiles(contents: string[]): string[] {
     return this.engine.extractFromFiles(contents);
   }
+
+  /** Scan files on disk without copying their contents across from JS */
+  scan(config: ContentConfig): ExtractionResult {
+    return this.engine.scan(config);
+  }
+
+  /** Start an empty stylesheet kept in sync with session changes */
+  liveStylesheet(): LiveStylesheet {
+    return new LiveStylesheet(this.engine.liveStylesheet());
+  }
+
+  /**
+   * Watch content files, rebuilding on a native background thread; the
+   * callback receives the initial build and every rebuild
+   */
+  watch(config: WatchConfig, callback: (err: Error | null, update: WatchUpdate) => void): Watcher {
+    return new Watcher(this.engine.watch(config, callback));
+  }
+
+  cacheStats(): EngineCacheStats {
+    return this.engine.cacheStats();
+  }
+
+  clearCaches(): void {
+    this.engine.clearCaches();
+  }
 }
 
 /**
@@ -141,6 +187,11 @@ export class Extractor implements IExtractor {
   extractParallel(contents: string[]): string[] {
     return this.extractor.extractParallel(contents);
   }
+
+  /** Every class occurrence in `content` with its location in `file` */
+  extractLocations(file: string, content: string): ClassOccurrence[] {
+    return this.extractor.extractLocations(file, content);
+  }
 }
 
 /**
@@ -157,6 +208,11 @@ export class Generator implements IGenerator {
         sourceComments: options.sourceComments,
         sortByProperty: options.sortByProperty,
         useLayers: options.useLayers,
+        important: options.important,
+        darkMode: options.darkMode,
+        mediaRangeSyntax: options.mediaRangeSyntax,
+        mergeRules: options.mergeRules,
+        sourceMap: options.sourceMap,
       });
     } else {
       this.generator = new native.NapiGenerator();
@@ -166,6 +222,90 @@ export class Generator implements IGenerator {
   generate(results: MatchResult[]): string {
     return this.generator.generate(JSON.stringify(results));
   }
+
+  /** Generate CSS with source comments and a source map from class usages */
+  generateWithSources(results: MatchResult[], sources: ClassOccurrence[]): GeneratedCss {
+    const output = this.generator.generateWithSources(JSON.stringify(results), sources);
+    return output.map == null ? { css: output.css } : output;
+  }
+}
+
+/**
+ * Native incremental extraction session, keyed by file path
+ */
+export class ExtractionSession {
+  private session: any;
+
+  constructor() {
+    const native = loadNative();
+    this.session = new native.NapiExtractionSession();
+  }
+
+  /** Add or re-extract a file; unchanged contents are skipped */
+  updateFile(path: string, contents: string): ClassChanges {
+    return this.session.updateFile(path, contents);
+  }
+
+  /** Forget a file, releasing the classes only it used */
+  removeFile(path: string): ClassChanges {
+    return this.session.removeFile(path);
+  }
+
+  /** Sync with the files on disk */
+  scan(config: ContentConfig): ClassChanges {
+    return this.session.scan(config);
+  }
+
+  /** All classes used by tracked files, sorted */
+  classes(): string[] {
+    return this.session.classes();
+  }
+
+  get fileCount(): number {
+    return this.session.fileCount;
+  }
+}
+
+/**
+ * Native stylesheet updated rule by rule, created by
+ * `TurboEngine.liveStylesheet`
+ */
+export class LiveStylesheet {
+  private sheet: any;
+
+  constructor(sheet: any) {
+    this.sheet = sheet;
+  }
+
+  /** Insert and delete rules for the classes a session update changed */
+  apply(changes: ClassChanges): StylesheetPatch {
+    return this.sheet.apply(changes);
+  }
+
+  /** The whole stylesheet */
+  css(): string {
+    return this.sheet.css();
+  }
+
+  get ruleCount(): number {
+    return this.sheet.ruleCount;
+  }
+}
+
+/**
+ * Native file watcher, created by `TurboEngine.watch`
+ */
+export class Watcher {
+  private watcher: any;
+
+  constructor(watcher: any) {
+    this.watcher = watcher;
+  }
+
+  /** Stop watching; the process can exit once every watcher is closed */
+  close(): void {
+    this.watcher.close();
+  }
 }
 
 // Quick functions (synchronous for native)
@@ -29,8 +29,22 @@ export interface ParsedClass {
 
   /** Negative prefix (-) */
   negative: boolean;
+
+  /** Variant group token this class was expanded from, if any */
+  source?: string;
+
+  /** Parse problems (e.g., an invalid arbitrary variant); such classes never match */
+  diagnostics?: string[];
+
+  /** Class kind; for arbitrary properties `utility` is the property name */
+  kind: ClassKind;
 }
 
+/**
+ * Kind of a parsed class
+ */
+export type ClassKind = 'utility' | 'arbitrary-property';
+
 /**
  * CSS property-value pair
  */
@@ -66,7 +80,7 @@ export interface GenerateOptions {
   /** Minify output */
   minify?: boolean;
 
-  /** Add source comments */
+  /** Precede each rule with the `file:line` of its class's first usage */
   sourceComments?: boolean;
 
   /** Sort utilities by property */
@@ -74,8 +88,62 @@ export interface GenerateOptions {
 
   /** Use CSS layers */
   useLayers?: boolean;
+
+  /** `true` for `!important` everywhere, or a scope selector such as "#app" */
+  important?: boolean | string;
+
+  /** How the `dark` variant is applied (default: `{ class: "dark" }`) */
+  darkMode?: DarkMode;
+
+  /** Write responsive queries as `width >= 768px` instead of `min-width` */
+  mediaRangeSyntax?: boolean;
+
+  /** Merge rules with identical declarations into selector lists */
+  mergeRules?: boolean;
+
+  /** Map each rule to the first or every usage of its class */
+  sourceMap?: SourceMapMode;
+}
+
+/**
+ * Which usages of a class a source map points its rule to
+ */
+export type SourceMapMode = 'off' | 'first' | 'all';
+
+/**
+ * A class found in a source file (1-based line and column)
+ */
+export interface ClassOccurrence {
+  class: string;
+  file: string;
+  line: number;
+  column: number;
+}
+
+/**
+ * Stylesheet with its Source Map v3 JSON
+ */
+export interface GeneratedCss {
+  css: string;
+
+  /** Present when `sourceMap` is enabled */
+  map?: string;
 }
 
+/**
+ * Dark mode strategy
+ *
+ * - `{ class }`: ancestor with this class name
+ * - `{ attribute }`: ancestor matching this attribute selector
+ * - `"media"`: `prefers-color-scheme: dark`
+ * - `{ where }`: zero-specificity `:where(sel, sel *)` on the element
+ */
+export type DarkMode =
+  | 'media'
+  | { class: string }
+  | { attribute: string }
+  | { where: string };
+
 /**
  * Extraction result from file scanning
  */
@@ -90,6 +158,125 @@ export interface ExtractionResult {
   timeUs: number;
 }
 
+/**
+ * Files to scan for class names on disk
+ */
+export interface ContentConfig {
+  /** Directory to walk; globs are relative to it */
+  root: string;
+
+  /** Globs of files to scan (e.g., "src/**\/*.{ts,tsx}"); default: all files */
+  include?: string[];
+
+  /** Globs of files and directories to skip */
+  exclude?: string[];
+
+  /** Skip files ignored by `.gitignore` (default: true) */
+  gitignore?: boolean;
+
+  /** Skip files larger than this many bytes (default: 1 MiB) */
+  maxFileSize?: number;
+}
+
+/**
+ * Content to watch and where to write the CSS built from it
+ */
+export interface WatchConfig {
+  /** Files to extract classes from */
+  content: ContentConfig;
+
+  /** File the stylesheet is written to (atomically) after every change */
+  output?: string;
+
+  /** Quiet period in milliseconds before a burst of file events is handled (default: 50) */
+  debounceMs?: number;
+}
+
+/**
+ * One rebuild of a watched stylesheet
+ */
+export interface WatchUpdate {
+  /** Content files re-read or removed; empty after a full scan */
+  files: string[];
+
+  changes: ClassChanges;
+  patch: StylesheetPatch;
+
+  /** The whole stylesheet after the update */
+  css: string;
+}
+
+/**
+ * Classes that entered or left an extraction session, sorted
+ */
+export interface ClassChanges {
+  added: string[];
+  removed: string[];
+}
+
+/**
+ * A rule inserted into or removed from a live stylesheet
+ */
+export interface PatchedRule {
+  /** Position in the rule list (see `StylesheetPatch`) */
+  index: number;
+
+  /** Class the rule was generated from */
+  class: string;
+
+  /** The rule on its own, wrapped in its layer and at-rules */
+  css: string;
+}
+
+/**
+ * Rule-level stylesheet update for HMR
+ *
+ * Apply `removed` in order (highest index first), then `inserted` in order
+ * (lowest index first), e.g. with `deleteRule` and `insertRule`.
+ */
+export interface StylesheetPatch {
+  removed: PatchedRule[];
+  inserted: PatchedRule[];
+}
+
+/**
+ * Design tokens; sections left out keep their defaults
+ */
+export interface Theme {
+  /** Spacing scale (e.g., { "4": "1rem" }) */
+  spacing?: Record<string, string>;
+
+  /** Colors, either a value or a map of shades */
+  colors?: Record<string, string | Record<string, string>>;
+
+  /** Breakpoint min-widths (e.g., { md: "768px" }) */
+  screens?: Record<string, string>;
+
+  /** Container query sizes (e.g., { md: "28rem" }) */
+  containers?: Record<string, string>;
+
+  /** Font sizes, optionally with a line height */
+  fontSize?: Record<string, string | [string, string]>;
+
+  /** Border radius scale */
+  borderRadius?: Record<string, string>;
+
+  /** Box shadow scale */
+  boxShadow?: Record<string, string>;
+
+  /** Z-index scale */
+  zIndex?: Record<string, string>;
+
+  /** Transition duration scale */
+  transitionDuration?: Record<string, string>;
+
+  /** Named aria-* states (e.g., { checked: 'checked="true"' }) */
+  aria?: Record<string, string>;
+
+  /** Named data-* states (e.g., { open: 'state="open"' }) */
+  data?: Record<string, string>;
+}
+
 /**
  * Engine configuration
  */
@@ -100,11 +287,29 @@ export interface EngineConfig {
   /** Maximum cache size */
   cacheSize?: number;
 
+  /** Worker threads for parallel processing (ignored by the WASM engine) */
+  threadCount?: number;
+
   /** Custom prefix (e.g., "tw-") */
   prefix?: string;
 
-  /** Important modifier */
-  important?: boolean;
+  /** `true` for `!important` everywhere, or a scope selector such as "#app" */
+  important?: boolean | string;
+
+  /** How the `dark` variant is applied (default: `{ class: "dark" }`) */
+  darkMode?: DarkMode;
+
+  /** Write responsive queries as `width >= 768px` instead of `min-width` */
+  mediaRangeSyntax?: boolean;
+
+  /** Merge rules with identical declarations into selector lists */
+  mergeRules?: boolean;
+
+  /** Precede each rule with the `file:line` of its class's first usage */
+  sourceComments?: boolean;
+
+  /** Map each rule to the first or every usage of its class */
+  sourceMap?: SourceMapMode;
 }
 
 /**
@@ -128,6 +333,33 @@ export interface TurboEngine {
 
   /** Extract from multiple file contents */
   extractFromFiles(contents: string[]): string[];
+
+  /** Hit/miss statistics of the parse and match caches */
+  cacheStats(): EngineCacheStats;
+
+  /** Drop all cached parse and match results */
+  clearCaches(): void;
+}
+
+/**
+ * Statistics for one cache
+ */
+export interface CacheStats {
+  hits: number;
+  misses: number;
+  evictions: number;
+  insertions: number;
+
+  /** Native backend only: hit rate as a percentage */
+  hitRate?: number;
+}
+
+/**
+ * Statistics for the engine's parse and match caches
+ */
+export interface EngineCacheStats {
+  parse: CacheStats;
+  matcher: CacheStats;
 }
 
 /**
@@ -11,7 +11,10 @@ import type {
   Extractor as IExtractor,
   Generator as IGenerator,
   GenerateOptions,
+  EngineConfig,
   MatchResult,
+  Theme,
+  EngineCacheStats,
 } from '../types';
 
 // WASM module will be loaded dynamically
@@ -71,9 +74,19 @@ export class TurboEngine implements ITurboEngine {
   /**
    * Create a new TurboEngine instance
    */
-  static async create(): Promise<TurboEngine> {
+  static async create(theme?: Theme, config?: EngineConfig): Promise<TurboEngine> {
     await init();
-    const engine = new wasmModule.WasmTurboEngine();
+    let engine;
+    if (config) {
+      engine = wasmModule.WasmTurboEngine.withConfig(
+        JSON.stringify(config),
+        theme ? JSON.stringify(theme) : undefined
+      );
+    } else {
+      engine = theme
+        ? wasmModule.WasmTurboEngine.withTheme(JSON.stringify(theme))
+        : new wasmModule.WasmTurboEngine();
+    }
     return new TurboEngine(engine);
   }
 
@@ -100,6 +113,14 @@ export class TurboEngine implements ITurboEngine {
   extractFromFiles(contents: string[]): string[] {
     return contents.flatMap((content) => this.extract(content));
   }
+
+  cacheStats(): EngineCacheStats {
+    return this.engine.cacheStats();
+  }
+
+  clearCaches(): void {
+    this.engine.clearCaches();
+  }
 }
 
 /**